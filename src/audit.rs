@@ -0,0 +1,97 @@
+use crate::data::stores::AuditStore;
+use chrono::{Local, TimeDelta};
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use surrealdb::Error;
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    static CONTEXT: AuditContext;
+}
+
+/// Who is behind a write, stored on its audit entry. Each request runs with
+/// its own context; writes made outside a request are the system's.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AuditContext {
+    /// The authenticated caller; unset until the API has authentication
+    pub actor: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn request(request_id: String) -> Self {
+        AuditContext {
+            actor: None,
+            request_id: Some(request_id),
+        }
+    }
+
+    /// Background tasks such as firing reminders.
+    pub fn system() -> Self {
+        AuditContext {
+            actor: Some("system".to_string()),
+            request_id: None,
+        }
+    }
+
+    /// The context of the running request, or the system's outside one.
+    pub fn current() -> Self {
+        CONTEXT
+            .try_with(AuditContext::clone)
+            .unwrap_or_else(|_| AuditContext::system())
+    }
+
+    /// The authenticated caller of the running request, if any.
+    pub fn caller() -> Option<String> {
+        CONTEXT
+            .try_with(|context| context.actor.clone())
+            .ok()
+            .flatten()
+    }
+
+    /// Runs `future` with this as the current context.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CONTEXT.scope(self, future).await
+    }
+}
+
+/// The current context as the `$audit` query parameter, which the audit
+/// events read when a query writes a todo, user or role.
+pub fn binding() -> (&'static str, AuditContext) {
+    ("audit", AuditContext::current())
+}
+
+/// Deletes audit entries older than `retention`, returning how many went.
+pub async fn prune(audit: &dyn AuditStore, retention: Duration) -> Result<usize, Error> {
+    // A retention reaching back before dates begin keeps everything
+    let Some(cutoff) = TimeDelta::from_std(retention)
+        .ok()
+        .and_then(|retention| Local::now().checked_sub_signed(retention))
+    else {
+        return Ok(0);
+    };
+    let pruned = audit.prune(cutoff).await?;
+    if pruned > 0 {
+        tracing::info!(pruned, %cutoff, "Pruned audit log");
+    }
+    Ok(pruned)
+}
+
+/// Prunes the audit log every `interval` until the runtime shuts down.
+pub fn spawn(
+    audit: Arc<dyn AuditStore>,
+    retention: Duration,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = prune(audit.as_ref(), retention).await {
+                tracing::error!(error = %e, "Failed to prune audit log");
+            }
+        }
+    })
+}
@@ -0,0 +1,109 @@
+use crate::data::contact::ContactNormalizer;
+use crate::outbox::{self, SinkKind};
+use crate::rate_limit::RateLimitConfig;
+use crate::retry::RetryPolicy;
+use crate::webhooks;
+use std::env;
+use std::time::Duration;
+
+/// Server settings loaded from the environment at startup.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub host: String,
+    pub port: String,
+    pub metrics_port: String,
+    pub allowed_origins: String,
+    pub contacts: ContactNormalizer,
+    /// How often the reminder task looks for due reminders
+    pub reminder_interval: Duration,
+    /// How often idle live feeds send a heartbeat
+    pub live_heartbeat: Duration,
+    /// How long audit entries are kept
+    pub audit_retention: Duration,
+    /// Whether `GET /api/audit`, which returns full snapshots of changed
+    /// records, is served
+    pub audit_api: bool,
+    /// Whether past versions of users, kept after they are deleted, are
+    /// served by `/api/users/{id}/history` and `as_of` reads
+    pub user_history_api: bool,
+    /// Whether user changes, with their contact details, are streamed by
+    /// `/api/users/live` and `/api/live`
+    pub user_live_api: bool,
+    /// Whether the `/api/webhooks` routes are served
+    pub webhooks_api: bool,
+    /// Whether webhooks may target loopback, private and link-local addresses
+    pub webhook_allow_private_targets: bool,
+    /// How often the webhook worker looks for due deliveries
+    pub webhook_interval: Duration,
+    pub webhook_retry: RetryPolicy,
+    /// How often the outbox dispatcher looks for new events
+    pub outbox_interval: Duration,
+    pub outbox_retry: RetryPolicy,
+    /// Where outbox events are published
+    pub outbox_sinks: Vec<SinkKind>,
+    /// How long published outbox events are kept
+    pub outbox_retention: Duration,
+    pub rate_limit: RateLimitConfig,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config {
+            host: env::var("HOST").unwrap_or("0.0.0.0".to_string()),
+            port: env::var("PORT").unwrap_or("8080".to_string()),
+            metrics_port: env::var("METRICS_PORT").unwrap_or("9090".to_string()),
+            allowed_origins: env::var("ALLOWED_ORIGINS")
+                .unwrap_or("http://localhost:3000".to_string()),
+            contacts: ContactNormalizer::from_env(),
+            reminder_interval: interval_secs("REMINDER_INTERVAL_SECS", 30),
+            live_heartbeat: interval_secs("LIVE_HEARTBEAT_SECS", 15),
+            audit_retention: Duration::from_secs(
+                env::var("AUDIT_RETENTION_DAYS")
+                    .ok()
+                    .and_then(|days| days.parse::<u64>().ok())
+                    .unwrap_or(90)
+                    .saturating_mul(24 * 60 * 60),
+            ),
+            audit_api: env::var("AUDIT_API_ENABLED")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            user_history_api: env::var("USER_HISTORY_API_ENABLED")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            user_live_api: env::var("USER_LIVE_API_ENABLED")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            webhooks_api: env::var("WEBHOOKS_API_ENABLED")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            webhook_allow_private_targets: env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            webhook_interval: interval_secs("WEBHOOK_INTERVAL_SECS", 5),
+            webhook_retry: RetryPolicy::from_env("WEBHOOK", webhooks::DEFAULT_RETRY),
+            outbox_interval: interval_secs("OUTBOX_INTERVAL_SECS", 1),
+            outbox_retry: RetryPolicy::from_env("OUTBOX", outbox::DEFAULT_RETRY),
+            outbox_sinks: SinkKind::from_env(),
+            outbox_retention: Duration::from_secs(
+                env::var("OUTBOX_RETENTION_HOURS")
+                    .ok()
+                    .and_then(|hours| hours.parse::<u64>().ok())
+                    .unwrap_or(24)
+                    .saturating_mul(60 * 60),
+            ),
+            rate_limit: RateLimitConfig::from_env(),
+        }
+    }
+}
+
+/// Reads a period in whole seconds from `var`, falling back to `default`.
+fn interval_secs(var: &str, default: u64) -> Duration {
+    Duration::from_secs(
+        env::var(var)
+            .ok()
+            .and_then(|secs| secs.parse::<u64>().ok())
+            .unwrap_or(default)
+            // tokio's interval panics on a zero period
+            .max(1),
+    )
+}
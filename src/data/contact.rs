@@ -0,0 +1,155 @@
+use std::env;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ContactError {
+    #[error("Invalid phone number: {0}")]
+    InvalidPhone(String),
+}
+
+/// Canonical forms for emails and phone numbers, applied before users are
+/// stored and to lookup input, so differently formatted values match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContactNormalizer {
+    /// Lowercase the part before `@` as well as the domain; off by default, as
+    /// mail servers may treat it as case-sensitive
+    pub lowercase_email_local_part: bool,
+    /// Calling code assumed for numbers written without a `+` prefix; without
+    /// one, such numbers are kept as their national digits
+    pub default_country_code: Option<String>,
+}
+
+impl ContactNormalizer {
+    pub fn from_env() -> Self {
+        ContactNormalizer {
+            lowercase_email_local_part: env::var("EMAIL_LOWERCASE_LOCAL_PART")
+                .map(|value| value.trim() == "true")
+                .unwrap_or(false),
+            default_country_code: env::var("PHONE_DEFAULT_COUNTRY_CODE")
+                .ok()
+                .map(|code| code.trim().trim_start_matches('+').to_string())
+                .filter(|code| !code.is_empty()),
+        }
+    }
+
+    /// Trims the address and lowercases its domain, and its local part too
+    /// unless that is switched off.
+    pub fn email(&self, raw: &str) -> String {
+        let email = raw.trim();
+        match email.rsplit_once('@') {
+            Some((local, domain)) if !self.lowercase_email_local_part => {
+                format!("{}@{}", local, domain.to_lowercase())
+            }
+            _ => email.to_lowercase(),
+        }
+    }
+
+    /// Converts a number to E.164, e.g. `(555) 555-0100` to `+15555550100`
+    /// with a default country code of 1. Numbers may start with `+` or `00`;
+    /// anything else is a national number and loses its trunk `0`, or is
+    /// only stripped of separators when there is no default country code.
+    pub fn phone(&self, raw: &str) -> Result<String, ContactError> {
+        let phone = raw.trim();
+        let (international, rest) = match phone.strip_prefix('+') {
+            Some(rest) => (true, rest),
+            None => match phone.strip_prefix("00") {
+                Some(rest) => (true, rest),
+                None => (false, phone),
+            },
+        };
+
+        let mut digits = String::with_capacity(rest.len());
+        for c in rest.chars() {
+            match c {
+                '0'..='9' => digits.push(c),
+                ' ' | '-' | '.' | '(' | ')' | '/' => {}
+                _ => return Err(ContactError::InvalidPhone(phone.to_string())),
+            }
+        }
+
+        let number = match (international, &self.default_country_code) {
+            (true, _) => digits,
+            (false, Some(code)) => format!("{}{}", code, digits.trim_start_matches('0')),
+            // Not E.164 without a country, but still accepted as before
+            (false, None) if (1..=15).contains(&digits.len()) => return Ok(digits),
+            (false, None) => return Err(ContactError::InvalidPhone(phone.to_string())),
+        };
+
+        // E.164 allows at most 15 digits and country codes never start with 0
+        if !(8..=15).contains(&number.len()) || number.starts_with('0') {
+            return Err(ContactError::InvalidPhone(phone.to_string()));
+        }
+        Ok(format!("+{}", number))
+    }
+
+    /// Normalises an optional phone number, treating a blank one as unset.
+    pub fn optional_phone(&self, raw: Option<&str>) -> Result<Option<String>, ContactError> {
+        match raw.map(str::trim) {
+            Some(phone) if !phone.is_empty() => self.phone(phone).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalizer() -> ContactNormalizer {
+        ContactNormalizer {
+            lowercase_email_local_part: true,
+            default_country_code: Some("1".to_string()),
+        }
+    }
+
+    #[test]
+    fn emails_are_trimmed_and_lowercased() {
+        assert_eq!(
+            normalizer().email("  Alice@Example.COM "),
+            "alice@example.com"
+        );
+
+        let keep_case = ContactNormalizer {
+            lowercase_email_local_part: false,
+            ..normalizer()
+        };
+        assert_eq!(keep_case.email("Alice@Example.COM"), "Alice@example.com");
+    }
+
+    #[test]
+    fn phones_are_converted_to_e164() {
+        let normalizer = normalizer();
+        assert_eq!(
+            normalizer.phone("+1 (555) 555-0100").unwrap(),
+            "+15555550100"
+        );
+        assert_eq!(normalizer.phone("(555) 555-0100").unwrap(), "+15555550100");
+        assert_eq!(
+            normalizer.phone("0044 20 7946 0958").unwrap(),
+            "+442079460958"
+        );
+        assert_eq!(normalizer.optional_phone(Some("  ")).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_phones_are_rejected() {
+        let normalizer = normalizer();
+        assert!(normalizer.phone("555-CALL-NOW").is_err());
+        assert!(normalizer.phone("+1 555").is_err());
+        assert!(normalizer.phone("+1234567890123456").is_err());
+    }
+
+    #[test]
+    fn only_the_email_domain_is_lowercased_by_default() {
+        let default = ContactNormalizer::default();
+        assert_eq!(default.email(" Alice@Example.COM "), "Alice@example.com");
+    }
+
+    #[test]
+    fn national_phones_keep_their_digits_without_a_default_country_code() {
+        let no_default = ContactNormalizer::default();
+        assert_eq!(no_default.phone("(555) 555-0100").unwrap(), "5555550100");
+        assert_eq!(no_default.phone("+1 555 555 0100").unwrap(), "+15555550100");
+        assert!(no_default.phone("()").is_err());
+    }
+}
@@ -1,24 +1,128 @@
-use crate::data;
+use crate::config::Config;
+use crate::data::repositories::{
+    audit_repository::AuditRepository, lists_repository::ListsRepository,
+    outbox_repository::OutboxRepository, rate_limits_repository::RateLimitsRepository,
+    roles_repository::RolesRepository, todos_repository::TodosRepository,
+    users_repository::UsersRepository, webhooks_repository::WebhooksRepository,
+};
+use crate::data::stores::{
+    AuditStore, ListStore, OutboxStore, RateLimitStore, RoleStore, TodoStore, UserStore,
+    WebhookStore,
+};
+use crate::rate_limit::{Backend, MemoryRateLimits};
+use std::fmt;
+use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct DataContext {
-    db: std::sync::Arc<crate::db::Database>,
+    todos: Arc<dyn TodoStore>,
+    users: Arc<dyn UserStore>,
+    roles: Arc<dyn RoleStore>,
+    lists: Arc<dyn ListStore>,
+    audit: Arc<dyn AuditStore>,
+    webhooks: Arc<dyn WebhookStore>,
+    outbox: Arc<dyn OutboxStore>,
+    rate_limits: Arc<dyn RateLimitStore>,
 }
 
 impl DataContext {
-    pub fn new(db: std::sync::Arc<crate::db::Database>) -> Self {
-        DataContext { db }
+    pub fn new(db: Arc<crate::db::Database>, config: &Config) -> Self {
+        DataContext {
+            todos: Arc::new(TodosRepository::new(db.clone())),
+            users: Arc::new(UsersRepository::new(db.clone(), config.contacts.clone())),
+            roles: Arc::new(RolesRepository::new(db.clone())),
+            lists: Arc::new(ListsRepository::new(db.clone())),
+            audit: Arc::new(AuditRepository::new(db.clone())),
+            webhooks: Arc::new(WebhooksRepository::new(db.clone())),
+            outbox: Arc::new(OutboxRepository::new(db.clone())),
+            rate_limits: match config.rate_limit.backend {
+                Backend::Memory => Arc::new(MemoryRateLimits::default()),
+                Backend::Surreal => Arc::new(RateLimitsRepository::new(db)),
+            },
+        }
     }
 
-    pub fn todos(&self) -> data::repositories::todos_repository::TodosRepository {
-        data::repositories::todos_repository::TodosRepository::new(self.db.clone())
+    /// Builds a context from arbitrary stores, e.g. in-memory ones in tests.
+    // One argument per store, like the fields
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_stores(
+        todos: Arc<dyn TodoStore>,
+        users: Arc<dyn UserStore>,
+        roles: Arc<dyn RoleStore>,
+        lists: Arc<dyn ListStore>,
+        audit: Arc<dyn AuditStore>,
+        webhooks: Arc<dyn WebhookStore>,
+        outbox: Arc<dyn OutboxStore>,
+        rate_limits: Arc<dyn RateLimitStore>,
+    ) -> Self {
+        DataContext {
+            todos,
+            users,
+            roles,
+            lists,
+            audit,
+            webhooks,
+            outbox,
+            rate_limits,
+        }
     }
 
-    pub fn users(&self) -> data::repositories::users_repository::UsersRepository {
-        data::repositories::users_repository::UsersRepository::new(self.db.clone())
+    /// A context backed by empty in-memory stores.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn in_memory() -> Self {
+        use crate::data::repositories::memory_repository::{
+            InMemoryAudit, InMemoryLists, InMemoryOutbox, InMemoryRoles, InMemoryTodos,
+            InMemoryUsers, InMemoryWebhooks,
+        };
+
+        let todos = Arc::new(InMemoryTodos::default());
+        DataContext::from_stores(
+            todos.clone(),
+            Arc::new(InMemoryUsers::default()),
+            Arc::new(InMemoryRoles::default()),
+            Arc::new(InMemoryLists::new(todos)),
+            Arc::new(InMemoryAudit),
+            Arc::new(InMemoryWebhooks::default()),
+            Arc::new(InMemoryOutbox),
+            Arc::new(MemoryRateLimits::default()),
+        )
+    }
+
+    pub fn todos(&self) -> Arc<dyn TodoStore> {
+        self.todos.clone()
+    }
+
+    pub fn users(&self) -> Arc<dyn UserStore> {
+        self.users.clone()
     }
 
-    pub fn roles(&self) -> data::repositories::roles_repository::RolesRepository {
-        data::repositories::roles_repository::RolesRepository::new(self.db.clone())
+    pub fn roles(&self) -> Arc<dyn RoleStore> {
+        self.roles.clone()
     }
-}
\ No newline at end of file
+
+    pub fn lists(&self) -> Arc<dyn ListStore> {
+        self.lists.clone()
+    }
+
+    pub fn audit(&self) -> Arc<dyn AuditStore> {
+        self.audit.clone()
+    }
+
+    pub fn webhooks(&self) -> Arc<dyn WebhookStore> {
+        self.webhooks.clone()
+    }
+
+    pub fn outbox(&self) -> Arc<dyn OutboxStore> {
+        self.outbox.clone()
+    }
+
+    pub fn rate_limits(&self) -> Arc<dyn RateLimitStore> {
+        self.rate_limits.clone()
+    }
+}
+
+impl fmt::Debug for DataContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DataContext").finish_non_exhaustive()
+    }
+}
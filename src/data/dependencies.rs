@@ -0,0 +1,80 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A dependency edge: the todo with id `todo` is blocked by the one with id `blocker`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Dependency {
+    pub todo: String,
+    pub blocker: String,
+}
+
+/// The shortest chain of dependencies leading from `from` to `to`, both ends
+/// included, or `None` when `from` does not depend on `to` even indirectly.
+///
+/// Making `todo` wait on `blocker` closes a cycle exactly when there is already
+/// a path from `blocker` to `todo`.
+pub fn dependency_path(edges: &[Dependency], from: &str, to: &str) -> Option<Vec<String>> {
+    let mut blockers: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in edges {
+        blockers
+            .entry(edge.todo.as_str())
+            .or_default()
+            .push(edge.blocker.as_str());
+    }
+
+    let mut came_from: HashMap<&str, &str> = HashMap::new();
+    let mut seen = HashSet::from([from]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut path = vec![current.to_string()];
+            let mut step = current;
+            while let Some(previous) = came_from.get(step) {
+                path.push(previous.to_string());
+                step = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in blockers.get(current).into_iter().flatten() {
+            if seen.insert(next) {
+                came_from.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(pairs: &[(&str, &str)]) -> Vec<Dependency> {
+        pairs
+            .iter()
+            .map(|(todo, blocker)| Dependency {
+                todo: todo.to_string(),
+                blocker: blocker.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_indirect_dependencies() {
+        let edges = edges(&[("a", "b"), ("b", "c"), ("c", "d"), ("a", "x")]);
+        assert_eq!(
+            dependency_path(&edges, "a", "d"),
+            Some(vec!["a".into(), "b".into(), "c".into(), "d".into()])
+        );
+        assert_eq!(dependency_path(&edges, "d", "a"), None);
+        assert_eq!(dependency_path(&edges, "x", "b"), None);
+    }
+
+    #[test]
+    fn terminates_on_existing_cycles() {
+        let edges = edges(&[("a", "b"), ("b", "a")]);
+        assert_eq!(dependency_path(&edges, "a", "c"), None);
+        assert_eq!(dependency_path(&edges, "a", "a"), Some(vec!["a".into()]));
+    }
+}
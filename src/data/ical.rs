@@ -0,0 +1,390 @@
+use crate::data::models::todo::{CreateTodo, Priority, Todo};
+use crate::data::recurrence::at_local;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// `Content-Type` of rendered calendars.
+pub const CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+const PRODID: &str = "-//rss-boilerplate//todos//EN";
+
+/// Content lines longer than this many octets are folded (RFC 5545 §3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders `todos` as a calendar of `VTODO` components.
+pub fn render(todos: &[Todo], now: DateTime<Local>) -> String {
+    let mut calendar = Calendar::default();
+    calendar.line("BEGIN", "VCALENDAR");
+    calendar.line("VERSION", "2.0");
+    calendar.line("PRODID", PRODID);
+    for todo in todos {
+        calendar.line("BEGIN", "VTODO");
+        let id = todo
+            .id
+            .as_ref()
+            .map(|id| id.id.to_raw())
+            .unwrap_or_default();
+        calendar.line("UID", &format!("{}@rss-boilerplate", id));
+        calendar.line(
+            "DTSTAMP",
+            &format_time(todo.updated_at.or(todo.created_at).unwrap_or(now)),
+        );
+        if let Some(created_at) = todo.created_at {
+            calendar.line("CREATED", &format_time(created_at));
+        }
+        if let Some(updated_at) = todo.updated_at {
+            calendar.line("LAST-MODIFIED", &format_time(updated_at));
+        }
+        calendar.line("SUMMARY", &escape(&todo.title));
+        if let Some(content) = todo
+            .content
+            .as_deref()
+            .filter(|content| !content.is_empty())
+        {
+            calendar.line("DESCRIPTION", &escape(content));
+        }
+        if let Some(due_at) = todo.due_at {
+            calendar.line("DUE", &format_time(due_at));
+        }
+        if todo.completed == Some(true) {
+            calendar.line("STATUS", "COMPLETED");
+            if let Some(completed_at) = todo.completed_at {
+                calendar.line("COMPLETED", &format_time(completed_at));
+            }
+        } else {
+            calendar.line("STATUS", "NEEDS-ACTION");
+        }
+        calendar.line(
+            "PRIORITY",
+            &priority_level(todo.priority.unwrap_or_default()).to_string(),
+        );
+        if let Some(tags) = todo.tags.as_deref().filter(|tags| !tags.is_empty()) {
+            let tags: Vec<String> = tags.iter().map(|tag| escape(tag)).collect();
+            calendar.line("CATEGORIES", &tags.join(","));
+        }
+        if let Some(recurrence) = todo.recurrence.as_deref() {
+            calendar.line("RRULE", recurrence);
+        }
+        calendar.line("END", "VTODO");
+    }
+    calendar.line("END", "VCALENDAR");
+    calendar.0
+}
+
+/// Reads every `VTODO` in `text` as a todo to create. The outer error means
+/// the text is not a calendar at all; the inner ones reject single components.
+pub fn parse(text: &str) -> Result<Vec<Result<CreateTodo, String>>, String> {
+    let mut todos = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut current: Option<Result<CreateTodo, String>> = None;
+
+    for (number, line) in unfold(text).into_iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = Property::parse(&line)
+            .ok_or_else(|| format!("Line {} is not a valid content line", number + 1))?;
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.to_ascii_uppercase();
+                if components.is_empty() && component != "VCALENDAR" {
+                    return Err("Calendar must start with BEGIN:VCALENDAR".to_string());
+                }
+                if component == "VTODO" && components.len() == 1 {
+                    current = Some(Ok(CreateTodo::default()));
+                }
+                components.push(component);
+            }
+            "END" => {
+                let component = property.value.to_ascii_uppercase();
+                if components.pop().as_deref() != Some(component.as_str()) {
+                    return Err(format!(
+                        "END:{} on line {} does not close the open component",
+                        component,
+                        number + 1
+                    ));
+                }
+                if component == "VTODO" && components.len() == 1 {
+                    if let Some(todo) = current.take() {
+                        todos.push(todo.and_then(|todo| {
+                            if todo.title.is_empty() {
+                                Err("VTODO has no SUMMARY".to_string())
+                            } else {
+                                Ok(todo)
+                            }
+                        }));
+                    }
+                }
+            }
+            _ => {
+                // Properties of the todo itself, not of nested alarms
+                if components.len() == 2 {
+                    if let Some(Ok(todo)) = current.as_mut() {
+                        if let Err(message) = apply(todo, &property) {
+                            current = Some(Err(message));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !components.is_empty() {
+        return Err(format!("Calendar ends inside {}", components.join(" > ")));
+    }
+    if todos.is_empty() && !text.to_ascii_uppercase().contains("BEGIN:VCALENDAR") {
+        return Err("Calendar must start with BEGIN:VCALENDAR".to_string());
+    }
+    Ok(todos)
+}
+
+/// Maps one `VTODO` property onto the todo being built.
+fn apply(todo: &mut CreateTodo, property: &Property) -> Result<(), String> {
+    match property.name.as_str() {
+        "SUMMARY" => todo.title = unescape(&property.value),
+        "DESCRIPTION" => todo.content = Some(unescape(&property.value)),
+        "DUE" => {
+            let date_only = property.param("VALUE").is_some_and(|value| value == "DATE");
+            todo.due_at = Some(
+                parse_time(&property.value, date_only)
+                    .ok_or_else(|| format!("Invalid DUE `{}`", property.value))?,
+            );
+        }
+        "STATUS" => todo.completed = Some(property.value.eq_ignore_ascii_case("COMPLETED")),
+        "PRIORITY" => {
+            let level = property
+                .value
+                .parse()
+                .ok()
+                .filter(|level| *level <= 9)
+                .ok_or_else(|| format!("Invalid PRIORITY `{}`", property.value))?;
+            todo.priority = Some(priority_from_level(level));
+        }
+        "CATEGORIES" => todo
+            .tags
+            .get_or_insert_with(Vec::new)
+            .extend(split_list(&property.value)),
+        "RRULE" => todo.recurrence = Some(property.value.clone()),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// iCalendar priorities run from 1 (highest) to 9 (lowest), 0 meaning unset.
+fn priority_level(priority: Priority) -> u8 {
+    match priority {
+        Priority::Urgent => 1,
+        Priority::High => 3,
+        Priority::Normal => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn priority_from_level(level: u8) -> Priority {
+    match level {
+        1..=2 => Priority::Urgent,
+        3..=4 => Priority::High,
+        6..=9 => Priority::Low,
+        _ => Priority::Normal,
+    }
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Parses UTC (`...Z`), floating and date-only values. Times in a `TZID` are
+/// read as server-local, since the server has no timezone database.
+fn parse_time(value: &str, date_only: bool) -> Option<DateTime<Local>> {
+    if date_only || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return at_local(date.and_hms_opt(0, 0, 0)?);
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+    at_local(NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Splits a comma-separated value, honouring escaped commas.
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ',' if !escaped => {
+                items.push(unescape(&value[start..index]));
+                start = index + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    items.push(unescape(&value[start..]));
+    items
+}
+
+/// Joins folded lines back together: a line starting with a space or tab
+/// continues the one before it.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// One content line: `NAME;PARAM=VALUE:value`.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(index, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(index),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.to_ascii_uppercase(),
+                    value.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(Property {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A calendar being written with CRLF line endings and folded long lines.
+#[derive(Default)]
+struct Calendar(String);
+
+impl Calendar {
+    fn line(&mut self, name: &str, value: &str) {
+        let line = format!("{}:{}", name, value);
+        let mut octets = 0;
+        for c in line.chars() {
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                self.0.push_str("\r\n ");
+                octets = 1;
+            }
+            self.0.push(c);
+            octets += c.len_utf8();
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_and_unfolds_long_lines() {
+        let mut calendar = Calendar::default();
+        let summary = "é".repeat(60);
+        calendar.line("SUMMARY", &summary);
+        assert!(calendar.0.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert_eq!(unfold(&calendar.0)[0], format!("SUMMARY:{}", summary));
+    }
+
+    #[test]
+    fn escapes_text_round_trip() {
+        let text = "Milk, eggs; bread\\butter\nand jam";
+        assert_eq!(unescape(&escape(text)), text);
+        assert_eq!(split_list("home,bills\\,misc"), vec!["home", "bills,misc"]);
+    }
+
+    #[test]
+    fn parses_todos_and_skips_other_components() {
+        let text = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Meeting\r\nEND:VEVENT\r\n\
+            BEGIN:VTODO\r\nSUMMARY:Pay\r\n  rent\r\nDUE;VALUE=DATE:20300107\r\n\
+            PRIORITY:2\r\nSTATUS:COMPLETED\r\n\
+            BEGIN:VALARM\r\nDESCRIPTION:Alarm\r\nEND:VALARM\r\nEND:VTODO\r\n\
+            BEGIN:VTODO\r\nDUE:soon\r\nEND:VTODO\r\n\
+            BEGIN:VTODO\r\nDESCRIPTION:No title\r\nEND:VTODO\r\n\
+            END:VCALENDAR\r\n";
+        let todos = parse(text).unwrap();
+        assert_eq!(todos.len(), 3);
+
+        let todo = todos[0].as_ref().unwrap();
+        assert_eq!(todo.title, "Pay rent");
+        assert_eq!(todo.content, None);
+        assert_eq!(todo.priority, Some(Priority::Urgent));
+        assert_eq!(todo.completed, Some(true));
+        assert_eq!(
+            todo.due_at
+                .map(|due_at| due_at.format("%Y-%m-%d %H:%M").to_string()),
+            Some("2030-01-07 00:00".to_string())
+        );
+        assert_eq!(todos[1].as_ref().unwrap_err(), "Invalid DUE `soon`");
+        assert_eq!(todos[2].as_ref().unwrap_err(), "VTODO has no SUMMARY");
+    }
+
+    #[test]
+    fn rejects_malformed_calendars() {
+        assert!(parse("hello").is_err());
+        assert!(parse("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nEND:VCALENDAR\r\n").is_err());
+        assert!(parse("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n").is_err());
+    }
+}
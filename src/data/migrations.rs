@@ -0,0 +1,142 @@
+use crate::data::contact::ContactNormalizer;
+use crate::data::models::user::User;
+use crate::db::Database;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use surrealdb::Error;
+
+/// A row in the `migration` table, keyed by the migration's name.
+#[derive(Debug, Serialize, Deserialize)]
+struct Applied {
+    applied_at: DateTime<Local>,
+}
+
+/// Runs the migrations not yet recorded in the `migration` table, recording
+/// each once it has completed.
+pub async fn run(db: &Database, contacts: &ContactNormalizer) -> Result<(), Error> {
+    if !applied(db, "normalize_contacts").await? {
+        let report = normalize_contacts(db, contacts).await?;
+        // Collisions hold back the unique index, so try again next start
+        if report.unique_email_index {
+            record(db, "normalize_contacts").await?;
+        }
+    }
+    Ok(())
+}
+
+async fn applied(db: &Database, name: &str) -> Result<bool, Error> {
+    let applied: Option<Applied> = db.client.select(("migration", name)).await?;
+    Ok(applied.is_some())
+}
+
+async fn record(db: &Database, name: &str) -> Result<(), Error> {
+    let _: Option<Applied> = db
+        .client
+        .create(("migration", name))
+        .content(Applied {
+            applied_at: Local::now(),
+        })
+        .await?;
+    tracing::info!(migration = name, "Recorded migration as applied");
+    Ok(())
+}
+
+/// Users whose stored emails become the same address once normalised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EmailCollision {
+    pub email: String,
+    pub user_ids: Vec<String>,
+}
+
+/// What `normalize_contacts` changed and what it left for a human to resolve.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContactMigrationReport {
+    /// Users whose email or phone was rewritten
+    pub updated: usize,
+    pub collisions: Vec<EmailCollision>,
+    /// Users whose phone could not be converted and was left as is
+    pub invalid_phones: Vec<String>,
+    /// Whether the unique email index is in place
+    pub unique_email_index: bool,
+}
+
+/// Rewrites stored emails and phone numbers into their canonical forms, then
+/// adds the unique index on email. Users whose emails collide are left
+/// untouched and reported, and the index waits until they are resolved.
+pub async fn normalize_contacts(
+    db: &Database,
+    contacts: &ContactNormalizer,
+) -> Result<ContactMigrationReport, Error> {
+    let users: Vec<User> = db.client.select("user").await?;
+
+    let mut by_email: BTreeMap<String, Vec<User>> = BTreeMap::new();
+    for user in users {
+        by_email
+            .entry(contacts.email(&user.email))
+            .or_default()
+            .push(user);
+    }
+
+    let mut report = ContactMigrationReport::default();
+    for (email, users) in by_email {
+        if users.len() > 1 {
+            let collision = EmailCollision {
+                email,
+                user_ids: users.iter().filter_map(record_key).collect(),
+            };
+            tracing::warn!(
+                email = %collision.email,
+                user_ids = ?collision.user_ids,
+                "Users share an email once normalised"
+            );
+            report.collisions.push(collision);
+            continue;
+        }
+
+        let user = &users[0];
+        let Some(id) = record_key(user) else { continue };
+        let phone = match contacts.optional_phone(user.phone.as_deref()) {
+            Ok(phone) => phone,
+            Err(e) => {
+                tracing::warn!(user_id = %id, error = %e, "Leaving unconvertible phone number");
+                report.invalid_phones.push(id.clone());
+                user.phone.clone()
+            }
+        };
+        if email == user.email && phone == user.phone {
+            continue;
+        }
+
+        let _: Option<User> = db
+            .client
+            .update(("user", id))
+            .merge(serde_json::json!({ "email": email, "phone": phone }))
+            .await?;
+        report.updated += 1;
+    }
+
+    if report.collisions.is_empty() {
+        db.client
+            .query("DEFINE INDEX IF NOT EXISTS user_email_unique ON user FIELDS email UNIQUE")
+            .await?
+            .check()?;
+        report.unique_email_index = true;
+    } else {
+        tracing::warn!(
+            collisions = report.collisions.len(),
+            "Unique email index not created until collisions are resolved"
+        );
+    }
+
+    tracing::info!(
+        updated = report.updated,
+        invalid_phones = report.invalid_phones.len(),
+        "Normalised user contacts"
+    );
+    Ok(report)
+}
+
+fn record_key(user: &User) -> Option<String> {
+    user.id.as_ref().map(|id| id.id.to_raw())
+}
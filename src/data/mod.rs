@@ -1,3 +1,10 @@
+pub mod contact;
+pub mod dependencies;
+pub mod ical;
+pub mod recurrence;
 pub mod repositories;
 pub mod models;
-pub mod data_context;
\ No newline at end of file
+pub mod data_context;
+pub mod migrations;
+pub mod stores;
+pub mod transfer;
\ No newline at end of file
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+/// Largest page of audit entries returned at once.
+pub const MAX_AUDIT_PAGE: usize = 500;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// One create, update or delete of a todo, user or role, written in the same
+/// transaction as the change itself.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct AuditEntry {
+    /// Record id of the entry, e.g. `audit:abc123`
+    pub id: String,
+    pub at: DateTime<Local>,
+    pub action: AuditAction,
+    /// The changed record, as `table:key`
+    pub resource: String,
+    /// Who made the change: `system` for background tasks, unset for
+    /// requests until the API has authentication
+    pub actor: Option<String>,
+    pub request_id: Option<String>,
+    /// The record before the change; unset for a create
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    /// The record after the change; unset for a delete
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    /// Each top-level field whose value differs between `before` and `after`
+    #[serde(default)]
+    pub changes: BTreeMap<String, FieldChange>,
+}
+
+impl AuditEntry {
+    /// Fills `changes` from the two snapshots.
+    pub fn with_changes(mut self) -> Self {
+        let empty = serde_json::Map::new();
+        let fields = |snapshot: &Option<serde_json::Value>| {
+            snapshot
+                .as_ref()
+                .and_then(|value| value.as_object())
+                .cloned()
+                .unwrap_or_else(|| empty.clone())
+        };
+        let (before, after) = (fields(&self.before), fields(&self.after));
+        self.changes = before
+            .keys()
+            .chain(after.keys())
+            .filter(|field| before.get(*field) != after.get(*field))
+            .map(|field| {
+                let change = FieldChange {
+                    before: before.get(field).cloned().unwrap_or_default(),
+                    after: after.get(field).cloned().unwrap_or_default(),
+                };
+                (field.clone(), change)
+            })
+            .collect();
+        self
+    }
+}
+
+/// A field's value before and after a change; `null` when unset.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+pub struct FieldChange {
+    #[schema(value_type = Object)]
+    pub before: serde_json::Value,
+    #[schema(value_type = Object)]
+    pub after: serde_json::Value,
+}
+
+/// Which audit entries to return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+    /// A table such as `user`, or one record such as `user:xyz`
+    pub resource: Option<String>,
+    pub action: Option<AuditAction>,
+    pub request_id: Option<String>,
+}
+
+/// Query string for `GET /api/audit`.
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+pub struct AuditParams {
+    /// A table such as `user`, or one record such as `user:xyz`
+    pub resource: Option<String>,
+    pub action: Option<AuditAction>,
+    pub request_id: Option<String>,
+    /// 1-based page number
+    #[serde(default = "default_page")]
+    pub page: usize,
+    /// Entries per page, at most 500
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    50
+}
+
+/// One page of audit entries, newest first, and how many match in all.
+#[derive(Debug, Clone, Default)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub total: usize,
+}
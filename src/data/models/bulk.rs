@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// How a bulk request treats failing items.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Every item is applied in one transaction, or none is
+    #[default]
+    Atomic,
+    /// Valid items are applied and each item reports its own result
+    Partial,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct BulkParams {
+    #[serde(default)]
+    pub mode: BulkMode,
+}
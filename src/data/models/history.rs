@@ -0,0 +1,21 @@
+use crate::data::models::audit::AuditAction;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+/// A record as it was after one of its writes. Versions are numbered from 1
+/// per record and written in the same transaction as the change itself.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Version<T> {
+    pub version: u64,
+    pub at: DateTime<Local>,
+    pub action: AuditAction,
+    /// Unset for the version that deleted the record
+    pub record: Option<T>,
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+pub struct AsOfParams {
+    /// Return the record as it was at this time, e.g. `2024-05-01T09:00:00Z`
+    pub as_of: Option<DateTime<Local>>,
+}
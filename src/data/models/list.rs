@@ -0,0 +1,92 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+/// A named group of todos, e.g. a project.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct TodoList {
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<Thing>,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl TodoList {
+    /// The record link stored on todos that belong to list `id`.
+    pub fn link(id: &str) -> Thing {
+        Thing::from(("list", id))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct CreateList {
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct UpdateList {
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Completion counts for the todos in one list.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, ToSchema)]
+pub struct ListStats {
+    pub total: usize,
+    pub completed: usize,
+    pub open: usize,
+    /// Open todos past their due date
+    pub overdue: usize,
+    /// Share of todos completed, from 0 to 1; 0 for an empty list
+    pub completion_rate: f64,
+}
+
+impl ListStats {
+    pub fn new(total: usize, completed: usize, overdue: usize) -> Self {
+        ListStats {
+            total,
+            completed,
+            open: total - completed,
+            overdue,
+            completion_rate: if total == 0 {
+                0.0
+            } else {
+                completed as f64 / total as f64
+            },
+        }
+    }
+}
+
+/// What happens to a list's todos when the list is deleted.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDelete {
+    /// Refuse to delete a list that still has todos
+    #[default]
+    Restrict,
+    /// Delete the list's todos along with it
+    Cascade,
+}
+
+/// Result of deleting a list under an `OnDelete` policy.
+#[derive(Debug, Clone)]
+pub enum ListDeletion {
+    /// The list was deleted, along with its todos under `Cascade`
+    Deleted(TodoList),
+    /// `Restrict` kept the list, which still has this many todos
+    Restricted(usize),
+}
+
+/// Query string for `DELETE /api/lists/{id}`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct DeleteListParams {
+    #[serde(default)]
+    pub on_delete: OnDelete,
+}
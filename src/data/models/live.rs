@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query string for `GET /api/{resource}/live`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct LiveParams {
+    /// Resume after this change id, for clients that cannot send the
+    /// `Last-Event-ID` header
+    pub last_event_id: Option<u64>,
+}
+
+/// Query string for the `GET /api/live` WebSocket.
+#[derive(Debug, Deserialize, Clone, Default, IntoParams)]
+pub struct SocketParams {
+    /// Resume after this change id
+    pub last_event_id: Option<u64>,
+    /// Comma-separated resources to follow, e.g. `todo,user`; all by default
+    pub resources: Option<String>,
+}
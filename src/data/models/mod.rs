@@ -1,3 +1,15 @@
+pub mod audit;
+pub mod bulk;
+pub mod history;
+pub mod list;
+pub mod live;
+pub mod outbox;
+pub mod rate_limit;
 pub mod role;
+pub mod search;
+pub mod stream;
+pub mod tag;
 pub mod todo;
+pub mod transfer;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutboxStatus {
+    /// Waiting for a sink, or for its next attempt
+    Pending,
+    /// Every sink took it
+    Published,
+    /// Gave up after the last retry
+    Dead,
+}
+
+/// A todo, user or role change, written to the outbox in the same
+/// transaction as the change itself.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DomainEvent {
+    /// Record id, e.g. `outbox:01J9Z...`
+    pub id: String,
+    /// Counts up with every event, in the transaction that wrote it, so
+    /// ordering by it orders by when the change was made
+    #[serde(default)]
+    pub sequence: u64,
+    /// The same names webhooks subscribe to, e.g. `todo.completed`
+    pub event: String,
+    /// The changed record, as `table:key`
+    pub resource: String,
+    /// The record after the change; for a delete, as it was
+    pub data: serde_json::Value,
+    pub request_id: Option<String>,
+    pub at: DateTime<Local>,
+    pub status: OutboxStatus,
+    #[serde(default)]
+    pub attempts: usize,
+    /// Sinks that have taken the event, so a retry skips them
+    #[serde(default)]
+    pub published_to: Vec<String>,
+    pub next_attempt_at: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+    pub published_at: Option<DateTime<Local>>,
+}
+
+impl DomainEvent {
+    /// The key of the event's record id.
+    pub fn key(&self) -> &str {
+        self.id.split_once(':').map_or(&self.id, |(_, key)| key)
+    }
+}
+
+/// How an attempt to publish an event ended, to be recorded on it.
+#[derive(Debug, Clone)]
+pub struct PublishOutcome {
+    pub status: OutboxStatus,
+    pub published_to: Vec<String>,
+    pub next_attempt_at: Option<DateTime<Local>>,
+    pub error: Option<String>,
+}
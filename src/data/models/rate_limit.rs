@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+/// A token bucket's size and refill rate: `burst` requests, refilled evenly
+/// over `period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Parses `N/unit`, e.g. `10/min`, with units `s`, `min`, `h` or `day`
+    /// and their longer spellings.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (burst, unit) = value.trim().split_once('/')?;
+        let burst = burst.trim().parse().ok().filter(|burst| *burst > 0)?;
+        let secs = match unit.trim() {
+            "s" | "sec" | "second" => 1,
+            "m" | "min" | "minute" => 60,
+            "h" | "hour" => 60 * 60,
+            "d" | "day" => 24 * 60 * 60,
+            _ => return None,
+        };
+        Some(Quota {
+            burst,
+            period: Duration::from_secs(secs),
+        })
+    }
+
+    /// Tokens added per millisecond.
+    pub fn rate(&self) -> f64 {
+        f64::from(self.burst) / self.period.as_millis().max(1) as f64
+    }
+
+    /// How long until a bucket holding `tokens` has `wanted` of them.
+    pub fn wait(&self, tokens: f64, wanted: f64) -> Duration {
+        let missing = (wanted - tokens).max(0.0);
+        Duration::from_millis((missing / self.rate()).ceil() as u64)
+    }
+}
+
+/// The tokens in one client's bucket as of `updated_ms`, in Unix
+/// milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bucket {
+    pub tokens: f64,
+    pub updated_ms: i64,
+}
+
+impl Bucket {
+    pub fn full(quota: Quota, now_ms: i64) -> Self {
+        Bucket {
+            tokens: f64::from(quota.burst),
+            updated_ms: now_ms,
+        }
+    }
+
+    /// Refills the bucket up to `now_ms`, then takes a token if one is left.
+    pub fn take(&mut self, quota: Quota, now_ms: i64) -> Take {
+        let elapsed = (now_ms - self.updated_ms).max(0) as f64;
+        self.tokens = (self.tokens + elapsed * quota.rate()).min(f64::from(quota.burst));
+        self.updated_ms = self.updated_ms.max(now_ms);
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+        Take {
+            allowed,
+            tokens: self.tokens,
+        }
+    }
+}
+
+/// Whether a request got a token, and how many are left after it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Take {
+    pub allowed: bool,
+    pub tokens: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quotas() {
+        let quota = Quota::parse("10/min").unwrap();
+        assert_eq!(quota.burst, 10);
+        assert_eq!(quota.period, Duration::from_secs(60));
+        assert_eq!(Quota::parse(" 5 / hour").unwrap().period.as_secs(), 3600);
+        assert_eq!(Quota::parse("0/min"), None);
+        assert_eq!(Quota::parse("10/fortnight"), None);
+        assert_eq!(Quota::parse("ten/min"), None);
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_refills_at_the_rate() {
+        let quota = Quota::parse("2/s").unwrap();
+        let mut bucket = Bucket::full(quota, 0);
+        assert!(bucket.take(quota, 0).allowed);
+        assert!(bucket.take(quota, 0).allowed);
+        let denied = bucket.take(quota, 0);
+        assert!(!denied.allowed);
+        assert_eq!(quota.wait(denied.tokens, 1.0), Duration::from_millis(500));
+
+        assert!(!bucket.take(quota, 499).allowed);
+        assert!(bucket.take(quota, 600).allowed);
+        // Idle time never fills the bucket past its burst
+        let take = bucket.take(quota, 60_000);
+        assert!(take.allowed);
+        assert_eq!(take.tokens, 1.0);
+    }
+}
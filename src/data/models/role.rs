@@ -1,22 +1,25 @@
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct Role {
+    #[schema(value_type = Option<Object>)]
     pub id: Option<Thing>,
     pub name: String,
+    #[schema(value_type = Option<Vec<Object>>)]
     pub users: Option<Vec<Thing>>,
     pub created_at: Option<DateTime<Local>>,
     pub updated_at: Option<DateTime<Local>>
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct CreateRole {
     pub name: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct UpdateRole {
     pub name: String,
 }
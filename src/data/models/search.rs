@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::IntoParams;
+
+/// Largest page size the search endpoints return.
+pub const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+pub struct SearchParams {
+    /// Words to look for; matching is case-insensitive
+    pub q: String,
+    /// 1-based page number
+    #[serde(default = "default_page")]
+    pub page: usize,
+    /// Results per page, at most 100
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    20
+}
+
+/// A matching record with its relevance score and, for each field that
+/// matched, the field's text with matched terms wrapped in `<mark>` tags.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SearchHit<T> {
+    pub record: T,
+    pub score: f64,
+    pub highlights: BTreeMap<String, String>,
+}
+
+/// One page of search hits, best match first, and the total number of matches.
+#[derive(Debug, Clone)]
+pub struct SearchPage<T> {
+    pub hits: Vec<SearchHit<T>>,
+    pub total: usize,
+}
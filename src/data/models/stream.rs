@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// How a list endpoint streams its records.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamFormat {
+    /// One JSON record per line
+    Ndjson,
+    /// A single JSON array of records, sent in chunks
+    Json,
+}
+
+impl StreamFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            StreamFormat::Ndjson => "application/x-ndjson",
+            StreamFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Query string of list endpoints that can stream.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct StreamParams {
+    /// Send records in id order as they are read, instead of one document
+    /// with a count
+    pub stream: Option<StreamFormat>,
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Longest tag accepted, in characters.
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/// How a todo filter with several tags matches.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Todos with at least one of the tags
+    Any,
+    /// Todos with every one of the tags
+    #[default]
+    All,
+}
+
+/// How many todos carry a tag.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Body of `POST /api/todos/{id}/tags`.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct AddTags {
+    pub tags: Vec<String>,
+}
+
+/// The canonical form of a tag: trimmed and lowercased.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Normalises every tag and drops repeats, keeping the first-seen order.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = normalize_tag(tag);
+        if tag.is_empty() {
+            return Err("Tags must not be empty".to_string());
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(format!(
+                "Tag {} is longer than {} characters",
+                tag, MAX_TAG_LENGTH
+            ));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    Ok(normalized)
+}
@@ -1,26 +1,413 @@
+use crate::data::models::list::TodoList;
+use crate::data::models::tag::TagMatch;
+use crate::data::recurrence::Recurrence;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use surrealdb::sql::Thing;
+use std::cmp::Ordering;
+use surrealdb::sql::{Id, Thing};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(
+    Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct Todo {
+    #[schema(value_type = Option<Object>)]
     pub id: Option<Thing>,
     pub title: String,
     pub content: Option<String>,
     pub completed: Option<bool>,
+    /// The list the todo belongs to, if any
+    #[schema(value_type = Option<Object>)]
+    pub list: Option<Thing>,
+    /// The todo this is a subtask of, if any
+    #[schema(value_type = Option<Object>)]
+    pub parent: Option<Thing>,
+    pub tags: Option<Vec<String>>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Local>>,
+    /// Set when `completed` becomes true and cleared when it becomes false
+    pub completed_at: Option<DateTime<Local>>,
+    pub remind_at: Option<DateTime<Local>>,
+    /// When the reminder task fired `remind_at`; reset when `remind_at` changes
+    pub reminder_fired_at: Option<DateTime<Local>>,
+    /// iCalendar RRULE; completing the todo creates the next occurrence
+    pub recurrence: Option<String>,
+    /// The todo created when this one was first completed, so completing it
+    /// again does not create another
+    #[schema(value_type = Option<Object>)]
+    pub next_occurrence: Option<Thing>,
     pub created_at: Option<DateTime<Local>>,
     pub updated_at: Option<DateTime<Local>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+impl Todo {
+    /// The record link for the todo with id `id`.
+    pub fn link(id: &str) -> Thing {
+        Thing::from(("todo", id))
+    }
+
+    /// A new, unsaved todo built from a create request.
+    pub fn from_create(body: CreateTodo, now: DateTime<Local>) -> Self {
+        let completed = body.completed.unwrap_or(false);
+        Todo {
+            id: None,
+            title: body.title,
+            content: Some(body.content.unwrap_or_default()),
+            completed: Some(completed),
+            list: body.list_id.as_deref().map(TodoList::link),
+            parent: body.parent_id.as_deref().map(Todo::link),
+            tags: Some(body.tags.unwrap_or_default()),
+            priority: Some(body.priority.unwrap_or_default()),
+            due_at: body.due_at,
+            completed_at: completed.then_some(now),
+            remind_at: body.remind_at,
+            reminder_fired_at: None,
+            recurrence: body.recurrence,
+            next_occurrence: None,
+            created_at: Some(now),
+            updated_at: None,
+        }
+    }
+
+    /// Whether the todo is still open after its due date.
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.completed != Some(true) && self.due_at.is_some_and(|due_at| due_at < now)
+    }
+
+    /// Applies a change to `completed`, stamping or clearing `completed_at`
+    /// only when the value actually flips.
+    pub fn set_completed(&mut self, completed: bool, now: DateTime<Local>) {
+        if let Some(completed_at) = completed_at_change(self.completed, Some(completed), now) {
+            self.completed_at = completed_at;
+        }
+        self.completed = Some(completed);
+    }
+
+    /// The next open todo in this one's series, due at the following
+    /// occurrence with its reminder the same time ahead, or `None` when the
+    /// todo does not repeat or the series has ended. Its id is chosen up
+    /// front so this todo can link to it before it is stored.
+    pub fn next_in_series(&self, now: DateTime<Local>) -> Option<Todo> {
+        let rule = Recurrence::parse(self.recurrence.as_deref()?).ok()?;
+        let due_at = self.due_at?;
+        let next_due_at = rule.next_after(due_at)?;
+        Some(Todo {
+            id: Some(Thing::from(("todo", Id::rand()))),
+            completed: Some(false),
+            due_at: Some(next_due_at),
+            completed_at: None,
+            remind_at: self
+                .remind_at
+                .map(|remind_at| next_due_at - (due_at - remind_at)),
+            reminder_fired_at: None,
+            recurrence: Some(rule.advance().to_string()),
+            next_occurrence: None,
+            created_at: Some(now),
+            updated_at: None,
+            ..self.clone()
+        })
+    }
+
+    /// When an update has just completed this recurring todo for the first
+    /// time, links it to the next occurrence and returns that for the caller
+    /// to create along with the update.
+    pub fn link_next_occurrence(
+        &mut self,
+        was_completed: bool,
+        now: DateTime<Local>,
+    ) -> Option<Todo> {
+        if was_completed || self.completed != Some(true) || self.next_occurrence.is_some() {
+            return None;
+        }
+        let next = self.next_in_series(now)?;
+        self.next_occurrence = next.id.clone();
+        Some(next)
+    }
+
+    /// Applies every field set in `body`, leaving the rest as they are.
+    pub fn apply_update(&mut self, body: UpdateTodo, now: DateTime<Local>) {
+        if let Some(title) = body.title {
+            self.title = title;
+        }
+        self.content = body.content.or(self.content.take());
+        if let Some(completed) = body.completed {
+            self.set_completed(completed, now);
+        }
+        if let Some(list_id) = body.list_id.as_deref() {
+            self.list = Some(TodoList::link(list_id));
+        }
+        if let Some(parent_id) = body.parent_id.as_deref() {
+            self.parent = Some(Todo::link(parent_id));
+        }
+        self.tags = body.tags.or(self.tags.take());
+        self.priority = body.priority.or(self.priority);
+        self.due_at = body.due_at.or(self.due_at);
+        if let Some(remind_at) = body.remind_at {
+            self.set_remind_at(remind_at);
+        }
+        self.recurrence = body.recurrence.or(self.recurrence.take());
+        self.updated_at = Some(now);
+    }
+
+    /// Moves the reminder, re-arming it if the time changed.
+    pub fn set_remind_at(&mut self, remind_at: DateTime<Local>) {
+        if self.remind_at != Some(remind_at) {
+            self.reminder_fired_at = None;
+        }
+        self.remind_at = Some(remind_at);
+    }
+}
+
+/// The new `completed_at` when `completed` goes from `was` to `now_completed`,
+/// or `None` when it does not flip.
+pub fn completed_at_change(
+    was: Option<bool>,
+    now_completed: Option<bool>,
+    now: DateTime<Local>,
+) -> Option<Option<DateTime<Local>>> {
+    let was = was.unwrap_or(false);
+    match now_completed {
+        Some(true) if !was => Some(Some(now)),
+        Some(false) if was => Some(None),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, ToSchema, Validate)]
 pub struct CreateTodo {
+    #[validate(length(min = 1, message = "must not be empty"))]
     pub title: String,
     pub content: Option<String>,
     pub completed: Option<bool>,
+    /// Id of the list to add the todo to
+    pub list_id: Option<String>,
+    /// Id of the todo to make this a subtask of
+    pub parent_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Local>>,
+    pub remind_at: Option<DateTime<Local>>,
+    /// iCalendar RRULE such as `FREQ=WEEKLY;BYDAY=MO`; needs `due_at`
+    pub recurrence: Option<String>,
 }
-#[derive(Debug, Deserialize, Serialize, Clone)]
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, ToSchema)]
 pub struct UpdateTodo {
     pub title: Option<String>,
     pub content: Option<String>,
     pub completed: Option<bool>,
-}
\ No newline at end of file
+    /// Id of the list to move the todo to
+    pub list_id: Option<String>,
+    /// Id of the todo to make this a subtask of
+    pub parent_id: Option<String>,
+    /// Replaces every tag on the todo
+    pub tags: Option<Vec<String>>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Local>>,
+    pub remind_at: Option<DateTime<Local>>,
+    /// iCalendar RRULE such as `FREQ=WEEKLY;BYDAY=MO`; needs `due_at`
+    pub recurrence: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoSort {
+    /// Most urgent first
+    Priority,
+    /// Soonest first, undated todos last
+    DueAt,
+    /// Oldest first
+    CreatedAt,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Query string for `GET /api/todos`.
+#[derive(Debug, Deserialize, Clone, Default, IntoParams)]
+pub struct TodoFilter {
+    /// Only open todos whose due date has passed
+    pub overdue: Option<bool>,
+    /// Only todos due before this instant
+    pub due_before: Option<DateTime<Local>>,
+    /// Only todos due after this instant
+    pub due_after: Option<DateTime<Local>>,
+    pub completed: Option<bool>,
+    pub priority: Option<Priority>,
+    /// Only todos in this list
+    pub list_id: Option<String>,
+    /// Only subtasks of this todo
+    pub parent_id: Option<String>,
+    /// Only todos carrying these tags, given as repeated `tag` parameters
+    #[serde(skip)]
+    pub tags: Vec<String>,
+    /// Whether todos need all of the tags or any one of them
+    pub tag_match: Option<TagMatch>,
+    pub sort: Option<TodoSort>,
+    /// Direction of the sort; defaults to the one described on each sort
+    pub order: Option<SortOrder>,
+}
+
+impl TodoFilter {
+    /// Whether `todo` passes every filter that is set.
+    pub fn matches(&self, todo: &Todo, now: DateTime<Local>) -> bool {
+        let due_within = |bound: Option<DateTime<Local>>, ordering: Ordering| match bound {
+            Some(bound) => todo
+                .due_at
+                .is_some_and(|due_at| due_at.cmp(&bound) == ordering),
+            None => true,
+        };
+
+        self.overdue
+            .is_none_or(|overdue| todo.is_overdue(now) == overdue)
+            && due_within(self.due_before, Ordering::Less)
+            && due_within(self.due_after, Ordering::Greater)
+            && self
+                .completed
+                .is_none_or(|completed| todo.completed.unwrap_or(false) == completed)
+            && self
+                .priority
+                .is_none_or(|priority| todo.priority.unwrap_or_default() == priority)
+            && self
+                .list_id
+                .as_deref()
+                .is_none_or(|list_id| todo.list.as_ref() == Some(&TodoList::link(list_id)))
+            && self
+                .parent_id
+                .as_deref()
+                .is_none_or(|parent_id| todo.parent.as_ref() == Some(&Todo::link(parent_id)))
+            && self.matches_tags(todo.tags.as_deref().unwrap_or_default())
+    }
+
+    fn matches_tags(&self, tags: &[String]) -> bool {
+        if self.tags.is_empty() {
+            return true;
+        }
+        match self.tag_match.unwrap_or_default() {
+            TagMatch::Any => self.tags.iter().any(|tag| tags.contains(tag)),
+            TagMatch::All => self.tags.iter().all(|tag| tags.contains(tag)),
+        }
+    }
+
+    /// Orders `todos` by the requested sort, leaving them as they are without one.
+    pub fn sort(&self, todos: &mut [Todo]) {
+        let Some(sort) = self.sort else { return };
+        todos.sort_by(|a, b| {
+            let ordering = match sort {
+                TodoSort::Priority => b
+                    .priority
+                    .unwrap_or_default()
+                    .cmp(&a.priority.unwrap_or_default()),
+                TodoSort::DueAt => match (a.due_at, b.due_at) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                TodoSort::CreatedAt => a.created_at.cmp(&b.created_at),
+            };
+            match self.order {
+                Some(SortOrder::Desc) if sort != TodoSort::Priority => ordering.reverse(),
+                Some(SortOrder::Asc) if sort == TodoSort::Priority => ordering.reverse(),
+                _ => ordering,
+            }
+        });
+    }
+}
+
+/// Query string for requests that may complete todos.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct CompletionParams {
+    /// Complete parents even while some of their subtasks are open
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Largest number of occurrences `GET /api/todos/{id}/occurrences` previews.
+pub const MAX_OCCURRENCES: usize = 100;
+
+/// Query string for `GET /api/todos/{id}/occurrences`.
+#[derive(Debug, Deserialize, Clone, Copy, IntoParams)]
+pub struct OccurrenceParams {
+    /// How many upcoming occurrences to list, at most 100
+    #[serde(default = "default_occurrences")]
+    pub count: usize,
+}
+
+fn default_occurrences() -> usize {
+    5
+}
+
+/// A todo along with the todos blocking it that are still open.
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct TodoWithBlockers {
+    #[serde(flatten)]
+    pub todo: Todo,
+    pub open_blockers: Vec<Todo>,
+}
+
+/// Body of `POST /api/todos/{id}/blockers`.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct AddBlocker {
+    /// Id of the todo that has to be completed first
+    pub blocker_id: String,
+}
+
+/// One entry in a `PATCH /api/todos/bulk` request.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct BulkTodoPatch {
+    pub id: String,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub completed: Option<bool>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Local>>,
+    pub remind_at: Option<DateTime<Local>>,
+}
+
+/// Fields merged into a stored todo; unset fields are left untouched. The
+/// nested options clear a field with `Some(None)`.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct TodoPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub list: Option<Thing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Local>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<Option<DateTime<Local>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remind_at: Option<DateTime<Local>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder_fired_at: Option<Option<DateTime<Local>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Local>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_occurrence: Option<Thing>,
+    /// Created along with the patch, as the todo `next_occurrence` links to
+    #[serde(skip)]
+    pub next: Option<Todo>,
+}
@@ -0,0 +1,242 @@
+use crate::data::models::role::Role;
+use crate::data::models::todo::{CreateTodo, Priority, Todo, UpdateTodo};
+use crate::data::models::user::User;
+use crate::data::transfer::{Column, Transfer};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+/// File format of an export or import.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl TransferFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            TransferFormat::Csv => "text/csv; charset=utf-8",
+            TransferFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            TransferFormat::Csv => "csv",
+            TransferFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// The format named by a `Content-Type` header, if any.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "text/csv" => Some(TransferFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Some(TransferFormat::Ndjson)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Query string for `GET /api/{resource}/export`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct ExportParams {
+    #[serde(default)]
+    pub format: TransferFormat,
+}
+
+/// Query string for `POST /api/{resource}/import`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, IntoParams)]
+pub struct ImportParams {
+    /// Overrides the format given by the `Content-Type` header
+    pub format: Option<TransferFormat>,
+    /// Validate every row and report what would change without writing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A todo as one export row. On import, a row whose `id` names an existing
+/// todo updates it, keeping the stored value of any empty column; other rows
+/// create new todos. Timestamps are only exported.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct TodoRow {
+    pub id: Option<String>,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub title: String,
+    pub content: Option<String>,
+    pub completed: Option<bool>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Local>>,
+    pub remind_at: Option<DateTime<Local>>,
+    pub recurrence: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub list_id: Option<String>,
+    pub parent_id: Option<String>,
+    pub completed_at: Option<DateTime<Local>>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl TodoRow {
+    pub fn into_create(self) -> CreateTodo {
+        CreateTodo {
+            title: self.title,
+            content: self.content,
+            completed: self.completed,
+            list_id: self.list_id,
+            parent_id: self.parent_id,
+            tags: self.tags,
+            priority: self.priority,
+            due_at: self.due_at,
+            remind_at: self.remind_at,
+            recurrence: self.recurrence,
+        }
+    }
+
+    pub fn into_update(self) -> UpdateTodo {
+        UpdateTodo {
+            title: Some(self.title),
+            content: self.content,
+            completed: self.completed,
+            list_id: self.list_id,
+            parent_id: self.parent_id,
+            tags: self.tags,
+            priority: self.priority,
+            due_at: self.due_at,
+            remind_at: self.remind_at,
+            recurrence: self.recurrence,
+        }
+    }
+}
+
+impl Transfer for Todo {
+    type Row = TodoRow;
+    const COLUMNS: &'static [Column] = &[
+        Column::text("id"),
+        Column::text("title"),
+        Column::text("content"),
+        Column::flag("completed"),
+        Column::text("priority"),
+        Column::text("due_at"),
+        Column::text("remind_at"),
+        Column::text("recurrence"),
+        Column::list("tags"),
+        Column::text("list_id"),
+        Column::text("parent_id"),
+        Column::text("completed_at"),
+        Column::text("created_at"),
+        Column::text("updated_at"),
+    ];
+
+    fn to_row(&self) -> TodoRow {
+        TodoRow {
+            id: self.key(),
+            title: self.title.clone(),
+            content: self.content.clone(),
+            completed: self.completed,
+            priority: self.priority,
+            due_at: self.due_at,
+            remind_at: self.remind_at,
+            recurrence: self.recurrence.clone(),
+            tags: self.tags.clone(),
+            list_id: self.list.as_ref().map(|list| list.id.to_raw()),
+            parent_id: self.parent.as_ref().map(|parent| parent.id.to_raw()),
+            completed_at: self.completed_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    fn key(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.id.to_raw())
+    }
+}
+
+/// A user as one export row. On import, users are matched by `email`; `id`
+/// and the timestamps are only exported.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UserRow {
+    pub id: Option<String>,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub name: String,
+    #[validate(email(message = "must be a valid email address"))]
+    pub email: String,
+    pub phone: Option<String>,
+    pub role_id: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Transfer for User {
+    type Row = UserRow;
+    const COLUMNS: &'static [Column] = &[
+        Column::text("id"),
+        Column::text("name"),
+        Column::text("email"),
+        Column::text("phone"),
+        Column::text("role_id"),
+        Column::text("created_at"),
+        Column::text("updated_at"),
+    ];
+
+    fn to_row(&self) -> UserRow {
+        UserRow {
+            id: self.key(),
+            name: self.name.clone(),
+            email: self.email.clone(),
+            phone: self.phone.clone(),
+            role_id: self.role.as_ref().map(|role| role.id.to_raw()),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    fn key(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.id.to_raw())
+    }
+}
+
+/// A role as one export row. On import, roles are matched by `name`; `id`
+/// and the timestamps are only exported.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RoleRow {
+    pub id: Option<String>,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub name: String,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Transfer for Role {
+    type Row = RoleRow;
+    const COLUMNS: &'static [Column] = &[
+        Column::text("id"),
+        Column::text("name"),
+        Column::text("created_at"),
+        Column::text("updated_at"),
+    ];
+
+    fn to_row(&self) -> RoleRow {
+        RoleRow {
+            id: self.key(),
+            name: self.name.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    fn key(&self) -> Option<String> {
+        self.id.as_ref().map(|id| id.id.to_raw())
+    }
+}
@@ -1,30 +1,101 @@
+use crate::data::contact::{ContactError, ContactNormalizer};
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
+use utoipa::ToSchema;
+use validator::Validate;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct User {
+    #[schema(value_type = Option<Object>)]
     pub id: Option<Thing>,
     pub name: String,
     pub email: String,
+    /// E.164, e.g. `+15555550100`, except for a number given without a
+    /// country code while `PHONE_DEFAULT_COUNTRY_CODE` is unset, which is kept
+    /// as its national digits
     pub phone: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub role: Option<Thing>,
     pub created_at: Option<DateTime<Local>>,
     pub updated_at: Option<DateTime<Local>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+impl User {
+    /// Puts the email and phone number into their canonical forms.
+    pub fn normalize(&mut self, contacts: &ContactNormalizer) -> Result<(), ContactError> {
+        self.email = contacts.email(&self.email);
+        self.phone = contacts.optional_phone(self.phone.as_deref())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
 pub struct CreateUser {
+    #[validate(length(min = 1, message = "must not be empty"))]
     pub name: String,
+    #[validate(email(message = "must be a valid email address"))]
     pub email: String,
+    /// Converted to E.164; a number without `+` or `00` takes
+    /// `PHONE_DEFAULT_COUNTRY_CODE`, or is stored as its national digits when
+    /// that is unset
     pub phone: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub role: Option<Thing>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct UpdateUser {
     pub name: String,
     pub email: String,
+    /// Converted to E.164; a number without `+` or `00` takes
+    /// `PHONE_DEFAULT_COUNTRY_CODE`, or is stored as its national digits when
+    /// that is unset
     pub phone: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub role: Option<Thing>,
 }
+
+/// One entry in a `PATCH /api/users/bulk` request.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct BulkUserPatch {
+    pub id: String,
+    #[validate(length(min = 1, message = "must not be empty"))]
+    pub name: Option<String>,
+    #[validate(email(message = "must be a valid email address"))]
+    pub email: Option<String>,
+    /// Converted to E.164; a number without `+` or `00` takes
+    /// `PHONE_DEFAULT_COUNTRY_CODE`, or is stored as its national digits when
+    /// that is unset
+    pub phone: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub role: Option<Thing>,
+}
+
+/// Fields merged into a stored user; unset fields are left untouched.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct UserPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<Thing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl UserPatch {
+    /// Puts any changed email and phone number into their canonical forms.
+    pub fn normalize(&mut self, contacts: &ContactNormalizer) -> Result<(), ContactError> {
+        if let Some(email) = &self.email {
+            self.email = Some(contacts.email(email));
+        }
+        if let Some(phone) = &self.phone {
+            self.phone = contacts.optional_phone(Some(phone))?;
+        }
+        Ok(())
+    }
+}
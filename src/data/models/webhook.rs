@@ -0,0 +1,171 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+/// Every event a webhook can subscribe to. `todo.completed` is sent, along
+/// with `todo.updated`, when a todo's `completed` turns true.
+pub const EVENT_TYPES: [&str; 10] = [
+    "todo.created",
+    "todo.updated",
+    "todo.completed",
+    "todo.deleted",
+    "user.created",
+    "user.updated",
+    "user.deleted",
+    "role.created",
+    "role.updated",
+    "role.deleted",
+];
+
+/// Largest page of deliveries returned at once.
+pub const MAX_DELIVERY_PAGE: usize = 500;
+
+/// An external endpoint notified of the events it subscribes to.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct Webhook {
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<Thing>,
+    pub url: String,
+    /// Key for the `X-Webhook-Signature` HMAC; only returned when the webhook
+    /// is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub events: Vec<String>,
+    /// Inactive webhooks are kept but sent nothing
+    pub active: bool,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+impl Webhook {
+    /// The webhook without its secret, for responses.
+    pub fn redacted(self) -> Self {
+        Webhook {
+            secret: None,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct CreateWebhook {
+    #[validate(url(message = "must be a valid URL"))]
+    pub url: String,
+    /// Generated when unset
+    #[validate(length(min = 16, message = "must be at least 16 characters"))]
+    pub secret: Option<String>,
+    #[validate(custom(function = "known_events"))]
+    pub events: Vec<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, Validate)]
+pub struct UpdateWebhook {
+    #[validate(url(message = "must be a valid URL"))]
+    pub url: Option<String>,
+    /// Replaces the secret, e.g. to rotate it
+    #[validate(length(min = 16, message = "must be at least 16 characters"))]
+    pub secret: Option<String>,
+    #[validate(custom(function = "known_events"))]
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+fn known_events(events: &[String]) -> Result<(), ValidationError> {
+    if events.is_empty() {
+        return Err(ValidationError::new("empty").with_message("must not be empty".into()));
+    }
+    match events
+        .iter()
+        .find(|event| !EVENT_TYPES.contains(&event.as_str()))
+    {
+        Some(event) => Err(ValidationError::new("unknown_event")
+            .with_message(format!("unknown event type: {}", event).into())),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    Delivered,
+    /// Gave up after the last retry; can be retried by hand
+    Dead,
+}
+
+/// One try at sending a delivery.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct DeliveryAttempt {
+    pub at: DateTime<Local>,
+    /// The endpoint's response status, unset when no response came
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// One event queued for one webhook, relayed from the outbox.
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct WebhookDelivery {
+    /// Record id of the delivery, e.g. `webhook_delivery:abc123`
+    pub id: String,
+    /// The webhook, as `webhook:key`
+    pub webhook: String,
+    pub event: String,
+    /// The JSON body sent
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    #[serde(default)]
+    pub attempts: Vec<DeliveryAttempt>,
+    /// When the next attempt is due, for pending deliveries
+    pub next_attempt_at: Option<DateTime<Local>>,
+    pub created_at: DateTime<Local>,
+    pub delivered_at: Option<DateTime<Local>>,
+}
+
+/// Which deliveries to return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryFilter {
+    /// Key of one webhook
+    pub webhook: Option<String>,
+    pub status: Option<DeliveryStatus>,
+}
+
+/// Query string for the delivery logs.
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+pub struct DeliveryParams {
+    pub status: Option<DeliveryStatus>,
+    /// 1-based page number
+    #[serde(default = "default_page")]
+    pub page: usize,
+    /// Deliveries per page, at most 500
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    50
+}
+
+/// One page of deliveries, newest first, and how many match in all.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryPage {
+    pub deliveries: Vec<WebhookDelivery>,
+    pub total: usize,
+}
+
+/// How a delivery attempt ended, to be recorded on the delivery.
+#[derive(Debug, Clone)]
+pub struct AttemptOutcome {
+    pub attempt: DeliveryAttempt,
+    pub status: DeliveryStatus,
+    pub next_attempt_at: Option<DateTime<Local>>,
+}
@@ -0,0 +1,499 @@
+use chrono::{
+    DateTime, Datelike, Days, Local, LocalResult, Months, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use std::fmt;
+
+/// Periods scanned without finding an occurrence before a rule is treated as
+/// exhausted, e.g. `FREQ=MONTHLY;BYMONTHDAY=30;BYDAY=MO` far in the future.
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A `BYDAY` entry such as `MO`, `2TU` or `-1FR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    /// Which occurrence of the weekday within the month, counting from the
+    /// end when negative; only allowed with `FREQ=MONTHLY`
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+/// The subset of an iCalendar RRULE (RFC 5545) that todos can repeat on:
+/// `FREQ`, `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` and `BYMONTHDAY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// Occurrences in the series, counting the one the rule starts from
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Local>>,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i8>,
+}
+
+impl Recurrence {
+    /// Parses a rule such as `FREQ=WEEKLY;BYDAY=MO,FR`, with or without the
+    /// `RRULE:` prefix and in any case.
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim().to_ascii_uppercase();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(&rule);
+
+        let mut frequency = None;
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+        };
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Malformed RRULE part `{}`", part))?;
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported FREQ `{}`", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or("INTERVAL must be a positive number")?
+                }
+                "COUNT" => {
+                    recurrence.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or("COUNT must be a positive number")?,
+                    )
+                }
+                "UNTIL" => recurrence.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    recurrence.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    recurrence.by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.parse::<i8>()
+                                .ok()
+                                .filter(|day| *day != 0 && (-31..=31).contains(day))
+                                .ok_or_else(|| format!("Invalid BYMONTHDAY `{}`", day))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+                _ => return Err(format!("Unsupported RRULE part `{}`", name)),
+            }
+        }
+
+        recurrence.frequency = frequency.ok_or("RRULE needs a FREQ")?;
+        if recurrence.count.is_some() && recurrence.until.is_some() {
+            return Err("COUNT and UNTIL cannot be used together".to_string());
+        }
+        if recurrence.frequency != Frequency::Monthly
+            && recurrence.by_day.iter().any(|day| day.ordinal.is_some())
+        {
+            return Err("Numbered BYDAY entries need FREQ=MONTHLY".to_string());
+        }
+        if recurrence.frequency == Frequency::Weekly && !recurrence.by_month_day.is_empty() {
+            return Err("BYMONTHDAY cannot be used with FREQ=WEEKLY".to_string());
+        }
+        if recurrence.frequency == Frequency::Yearly
+            && !(recurrence.by_day.is_empty() && recurrence.by_month_day.is_empty())
+        {
+            return Err("FREQ=YEARLY repeats on the start date and takes no BY* parts".to_string());
+        }
+        Ok(recurrence)
+    }
+
+    /// Up to `limit` occurrences after `start`, which is itself the first
+    /// occurrence of the series.
+    pub fn occurrences(&self, start: DateTime<Local>, limit: usize) -> Vec<DateTime<Local>> {
+        let remaining = match self.count {
+            Some(count) => limit.min((count as usize).saturating_sub(1)),
+            None => limit,
+        };
+        let mut occurrences = Vec::new();
+        let mut empty_periods = 0;
+        let mut period = 0;
+        while occurrences.len() < remaining && empty_periods < MAX_EMPTY_PERIODS {
+            let mut found = false;
+            for date in self.period_dates(start.date_naive(), period) {
+                let Some(occurrence) = at_local(date.and_time(start.time())) else {
+                    continue;
+                };
+                if occurrence <= start {
+                    continue;
+                }
+                if self.until.is_some_and(|until| occurrence > until) {
+                    return occurrences;
+                }
+                found = true;
+                occurrences.push(occurrence);
+                if occurrences.len() == remaining {
+                    break;
+                }
+            }
+            empty_periods = if found { 0 } else { empty_periods + 1 };
+            period += 1;
+        }
+        occurrences
+    }
+
+    /// The first occurrence after `start`, if the series goes on.
+    pub fn next_after(&self, start: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.occurrences(start, 1).into_iter().next()
+    }
+
+    /// The same rule seen from the next occurrence: `COUNT` drops by one.
+    pub fn advance(&self) -> Self {
+        Recurrence {
+            count: self.count.map(|count| count.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+
+    /// Candidate dates in the `period`-th period after the one holding `start`,
+    /// in ascending order.
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let Some(step) = period.checked_mul(self.interval) else {
+            return Vec::new();
+        };
+        let mut dates = match self.frequency {
+            Frequency::Daily => start
+                .checked_add_days(Days::new(step.into()))
+                .filter(|date| self.matches_weekday(*date) && self.matches_month_day(*date))
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let monday =
+                    start - chrono::Duration::days(start.weekday().num_days_from_monday().into());
+                let Some(week) = monday.checked_add_days(Days::new(u64::from(step) * 7)) else {
+                    return Vec::new();
+                };
+                if self.by_day.is_empty() {
+                    week.checked_add_days(Days::new(start.weekday().num_days_from_monday().into()))
+                        .into_iter()
+                        .collect()
+                } else {
+                    (0..7)
+                        .filter_map(|offset| week.checked_add_days(Days::new(offset)))
+                        .filter(|date| self.matches_weekday(*date))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let Some(first) = start
+                    .with_day(1)
+                    .and_then(|first| first.checked_add_months(Months::new(step)))
+                else {
+                    return Vec::new();
+                };
+                self.month_dates(start, first)
+            }
+            Frequency::Yearly => start
+                .year()
+                .checked_add(step as i32)
+                .and_then(|year| NaiveDate::from_ymd_opt(year, start.month(), start.day()))
+                .into_iter()
+                .collect(),
+        };
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// Dates in the month starting at `first` that the BY* parts select,
+    /// defaulting to the day of month of `start`.
+    fn month_dates(&self, start: NaiveDate, first: NaiveDate) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .collect();
+        if !self.by_month_day.is_empty() {
+            return self
+                .by_month_day
+                .iter()
+                .filter_map(|day| {
+                    let index = if *day > 0 {
+                        *day as usize - 1
+                    } else {
+                        days.len().checked_sub(day.unsigned_abs() as usize)?
+                    };
+                    days.get(index).copied()
+                })
+                .filter(|date| self.matches_weekday(*date))
+                .collect();
+        }
+        if self.by_day.is_empty() {
+            return days
+                .get(start.day0() as usize)
+                .copied()
+                .into_iter()
+                .collect();
+        }
+        self.by_day
+            .iter()
+            .flat_map(|by_day| {
+                let matching: Vec<NaiveDate> = days
+                    .iter()
+                    .copied()
+                    .filter(|date| date.weekday() == by_day.weekday)
+                    .collect();
+                match by_day.ordinal {
+                    None => matching,
+                    Some(ordinal) if ordinal > 0 => matching
+                        .get(ordinal as usize - 1)
+                        .copied()
+                        .into_iter()
+                        .collect(),
+                    Some(ordinal) => matching
+                        .len()
+                        .checked_sub(ordinal.unsigned_abs() as usize)
+                        .and_then(|index| matching.get(index).copied())
+                        .into_iter()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    fn matches_weekday(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|day| day.weekday == date.weekday())
+    }
+
+    fn matches_month_day(&self, date: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let days_in_month = date
+            .with_day(1)
+            .and_then(|first| first.checked_add_months(Months::new(1)))
+            .map(|next| next.pred_opt().map_or(31, |last| last.day()))
+            .unwrap_or(31) as i8;
+        let day = date.day() as i8;
+        self.by_month_day
+            .iter()
+            .any(|by| *by == day || *by == day - days_in_month - 1)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(
+                f,
+                ";UNTIL={}",
+                until.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+            )?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|day| {
+                    let code = weekday_code(day.weekday);
+                    match day.ordinal {
+                        Some(ordinal) => format!("{}{}", ordinal, code),
+                        None => code.to_string(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i8::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_until(value: &str) -> Result<DateTime<Local>, String> {
+    let invalid = || format!("Invalid UNTIL `{}`", value);
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map(|date| date.and_hms_opt(23, 59, 59).unwrap_or_default())
+        })
+        .map_err(|_| invalid())?;
+    at_local(naive).ok_or_else(invalid)
+}
+
+fn parse_by_day(value: &str) -> Result<ByDay, String> {
+    let invalid = || format!("Invalid BYDAY `{}`", value);
+    let split = value.len().checked_sub(2).ok_or_else(invalid)?;
+    let (ordinal, code) = value.split_at(split);
+    let weekday = match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(invalid()),
+    };
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .parse::<i8>()
+                .ok()
+                .filter(|ordinal| *ordinal != 0 && (-5..=5).contains(ordinal))
+                .ok_or_else(invalid)?,
+        ),
+    };
+    Ok(ByDay { ordinal, weekday })
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// `naive` in the local timezone, taking the earlier reading on a DST overlap
+/// and skipping times that a DST gap removes.
+pub(crate) fn at_local(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(time) => Some(time),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest),
+        LocalResult::None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(date: &str) -> DateTime<Local> {
+        at_local(NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()).unwrap()
+    }
+
+    fn dates(rule: &str, start: &str, limit: usize) -> Vec<String> {
+        Recurrence::parse(rule)
+            .unwrap()
+            .occurrences(local(start), limit)
+            .iter()
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_prints_canonical_rules() {
+        let rule = Recurrence::parse("rrule:freq=weekly;interval=2;byday=mo,-1fr;count=3");
+        assert_eq!(
+            rule.map(|rule| rule.to_string()),
+            Err("Numbered BYDAY entries need FREQ=MONTHLY".to_string())
+        );
+        let rule =
+            Recurrence::parse("rrule:freq=monthly;interval=2;byday=mo,-1fr;count=3").unwrap();
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=MONTHLY;INTERVAL=2;COUNT=3;BYDAY=MO,-1FR"
+        );
+
+        assert!(Recurrence::parse("INTERVAL=2").is_err());
+        assert!(Recurrence::parse("FREQ=HOURLY").is_err());
+        assert!(Recurrence::parse("FREQ=DAILY;BYHOUR=9").is_err());
+        assert!(Recurrence::parse("FREQ=DAILY;COUNT=2;UNTIL=20300101").is_err());
+    }
+
+    #[test]
+    fn expands_weekly_and_daily_rules() {
+        // 2030-01-01 is a Tuesday
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=MO,WE,FR", "2030-01-01 09:00", 4),
+            vec![
+                "2030-01-02 09:00",
+                "2030-01-04 09:00",
+                "2030-01-07 09:00",
+                "2030-01-09 09:00"
+            ]
+        );
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2", "2030-01-01 09:00", 2),
+            vec!["2030-01-15 09:00", "2030-01-29 09:00"]
+        );
+        assert_eq!(
+            dates("FREQ=DAILY;BYDAY=SA,SU", "2030-01-01 08:30", 3),
+            vec!["2030-01-05 08:30", "2030-01-06 08:30", "2030-01-12 08:30"]
+        );
+    }
+
+    #[test]
+    fn expands_monthly_and_yearly_rules() {
+        assert_eq!(
+            dates("FREQ=MONTHLY", "2030-01-31 10:00", 3),
+            vec!["2030-03-31 10:00", "2030-05-31 10:00", "2030-07-31 10:00"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1", "2030-01-31 10:00", 2),
+            vec!["2030-02-28 10:00", "2030-03-31 10:00"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR", "2030-01-01 10:00", 2),
+            vec!["2030-01-25 10:00", "2030-02-22 10:00"]
+        );
+        assert_eq!(
+            dates("FREQ=YEARLY", "2028-02-29 10:00", 2),
+            vec!["2032-02-29 10:00", "2036-02-29 10:00"]
+        );
+    }
+
+    #[test]
+    fn stops_at_count_and_until() {
+        assert_eq!(dates("FREQ=DAILY;COUNT=3", "2030-01-01 09:00", 10).len(), 2);
+        assert!(dates("FREQ=DAILY;COUNT=1", "2030-01-01 09:00", 10).is_empty());
+        assert_eq!(
+            dates("FREQ=DAILY;UNTIL=20300103", "2030-01-01 09:00", 10),
+            vec!["2030-01-02 09:00", "2030-01-03 09:00"]
+        );
+        let rule = Recurrence::parse("FREQ=DAILY;COUNT=3").unwrap();
+        assert_eq!(rule.advance().count, Some(2));
+    }
+}
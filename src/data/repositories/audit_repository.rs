@@ -0,0 +1,99 @@
+use crate::data::models::audit::{AuditEntry, AuditFilter, AuditPage};
+use crate::data::stores::AuditStore;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::error::Db::Serialization;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+use tracing::instrument;
+
+#[derive(Deserialize)]
+struct Total {
+    total: usize,
+}
+
+pub struct AuditRepository {
+    db: Arc<Database>,
+}
+
+impl AuditRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        AuditRepository { db }
+    }
+}
+
+#[async_trait]
+impl AuditStore for AuditRepository {
+    #[instrument(skip(self), err)]
+    async fn list(
+        &self,
+        filter: AuditFilter,
+        start: usize,
+        limit: usize,
+    ) -> Result<AuditPage, Error> {
+        let mut conditions = Vec::new();
+        if let Some(resource) = &filter.resource {
+            conditions.push(if resource.contains(':') {
+                "resource = $resource"
+            } else {
+                "table = $resource"
+            });
+        }
+        if filter.action.is_some() {
+            conditions.push("action = $action");
+        }
+        if filter.request_id.is_some() {
+            conditions.push("request_id = $request_id");
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+
+        let mut response = observe_statements(
+            "audit",
+            "list",
+            self.db
+                .client
+                .query(format!(
+                    "SELECT * FROM audit{condition} ORDER BY at DESC LIMIT $limit START $start;\n\
+                     SELECT count() AS total FROM audit{condition} GROUP ALL;"
+                ))
+                .bind(("resource", filter.resource))
+                .bind(("action", filter.action))
+                .bind(("request_id", filter.request_id))
+                .bind(("limit", limit))
+                .bind(("start", start)),
+        )
+        .await?;
+        // Snapshots hold records of any table, so read them as plain JSON
+        let entries: surrealdb::Value = response.take(0)?;
+        let entries: Vec<AuditEntry> = serde_json::from_value(entries.into_inner().into_json())
+            .map_err(|e| Error::Db(Serialization(e.to_string())))?;
+        let total: Option<Total> = response.take(1)?;
+        Ok(AuditPage {
+            entries: entries.into_iter().map(AuditEntry::with_changes).collect(),
+            total: total.map_or(0, |total| total.total),
+        })
+    }
+
+    #[instrument(skip(self), err)]
+    async fn prune(&self, cutoff: DateTime<Local>) -> Result<usize, Error> {
+        let mut response = observe_statements(
+            "audit",
+            "prune",
+            self.db
+                .client
+                .query("DELETE audit WHERE at < <datetime> $cutoff RETURN BEFORE")
+                .bind(("cutoff", cutoff)),
+        )
+        .await?;
+        let pruned: Vec<Thing> = response.take((0, "id"))?;
+        Ok(pruned.len())
+    }
+}
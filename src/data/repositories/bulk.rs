@@ -0,0 +1,294 @@
+use crate::audit;
+use crate::data::stores::BulkOutcome;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use surrealdb::error::Db;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+
+/// Creates every item with one statement each, in a single round trip.
+pub(crate) async fn create_many<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    items: Vec<T>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let mut statements = Vec::with_capacity(items.len());
+    let mut bindings = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        statements.push(format!(
+            "CREATE ONLY type::table($table) CONTENT $item{}",
+            index
+        ));
+        bindings.push((format!("item{}", index), item));
+    }
+
+    run_batch(
+        db,
+        table,
+        repository,
+        "create_many",
+        statements,
+        bindings,
+        Vec::<(String, String)>::new(),
+        atomic,
+    )
+    .await
+}
+
+/// A patch and the record created along with it, bound as one parameter.
+#[derive(Serialize)]
+struct Merge<P, T> {
+    patch: P,
+    created: Option<T>,
+}
+
+/// Merges each patch into its record, reporting missing records as `NotFound`.
+pub(crate) async fn merge_many<T, P>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    patches: Vec<(String, P)>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: Serialize + DeserializeOwned + 'static,
+    P: Serialize + 'static,
+{
+    let patches = patches
+        .into_iter()
+        .map(|(id, patch)| (id, patch, None))
+        .collect();
+    merge_creating(db, table, repository, patches, atomic).await
+}
+
+/// Like `merge_many`, also creating the record paired with a patch, under
+/// the id it carries, in the same statement once the patch has applied.
+pub(crate) async fn merge_creating<T, P>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    patches: Vec<(String, P, Option<T>)>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: Serialize + DeserializeOwned + 'static,
+    P: Serialize + 'static,
+{
+    let ids = patches
+        .iter()
+        .map(|(id, _, _)| id.clone())
+        .collect::<Vec<_>>();
+    let existing = existing_ids(db, table, repository, &ids).await?;
+
+    let mut statements = Vec::new();
+    let mut keys = Vec::new();
+    let mut bindings = Vec::new();
+    for (index, (id, patch, created)) in patches.into_iter().enumerate() {
+        if !existing.contains(&id) {
+            continue;
+        }
+        let merge = format!("UPDATE ONLY type::thing($table, $id{index}) MERGE $item{index}.patch");
+        statements.push(match created {
+            // A block is one statement, so outcomes still line up with items
+            Some(_) => format!(
+                "{{ LET $merged = {merge}; \
+                 IF $merged {{ CREATE ONLY $item{index}.created.id CONTENT $item{index}.created }}; \
+                 $merged; }}"
+            ),
+            None => merge,
+        });
+        keys.push((format!("id{}", index), id));
+        bindings.push((format!("item{}", index), Merge { patch, created }));
+    }
+
+    run_existing(
+        db,
+        table,
+        repository,
+        "merge_many",
+        &ids,
+        &existing,
+        statements,
+        bindings,
+        keys,
+        atomic,
+    )
+    .await
+}
+
+/// Deletes each record, reporting missing records as `NotFound`.
+pub(crate) async fn delete_many<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    ids: Vec<String>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: DeserializeOwned,
+{
+    let existing = existing_ids(db, table, repository, &ids).await?;
+
+    let mut statements = Vec::new();
+    let mut keys = Vec::new();
+    for (index, id) in ids.iter().enumerate() {
+        if !existing.contains(id) {
+            continue;
+        }
+        statements.push(format!(
+            "DELETE ONLY type::thing($table, $id{index}) RETURN BEFORE"
+        ));
+        keys.push((format!("id{}", index), id.clone()));
+    }
+
+    run_existing(
+        db,
+        table,
+        repository,
+        "delete_many",
+        &ids,
+        &existing,
+        statements,
+        Vec::<(String, ())>::new(),
+        keys,
+        atomic,
+    )
+    .await
+}
+
+async fn existing_ids(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    ids: &[String],
+) -> Result<HashSet<String>, Error> {
+    let records = ids
+        .iter()
+        .map(|id| Thing::from((table, id.as_str())))
+        .collect::<Vec<_>>();
+    let mut response = observe_statements(
+        repository,
+        "existing_ids",
+        db.client
+            .query("SELECT VALUE <string> meta::id(id) FROM $records")
+            .bind(("records", records)),
+    )
+    .await?;
+    let existing: Vec<String> = response.take(0)?;
+    Ok(existing.into_iter().collect())
+}
+
+/// Runs statements for the records that exist and slots their outcomes back
+/// between the missing ones. In atomic mode a missing record aborts the batch
+/// before anything is written.
+#[allow(clippy::too_many_arguments)]
+async fn run_existing<T, B>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    method: &'static str,
+    ids: &[String],
+    existing: &HashSet<String>,
+    statements: Vec<String>,
+    bindings: Vec<(String, B)>,
+    keys: Vec<(String, String)>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: DeserializeOwned,
+    B: Serialize + 'static,
+{
+    let all_exist = ids.iter().all(|id| existing.contains(id));
+    let mut outcomes = if atomic && !all_exist {
+        Vec::new()
+    } else {
+        run_batch(
+            db, table, repository, method, statements, bindings, keys, atomic,
+        )
+        .await?
+    }
+    .into_iter();
+
+    Ok(ids
+        .iter()
+        .map(|id| {
+            if !existing.contains(id) {
+                BulkOutcome::NotFound
+            } else if atomic && !all_exist {
+                BulkOutcome::Aborted
+            } else {
+                outcomes.next().unwrap_or(BulkOutcome::Aborted)
+            }
+        })
+        .collect())
+}
+
+/// Sends the statements as one query, binding each item's content and record
+/// key under the name its statement refers to.
+#[allow(clippy::too_many_arguments)]
+async fn run_batch<T, B>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    method: &'static str,
+    statements: Vec<String>,
+    bindings: Vec<(String, B)>,
+    keys: Vec<(String, String)>,
+    atomic: bool,
+) -> Result<Vec<BulkOutcome<T>>, Error>
+where
+    T: DeserializeOwned,
+    B: Serialize + 'static,
+{
+    let count = statements.len();
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut sql = statements.join(";\n");
+    if atomic {
+        sql = format!("BEGIN TRANSACTION;\n{};\nCOMMIT TRANSACTION;", sql);
+    }
+    let mut query = db
+        .client
+        .query(sql)
+        .bind(("table", table.to_string()))
+        .bind(audit::binding());
+    for binding in bindings {
+        query = query.bind(binding);
+    }
+    for key in keys {
+        query = query.bind(key);
+    }
+    let mut response = observe_statements(repository, method, query).await?;
+
+    let mut outcomes = Vec::with_capacity(count);
+    for index in 0..count {
+        outcomes.push(match response.take::<Option<T>>(index) {
+            Ok(Some(record)) => BulkOutcome::Done(record),
+            Ok(None) => BulkOutcome::NotFound,
+            Err(e) if is_not_executed(&e) => BulkOutcome::Aborted,
+            Err(e) => {
+                tracing::error!(error = %e, repository, method, index, "Bulk item failed");
+                BulkOutcome::Failed(e.to_string())
+            }
+        });
+    }
+    Ok(outcomes)
+}
+
+/// Whether the statement was skipped because its transaction failed. Remote
+/// engines only hand back the message, so compare on that.
+fn is_not_executed(error: &Error) -> bool {
+    error
+        .to_string()
+        .contains(&Db::QueryNotExecuted.to_string())
+}
@@ -0,0 +1,59 @@
+use crate::data::models::history::Version;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use surrealdb::Error;
+
+/// Every version of record `id`, newest first.
+pub(crate) async fn list<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    id: String,
+) -> Result<Vec<Version<T>>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut response = observe_statements(
+        repository,
+        "history",
+        db.client
+            .query(
+                "SELECT version, at, action, record FROM history \
+                 WHERE resource = type::thing($table, $id) ORDER BY version DESC",
+            )
+            .bind(("table", table.to_string()))
+            .bind(("id", id)),
+    )
+    .await?;
+    response.take(0)
+}
+
+/// The latest version of record `id` written at or before `at`.
+pub(crate) async fn as_of<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    id: String,
+    at: DateTime<Local>,
+) -> Result<Option<Version<T>>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut response = observe_statements(
+        repository,
+        "as_of",
+        db.client
+            .query(
+                "SELECT version, at, action, record FROM history \
+                 WHERE resource = type::thing($table, $id) AND at <= <datetime> $at \
+                 ORDER BY version DESC LIMIT 1",
+            )
+            .bind(("table", table.to_string()))
+            .bind(("id", id))
+            .bind(("at", at)),
+    )
+    .await?;
+    response.take(0)
+}
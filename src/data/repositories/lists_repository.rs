@@ -0,0 +1,137 @@
+use crate::audit;
+use crate::data::models::list::{ListDeletion, OnDelete, TodoList};
+use crate::data::repositories::paging;
+use crate::data::stores::ListStore;
+use crate::db::Database;
+use crate::metrics::{observe_query, observe_statements};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::err::Error::Thrown;
+use surrealdb::Error;
+use tracing::{instrument, Level};
+
+/// What the delete transaction saw and removed.
+#[derive(Deserialize)]
+struct Deletion {
+    total: usize,
+    list: Option<TodoList>,
+}
+
+pub struct ListsRepository {
+    db: Arc<Database>,
+    table: String,
+}
+
+impl ListsRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        ListsRepository {
+            db,
+            table: String::from("list"),
+        }
+    }
+}
+
+#[async_trait]
+impl ListStore for ListsRepository {
+    #[instrument(skip(self), err)]
+    async fn get_all(&self) -> Result<Vec<TodoList>, Error> {
+        let records = observe_query("lists", "get_all", self.db.client.select(&self.table)).await?;
+        Ok(records)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<TodoList>, Error> {
+        paging::page(&self.db, &self.table, "lists", after, limit).await
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_id(&self, id: String) -> Result<TodoList, Error> {
+        if let Some(record) = observe_query(
+            "lists",
+            "get_by_id",
+            self.db.client.select((&self.table, id.clone())),
+        )
+        .await?
+        {
+            return Ok(record);
+        }
+
+        Err(Error::Db(Thrown(format!("List with id {} not found", id))))
+    }
+
+    #[instrument(skip(self, content), err)]
+    async fn create(&self, content: TodoList) -> Result<TodoList, Error> {
+        let record = observe_query(
+            "lists",
+            "create",
+            self.db.client.create(&self.table).content(content),
+        )
+        .await?
+        .ok_or_else(|| Error::Db(Thrown("Failed to create list".to_string())))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self, content), err)]
+    async fn update(&self, id: String, content: TodoList) -> Result<TodoList, Error> {
+        let record = observe_query(
+            "lists",
+            "update",
+            self.db
+                .client
+                .update((&self.table, id.clone()))
+                .content(content),
+        )
+        .await?
+        .ok_or(Error::Db(Thrown(format!("List with id {} not found", id))))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn delete(&self, id: String) -> Result<TodoList, Error> {
+        let record = observe_query(
+            "lists",
+            "delete",
+            self.db.client.delete((&self.table, id.clone())),
+        )
+        .await?
+        .ok_or(Error::Db(Thrown(format!("List with id {} not found", id))))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn delete_with_todos(
+        &self,
+        id: String,
+        on_delete: OnDelete,
+    ) -> Result<ListDeletion, Error> {
+        let mut response = observe_statements(
+            "lists",
+            "delete_with_todos",
+            self.db
+                .client
+                .query(
+                    "BEGIN TRANSACTION;\n\
+                     LET $total = array::len((SELECT VALUE id FROM todo WHERE list = $list));\n\
+                     LET $deleted = IF $cascade OR $total = 0 {\n\
+                     DELETE todo WHERE list = $list;\n\
+                     (DELETE $list RETURN BEFORE)[0];\n\
+                     };\n\
+                     RETURN { total: $total, list: $deleted };\n\
+                     COMMIT TRANSACTION;",
+                )
+                .bind(("list", TodoList::link(&id)))
+                .bind(("cascade", on_delete == OnDelete::Cascade))
+                .bind(audit::binding()),
+        )
+        .await?;
+        let deletion: Option<Deletion> = response.take(0)?;
+        match deletion {
+            Some(Deletion {
+                list: Some(list), ..
+            }) => Ok(ListDeletion::Deleted(list)),
+            Some(Deletion { total, .. }) if total > 0 => Ok(ListDeletion::Restricted(total)),
+            _ => Err(Error::Db(Thrown(format!("List with id {} not found", id)))),
+        }
+    }
+}
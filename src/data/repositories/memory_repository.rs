@@ -0,0 +1,938 @@
+use crate::data::contact::{ContactError, ContactNormalizer};
+use crate::data::dependencies::Dependency;
+use crate::data::models::audit::{AuditFilter, AuditPage};
+use crate::data::models::history::Version;
+use crate::data::models::list::{ListDeletion, ListStats, OnDelete, TodoList};
+use crate::data::models::outbox::{DomainEvent, PublishOutcome};
+use crate::data::models::role::Role;
+use crate::data::models::search::{SearchHit, SearchPage};
+use crate::data::models::tag::TagCount;
+use crate::data::models::todo::{Todo, TodoFilter, TodoPatch};
+use crate::data::models::user::{User, UserPatch};
+use crate::data::models::webhook::{
+    AttemptOutcome, DeliveryFilter, DeliveryPage, Webhook, WebhookDelivery,
+};
+use crate::data::stores::{
+    AuditStore, BulkOutcome, ListStore, OutboxStore, RoleStore, TodoStore, UserStore, WebhookStore,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
+use surrealdb::err::Error::Thrown;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+use uuid::Uuid;
+
+/// A searchable field: its name, score weight and how to read it.
+type SearchField<T> = (&'static str, f64, fn(&T) -> Option<&str>);
+
+/// Records that carry a SurrealDB record id.
+trait Record: Clone + Serialize + DeserializeOwned {
+    fn set_id(&mut self, id: Thing);
+}
+
+impl Record for Todo {
+    fn set_id(&mut self, id: Thing) {
+        self.id = Some(id);
+    }
+}
+
+impl Record for User {
+    fn set_id(&mut self, id: Thing) {
+        self.id = Some(id);
+    }
+}
+
+impl Record for TodoList {
+    fn set_id(&mut self, id: Thing) {
+        self.id = Some(id);
+    }
+}
+
+impl Record for Role {
+    fn set_id(&mut self, id: Thing) {
+        self.id = Some(id);
+    }
+}
+
+impl Record for Webhook {
+    fn set_id(&mut self, id: Thing) {
+        self.id = Some(id);
+    }
+}
+
+/// A table kept in memory, returning the same errors as the SurrealDB repositories.
+#[derive(Debug)]
+struct MemoryTable<T> {
+    table: &'static str,
+    label: &'static str,
+    records: Mutex<BTreeMap<String, T>>,
+}
+
+// The error type is dictated by the store traits
+#[allow(clippy::result_large_err)]
+impl<T: Record> MemoryTable<T> {
+    fn new(table: &'static str, label: &'static str) -> Self {
+        MemoryTable {
+            table,
+            label,
+            records: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn not_found(&self, field: &str, value: &str) -> Error {
+        Error::Db(Thrown(format!(
+            "{} with {} {} not found",
+            self.label, field, value
+        )))
+    }
+
+    fn all(&self) -> Vec<T> {
+        self.records.lock().unwrap().values().cloned().collect()
+    }
+
+    fn page(&self, after: Option<&str>, limit: usize, keep: impl Fn(&T) -> bool) -> Vec<T> {
+        let records = self.records.lock().unwrap();
+        let start = match after {
+            Some(after) => Bound::Excluded(after.to_string()),
+            None => Bound::Unbounded,
+        };
+        records
+            .range((start, Bound::Unbounded))
+            .map(|(_, record)| record)
+            .filter(|record| keep(record))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn get(&self, id: &str) -> Result<T, Error> {
+        self.records
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| self.not_found("id", id))
+    }
+
+    fn find(&self, field: &str, value: &str, matches: impl Fn(&T) -> bool) -> Result<T, Error> {
+        self.records
+            .lock()
+            .unwrap()
+            .values()
+            .find(|record| matches(record))
+            .cloned()
+            .ok_or_else(|| self.not_found(field, value))
+    }
+
+    fn insert(&self, mut record: T) -> T {
+        let id = Uuid::new_v4().simple().to_string();
+        record.set_id(Thing::from((self.table, id.as_str())));
+        self.records.lock().unwrap().insert(id, record.clone());
+        record
+    }
+
+    fn replace(&self, id: &str, mut record: T) -> Result<T, Error> {
+        let mut records = self.records.lock().unwrap();
+        let existing = records
+            .get_mut(id)
+            .ok_or_else(|| self.not_found("id", id))?;
+        record.set_id(Thing::from((self.table, id)));
+        *existing = record.clone();
+        Ok(record)
+    }
+
+    fn remove(&self, id: &str) -> Result<T, Error> {
+        self.records
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| self.not_found("id", id))
+    }
+
+    /// A plain stand-in for the BM25 indexes: every query word found as a
+    /// whole word in a field adds that field's weight to the score.
+    fn search(
+        &self,
+        fields: &[SearchField<T>],
+        query: &str,
+        start: usize,
+        limit: usize,
+    ) -> SearchPage<T> {
+        let terms = words(query).map(str::to_lowercase).collect::<HashSet<_>>();
+        let mut hits = self
+            .all()
+            .into_iter()
+            .filter_map(|record| {
+                let mut score = 0.0;
+                let mut highlights = BTreeMap::new();
+                for (name, weight, read) in fields {
+                    let Some(text) = read(&record) else { continue };
+                    let matched = words(text)
+                        .filter(|word| terms.contains(&word.to_lowercase()))
+                        .count();
+                    if matched > 0 {
+                        score += weight * matched as f64;
+                        highlights.insert(name.to_string(), highlight(text, &terms));
+                    }
+                }
+                (score > 0.0).then_some(SearchHit {
+                    record,
+                    score,
+                    highlights,
+                })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        SearchPage {
+            total: hits.len(),
+            hits: hits.into_iter().skip(start).take(limit).collect(),
+        }
+    }
+
+    fn insert_many(&self, items: Vec<T>) -> Vec<BulkOutcome<T>> {
+        items
+            .into_iter()
+            .map(|item| BulkOutcome::Done(self.insert(item)))
+            .collect()
+    }
+
+    /// Computes every merged record first so an atomic batch can be dropped
+    /// without having written anything.
+    fn merge_many<P: Serialize>(
+        &self,
+        patches: Vec<(String, P)>,
+        atomic: bool,
+    ) -> Vec<BulkOutcome<T>> {
+        let mut records = self.records.lock().unwrap();
+        let outcomes = patches
+            .iter()
+            .map(|(id, patch)| match records.get(id) {
+                Some(existing) => match merge(existing, patch) {
+                    Ok(record) => BulkOutcome::Done(record),
+                    Err(e) => BulkOutcome::Failed(e.to_string()),
+                },
+                None => BulkOutcome::NotFound,
+            })
+            .collect::<Vec<_>>();
+        if atomic
+            && outcomes
+                .iter()
+                .any(|outcome| !matches!(outcome, BulkOutcome::Done(_)))
+        {
+            return abort_batch(outcomes);
+        }
+
+        for ((id, _), outcome) in patches.iter().zip(&outcomes) {
+            if let BulkOutcome::Done(record) = outcome {
+                records.insert(id.clone(), record.clone());
+            }
+        }
+        outcomes
+    }
+
+    fn remove_many(&self, ids: Vec<String>, atomic: bool) -> Vec<BulkOutcome<T>> {
+        let mut records = self.records.lock().unwrap();
+        if atomic && ids.iter().any(|id| !records.contains_key(id)) {
+            let outcomes = ids
+                .iter()
+                .map(|id| match records.get(id) {
+                    Some(record) => BulkOutcome::Done(record.clone()),
+                    None => BulkOutcome::NotFound,
+                })
+                .collect();
+            return abort_batch(outcomes);
+        }
+
+        ids.iter()
+            .map(|id| match records.remove(id) {
+                Some(record) => BulkOutcome::Done(record),
+                None => BulkOutcome::NotFound,
+            })
+            .collect()
+    }
+}
+
+fn merge<T: Record, P: Serialize>(record: &T, patch: &P) -> Result<T, serde_json::Error> {
+    let mut merged = serde_json::to_value(record)?;
+    let changes = serde_json::to_value(patch)?;
+    if let (Some(target), Some(changes)) = (merged.as_object_mut(), changes.as_object()) {
+        for (key, value) in changes {
+            target.insert(key.clone(), value.clone());
+        }
+    }
+    serde_json::from_value(merged)
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// Wraps each word of `text` found in `terms` in `<mark>` tags.
+fn highlight(text: &str, terms: &HashSet<String>) -> String {
+    let mut marked = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(char::is_alphanumeric) {
+        marked.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        if terms.contains(&word.to_lowercase()) {
+            marked.push_str("<mark>");
+            marked.push_str(word);
+            marked.push_str("</mark>");
+        } else {
+            marked.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    marked.push_str(rest);
+    marked
+}
+
+fn invalid_contact(error: ContactError) -> Error {
+    Error::Db(Thrown(error.to_string()))
+}
+
+/// Marks the items that would have succeeded as aborted.
+fn abort_batch<T>(outcomes: Vec<BulkOutcome<T>>) -> Vec<BulkOutcome<T>> {
+    outcomes
+        .into_iter()
+        .map(|outcome| match outcome {
+            BulkOutcome::Done(_) => BulkOutcome::Aborted,
+            other => other,
+        })
+        .collect()
+}
+
+/// In-memory `TodoStore` for handler tests.
+#[derive(Debug)]
+pub struct InMemoryTodos {
+    table: MemoryTable<Todo>,
+    dependencies: Mutex<Vec<Dependency>>,
+}
+
+impl Default for InMemoryTodos {
+    fn default() -> Self {
+        InMemoryTodos {
+            table: MemoryTable::new("todo", "Todo"),
+            dependencies: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl InMemoryTodos {
+    /// Stores a next occurrence under the id it was given up front.
+    fn insert_next(&self, next: Todo) -> Todo {
+        match next.id.as_ref() {
+            Some(id) => {
+                let mut records = self.table.records.lock().unwrap();
+                records.insert(id.id.to_raw(), next.clone());
+                next
+            }
+            None => self.table.insert(next),
+        }
+    }
+}
+
+#[async_trait]
+impl TodoStore for InMemoryTodos {
+    async fn get_all(&self) -> Result<Vec<Todo>, Error> {
+        Ok(self.table.all())
+    }
+
+    async fn page(
+        &self,
+        filter: TodoFilter,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<Todo>, Error> {
+        let now = Local::now();
+        Ok(self
+            .table
+            .page(after.as_deref(), limit, |todo| filter.matches(todo, now)))
+    }
+
+    async fn list(&self, filter: TodoFilter) -> Result<Vec<Todo>, Error> {
+        let now = Local::now();
+        let mut todos = self
+            .table
+            .all()
+            .into_iter()
+            .filter(|todo| filter.matches(todo, now))
+            .collect::<Vec<_>>();
+        filter.sort(&mut todos);
+        Ok(todos)
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<Todo, Error> {
+        self.table.get(&id)
+    }
+
+    async fn get_by_title(&self, title: String) -> Result<Todo, Error> {
+        self.table.find("title", &title, |todo| todo.title == title)
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<Todo>, Error> {
+        let fields: [SearchField<Todo>; 2] = [
+            ("title", 2.0, |todo| Some(todo.title.as_str())),
+            ("content", 1.0, |todo| todo.content.as_deref()),
+        ];
+        Ok(self.table.search(&fields, &query, start, limit))
+    }
+
+    async fn create(&self, content: Todo) -> Result<Todo, Error> {
+        Ok(self.table.insert(content))
+    }
+
+    async fn update(&self, id: String, content: Todo) -> Result<Todo, Error> {
+        self.table.replace(&id, content)
+    }
+
+    async fn update_with_next(
+        &self,
+        id: String,
+        content: Todo,
+        next: Todo,
+    ) -> Result<(Todo, Todo), Error> {
+        let todo = self.table.replace(&id, content)?;
+        Ok((todo, self.insert_next(next)))
+    }
+
+    /// Mirrors the database, which drops a deleted todo's dependency edges and
+    /// detaches its subtasks.
+    async fn delete(&self, id: String) -> Result<Todo, Error> {
+        let todo = self.table.remove(&id)?;
+        self.dependencies
+            .lock()
+            .unwrap()
+            .retain(|edge| edge.todo != id && edge.blocker != id);
+        let link = Todo::link(&id);
+        for subtask in self.table.records.lock().unwrap().values_mut() {
+            if subtask.parent.as_ref() == Some(&link) {
+                subtask.parent = None;
+            }
+        }
+        Ok(todo)
+    }
+
+    /// Versions are written by the database, so these stores keep none.
+    async fn history(&self, _id: String) -> Result<Vec<Version<Todo>>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn as_of(
+        &self,
+        _id: String,
+        _at: DateTime<Local>,
+    ) -> Result<Option<Version<Todo>>, Error> {
+        Ok(None)
+    }
+
+    async fn add_tags(&self, id: String, tags: Vec<String>) -> Result<Todo, Error> {
+        let mut todo = self.table.get(&id)?;
+        let current = todo.tags.get_or_insert_with(Vec::new);
+        for tag in tags {
+            if !current.contains(&tag) {
+                current.push(tag);
+            }
+        }
+        todo.updated_at = Some(Local::now());
+        self.table.replace(&id, todo)
+    }
+
+    async fn remove_tag(&self, id: String, tag: String) -> Result<Todo, Error> {
+        let mut todo = self.table.get(&id)?;
+        if let Some(tags) = todo.tags.as_mut() {
+            tags.retain(|current| *current != tag);
+        }
+        todo.updated_at = Some(Local::now());
+        self.table.replace(&id, todo)
+    }
+
+    async fn tag_counts(&self) -> Result<Vec<TagCount>, Error> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for todo in self.table.all() {
+            for tag in todo.tags.unwrap_or_default() {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut counts = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect::<Vec<_>>();
+        counts.sort_by_key(|tag| std::cmp::Reverse(tag.count));
+        Ok(counts)
+    }
+
+    async fn blockers(&self, id: String) -> Result<Vec<Todo>, Error> {
+        let dependencies = self.dependencies.lock().unwrap();
+        let records = self.table.records.lock().unwrap();
+        Ok(dependencies
+            .iter()
+            .filter(|edge| edge.todo == id)
+            .filter_map(|edge| records.get(&edge.blocker).cloned())
+            .collect())
+    }
+
+    async fn dependencies(&self) -> Result<Vec<Dependency>, Error> {
+        Ok(self.dependencies.lock().unwrap().clone())
+    }
+
+    async fn add_blocker(&self, id: String, blocker_id: String) -> Result<(), Error> {
+        let edge = Dependency {
+            todo: id,
+            blocker: blocker_id,
+        };
+        let mut dependencies = self.dependencies.lock().unwrap();
+        if dependencies.contains(&edge) {
+            return Err(Error::Db(Thrown(format!(
+                "Todo with id {} is already blocked by {}",
+                edge.todo, edge.blocker
+            ))));
+        }
+        dependencies.push(edge);
+        Ok(())
+    }
+
+    async fn remove_blocker(&self, id: String, blocker_id: String) -> Result<(), Error> {
+        let mut dependencies = self.dependencies.lock().unwrap();
+        let before = dependencies.len();
+        dependencies.retain(|edge| edge.todo != id || edge.blocker != blocker_id);
+        if dependencies.len() == before {
+            return Err(Error::Db(Thrown(format!(
+                "Todo with id {} is not blocked by {}",
+                id, blocker_id
+            ))));
+        }
+        Ok(())
+    }
+
+    async fn list_stats(&self, list_id: String) -> Result<ListStats, Error> {
+        let now = Local::now();
+        let list = TodoList::link(&list_id);
+        let todos = self
+            .table
+            .all()
+            .into_iter()
+            .filter(|todo| todo.list.as_ref() == Some(&list))
+            .collect::<Vec<_>>();
+        Ok(ListStats::new(
+            todos.len(),
+            todos
+                .iter()
+                .filter(|todo| todo.completed == Some(true))
+                .count(),
+            todos.iter().filter(|todo| todo.is_overdue(now)).count(),
+        ))
+    }
+
+    async fn fire_due_reminders(&self, now: DateTime<Local>) -> Result<Vec<Todo>, Error> {
+        let mut records = self.table.records.lock().unwrap();
+        let mut fired = Vec::new();
+        for todo in records.values_mut() {
+            let due = todo.remind_at.is_some_and(|remind_at| remind_at <= now);
+            if due && todo.reminder_fired_at.is_none() && todo.completed != Some(true) {
+                todo.reminder_fired_at = Some(now);
+                fired.push(todo.clone());
+            }
+        }
+        Ok(fired)
+    }
+
+    async fn create_many(
+        &self,
+        items: Vec<Todo>,
+        _atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        Ok(self.table.insert_many(items))
+    }
+
+    async fn merge_many(
+        &self,
+        patches: Vec<(String, TodoPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        let nexts = patches
+            .iter()
+            .map(|(_, patch)| patch.next.clone())
+            .collect::<Vec<_>>();
+        let outcomes = self.table.merge_many(patches, atomic);
+        for (outcome, next) in outcomes.iter().zip(nexts) {
+            if let (BulkOutcome::Done(_), Some(next)) = (outcome, next) {
+                self.insert_next(next);
+            }
+        }
+        Ok(outcomes)
+    }
+
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        Ok(self.table.remove_many(ids, atomic))
+    }
+}
+
+/// In-memory `UserStore` for handler tests.
+#[derive(Debug)]
+pub struct InMemoryUsers {
+    table: MemoryTable<User>,
+    contacts: ContactNormalizer,
+}
+
+impl InMemoryUsers {
+    pub fn new(contacts: ContactNormalizer) -> Self {
+        InMemoryUsers {
+            table: MemoryTable::new("user", "User"),
+            contacts,
+        }
+    }
+}
+
+impl Default for InMemoryUsers {
+    fn default() -> Self {
+        InMemoryUsers::new(ContactNormalizer::default())
+    }
+}
+
+#[async_trait]
+impl UserStore for InMemoryUsers {
+    async fn get_all(&self) -> Result<Vec<User>, Error> {
+        Ok(self.table.all())
+    }
+
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<User>, Error> {
+        Ok(self.table.page(after.as_deref(), limit, |_| true))
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<User, Error> {
+        self.table.get(&id)
+    }
+
+    async fn get_by_email(&self, email: String) -> Result<User, Error> {
+        let email = self.contacts.email(&email);
+        self.table.find("email", &email, |user| user.email == email)
+    }
+
+    async fn get_by_phone(&self, phone: String) -> Result<User, Error> {
+        let phone = self.contacts.phone(&phone).map_err(invalid_contact)?;
+        self.table.find("phone", &phone, |user| {
+            user.phone.as_deref() == Some(phone.as_str())
+        })
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<User>, Error> {
+        let fields: [SearchField<User>; 2] = [
+            ("name", 2.0, |user| Some(user.name.as_str())),
+            ("email", 1.0, |user| Some(user.email.as_str())),
+        ];
+        Ok(self.table.search(&fields, &query, start, limit))
+    }
+
+    async fn create(&self, mut user: User) -> Result<User, Error> {
+        user.normalize(&self.contacts).map_err(invalid_contact)?;
+        user.created_at = Some(Local::now());
+        Ok(self.table.insert(user))
+    }
+
+    async fn update(&self, id: String, mut user: User) -> Result<User, Error> {
+        user.normalize(&self.contacts).map_err(invalid_contact)?;
+        user.updated_at = Some(Local::now());
+        self.table.replace(&id, user)
+    }
+
+    async fn delete(&self, id: String) -> Result<User, Error> {
+        self.table.remove(&id)
+    }
+
+    async fn history(&self, _id: String) -> Result<Vec<Version<User>>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn as_of(
+        &self,
+        _id: String,
+        _at: DateTime<Local>,
+    ) -> Result<Option<Version<User>>, Error> {
+        Ok(None)
+    }
+
+    async fn create_many(
+        &self,
+        mut users: Vec<User>,
+        _atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        let now = Local::now();
+        for user in users.iter_mut() {
+            user.normalize(&self.contacts).map_err(invalid_contact)?;
+            user.created_at = Some(now);
+        }
+        Ok(self.table.insert_many(users))
+    }
+
+    async fn merge_many(
+        &self,
+        mut patches: Vec<(String, UserPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        for (_, patch) in patches.iter_mut() {
+            patch.normalize(&self.contacts).map_err(invalid_contact)?;
+        }
+        Ok(self.table.merge_many(patches, atomic))
+    }
+
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        Ok(self.table.remove_many(ids, atomic))
+    }
+}
+
+/// In-memory `ListStore` for handler tests, deleting from `todos` on cascade.
+#[derive(Debug)]
+pub struct InMemoryLists {
+    table: MemoryTable<TodoList>,
+    todos: Arc<InMemoryTodos>,
+}
+
+impl InMemoryLists {
+    pub fn new(todos: Arc<InMemoryTodos>) -> Self {
+        InMemoryLists {
+            table: MemoryTable::new("list", "List"),
+            todos,
+        }
+    }
+}
+
+#[async_trait]
+impl ListStore for InMemoryLists {
+    async fn get_all(&self) -> Result<Vec<TodoList>, Error> {
+        Ok(self.table.all())
+    }
+
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<TodoList>, Error> {
+        Ok(self.table.page(after.as_deref(), limit, |_| true))
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<TodoList, Error> {
+        self.table.get(&id)
+    }
+
+    async fn create(&self, content: TodoList) -> Result<TodoList, Error> {
+        Ok(self.table.insert(content))
+    }
+
+    async fn update(&self, id: String, content: TodoList) -> Result<TodoList, Error> {
+        self.table.replace(&id, content)
+    }
+
+    async fn delete(&self, id: String) -> Result<TodoList, Error> {
+        self.table.remove(&id)
+    }
+
+    async fn delete_with_todos(
+        &self,
+        id: String,
+        on_delete: OnDelete,
+    ) -> Result<ListDeletion, Error> {
+        let list = TodoList::link(&id);
+        // Held throughout, as the transaction would be
+        let mut todos = self.todos.table.records.lock().unwrap();
+        let ids = todos
+            .iter()
+            .filter(|(_, todo)| todo.list.as_ref() == Some(&list))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        if on_delete == OnDelete::Restrict && !ids.is_empty() {
+            return Ok(ListDeletion::Restricted(ids.len()));
+        }
+        let deleted = self.table.remove(&id)?;
+        for id in ids {
+            todos.remove(&id);
+        }
+        Ok(ListDeletion::Deleted(deleted))
+    }
+}
+
+/// In-memory `RoleStore` for handler tests.
+#[derive(Debug)]
+pub struct InMemoryRoles(MemoryTable<Role>);
+
+impl Default for InMemoryRoles {
+    fn default() -> Self {
+        InMemoryRoles(MemoryTable::new("role", "Role"))
+    }
+}
+
+#[async_trait]
+impl RoleStore for InMemoryRoles {
+    async fn get_all(&self) -> Result<Vec<Role>, Error> {
+        Ok(self.0.all())
+    }
+
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<Role>, Error> {
+        Ok(self.0.page(after.as_deref(), limit, |_| true))
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<Role, Error> {
+        self.0.get(&id)
+    }
+
+    async fn get_by_name(&self, name: String) -> Result<Role, Error> {
+        self.0.find("name", &name, |role| role.name == name)
+    }
+
+    async fn create(&self, content: Role) -> Result<Role, Error> {
+        Ok(self.0.insert(content))
+    }
+
+    async fn update(&self, id: String, content: Role) -> Result<Role, Error> {
+        self.0.replace(&id, content)
+    }
+
+    async fn delete(&self, id: String) -> Result<Role, Error> {
+        self.0.remove(&id)
+    }
+}
+
+/// In-memory `AuditStore` for handler tests. Audit entries are written by the
+/// database, so nothing writes to this one and it reads back empty.
+#[derive(Debug, Default)]
+pub struct InMemoryAudit;
+
+#[async_trait]
+impl AuditStore for InMemoryAudit {
+    async fn list(
+        &self,
+        _filter: AuditFilter,
+        _start: usize,
+        _limit: usize,
+    ) -> Result<AuditPage, Error> {
+        Ok(AuditPage::default())
+    }
+
+    async fn prune(&self, _cutoff: DateTime<Local>) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
+
+/// In-memory `WebhookStore` for handler tests. Nothing is written to the
+/// outbox here, so the delivery queue stays empty.
+#[derive(Debug)]
+pub struct InMemoryWebhooks(MemoryTable<Webhook>);
+
+impl Default for InMemoryWebhooks {
+    fn default() -> Self {
+        InMemoryWebhooks(MemoryTable::new("webhook", "Webhook"))
+    }
+}
+
+#[async_trait]
+impl WebhookStore for InMemoryWebhooks {
+    async fn get_all(&self) -> Result<Vec<Webhook>, Error> {
+        Ok(self.0.all())
+    }
+
+    async fn get_by_id(&self, id: String) -> Result<Webhook, Error> {
+        self.0.get(&id)
+    }
+
+    async fn create(&self, webhook: Webhook) -> Result<Webhook, Error> {
+        Ok(self.0.insert(webhook))
+    }
+
+    async fn update(&self, id: String, webhook: Webhook) -> Result<Webhook, Error> {
+        self.0.replace(&id, webhook)
+    }
+
+    async fn delete(&self, id: String) -> Result<Webhook, Error> {
+        self.0.remove(&id)
+    }
+
+    async fn deliveries(
+        &self,
+        _filter: DeliveryFilter,
+        _start: usize,
+        _limit: usize,
+    ) -> Result<DeliveryPage, Error> {
+        Ok(DeliveryPage::default())
+    }
+
+    async fn claim_due(
+        &self,
+        _now: DateTime<Local>,
+        _lease: DateTime<Local>,
+        _limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn record_attempt(&self, id: String, _outcome: AttemptOutcome) -> Result<(), Error> {
+        Err(Error::Db(Thrown(format!(
+            "Delivery with id {} not found",
+            id
+        ))))
+    }
+
+    async fn redeliver(&self, id: String, _now: DateTime<Local>) -> Result<WebhookDelivery, Error> {
+        Err(Error::Db(Thrown(format!(
+            "Delivery with id {} not found",
+            id
+        ))))
+    }
+
+    async fn enqueue(&self, _event: DomainEvent) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// In-memory `OutboxStore` for handler tests. Events are written by the
+/// database, so the outbox here stays empty.
+#[derive(Debug, Default)]
+pub struct InMemoryOutbox;
+
+#[async_trait]
+impl OutboxStore for InMemoryOutbox {
+    async fn claim_due(
+        &self,
+        _now: DateTime<Local>,
+        _lease: DateTime<Local>,
+        _limit: usize,
+    ) -> Result<Vec<DomainEvent>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn record_attempt(&self, id: String, _outcome: PublishOutcome) -> Result<(), Error> {
+        Err(Error::Db(Thrown(format!("Event with id {} not found", id))))
+    }
+
+    async fn release(&self, _ids: Vec<String>, _now: DateTime<Local>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn prune(&self, _cutoff: DateTime<Local>) -> Result<usize, Error> {
+        Ok(0)
+    }
+}
@@ -1,3 +1,15 @@
+#[cfg(any(test, feature = "testing"))]
+pub mod memory_repository;
+mod bulk;
+mod history;
+mod paging;
+mod records;
+mod search;
+pub mod audit_repository;
+pub mod lists_repository;
+pub mod outbox_repository;
+pub mod rate_limits_repository;
 pub mod roles_repository;
 pub mod todos_repository;
 pub mod users_repository;
+pub mod webhooks_repository;
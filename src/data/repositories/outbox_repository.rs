@@ -0,0 +1,126 @@
+use crate::data::models::outbox::{DomainEvent, PublishOutcome};
+use crate::data::stores::OutboxStore;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use std::sync::Arc;
+use surrealdb::error::Db::Serialization;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+use tracing::instrument;
+
+pub struct OutboxRepository {
+    db: Arc<Database>,
+}
+
+impl OutboxRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        OutboxRepository { db }
+    }
+}
+
+#[async_trait]
+impl OutboxStore for OutboxRepository {
+    #[instrument(skip(self), err)]
+    async fn claim_due(
+        &self,
+        now: DateTime<Local>,
+        lease: DateTime<Local>,
+        limit: usize,
+    ) -> Result<Vec<DomainEvent>, Error> {
+        let mut response = observe_statements(
+            "outbox",
+            "claim_due",
+            self.db
+                .client
+                .query(
+                    "LET $blocked = (SELECT VALUE sequence FROM outbox \
+                     WHERE status = 'pending' AND next_attempt_at > <datetime> $now \
+                     ORDER BY sequence LIMIT 1)[0];\n\
+                     UPDATE (SELECT id, sequence FROM outbox \
+                     WHERE status = 'pending' AND next_attempt_at <= <datetime> $now \
+                     AND ($blocked = NONE OR sequence < $blocked) \
+                     ORDER BY sequence LIMIT $limit).id \
+                     SET next_attempt_at = <datetime> $lease RETURN AFTER;",
+                )
+                .bind(("now", now))
+                .bind(("lease", lease))
+                .bind(("limit", limit)),
+        )
+        .await?;
+        // Events carry records of any table, so read them as plain JSON
+        let events: surrealdb::Value = response.take(1)?;
+        let mut events: Vec<DomainEvent> = serde_json::from_value(events.into_inner().into_json())
+            .map_err(|e| Error::Db(Serialization(e.to_string())))?;
+        events.sort_by_key(|event| event.sequence);
+        Ok(events)
+    }
+
+    #[instrument(skip(self, outcome), err)]
+    async fn record_attempt(&self, id: String, outcome: PublishOutcome) -> Result<(), Error> {
+        observe_statements(
+            "outbox",
+            "record_attempt",
+            self.db
+                .client
+                .query(
+                    "UPDATE type::thing('outbox', $id) SET \
+                     attempts += 1, \
+                     status = $status, \
+                     published_to = $published_to, \
+                     next_attempt_at = IF $next THEN <datetime> $next ELSE NONE END, \
+                     last_error = $error, \
+                     published_at = IF $status = 'published' THEN time::now() ELSE published_at END \
+                     WHERE id != NONE",
+                )
+                .bind(("id", id))
+                .bind(("status", outcome.status))
+                .bind(("published_to", outcome.published_to))
+                .bind(("next", outcome.next_attempt_at))
+                .bind(("error", outcome.error)),
+        )
+        .await?
+        .check()?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn release(&self, ids: Vec<String>, now: DateTime<Local>) -> Result<(), Error> {
+        observe_statements(
+            "outbox",
+            "release",
+            self.db
+                .client
+                .query(
+                    "FOR $id IN $ids { \
+                     UPDATE type::thing('outbox', $id) SET next_attempt_at = <datetime> $now \
+                     WHERE status = 'pending' \
+                     };",
+                )
+                .bind(("ids", ids))
+                .bind(("now", now)),
+        )
+        .await?
+        .check()?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn prune(&self, cutoff: DateTime<Local>) -> Result<usize, Error> {
+        let mut response = observe_statements(
+            "outbox",
+            "prune",
+            self.db
+                .client
+                .query(
+                    "DELETE outbox WHERE status = 'published' \
+                     AND published_at < <datetime> $cutoff RETURN BEFORE",
+                )
+                .bind(("cutoff", cutoff)),
+        )
+        .await?;
+        let pruned: Vec<Thing> = response.take((0, "id"))?;
+        Ok(pruned.len())
+    }
+}
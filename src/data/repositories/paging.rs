@@ -0,0 +1,34 @@
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use serde::de::DeserializeOwned;
+use surrealdb::Error;
+
+/// Up to `limit` records ordered by id, starting after the record keyed
+/// `after`. Walking pages by key rather than offset keeps each query cheap and
+/// unaffected by rows inserted behind the cursor.
+pub(crate) async fn page<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    after: Option<String>,
+    limit: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut response = observe_statements(
+        repository,
+        "page",
+        db.client
+            .query(
+                "SELECT * FROM type::table($table) \
+                 WHERE $after = NONE OR id > type::thing($table, $after) \
+                 ORDER BY id LIMIT $limit",
+            )
+            .bind(("table", table.to_string()))
+            .bind(("after", after))
+            .bind(("limit", limit)),
+    )
+    .await?;
+    response.take(0)
+}
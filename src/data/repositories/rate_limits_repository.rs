@@ -0,0 +1,84 @@
+use crate::data::models::rate_limit::{Quota, Take};
+use crate::data::stores::RateLimitStore;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::error::Db::Thrown;
+use surrealdb::sql::Thing;
+use surrealdb::Error;
+use tracing::instrument;
+
+#[derive(Deserialize)]
+struct Taken {
+    allowed: bool,
+    tokens: f64,
+}
+
+/// Buckets kept in SurrealDB, so every instance sharing the database draws
+/// on the same ones. Each take is one transaction.
+pub struct RateLimitsRepository {
+    db: Arc<Database>,
+}
+
+impl RateLimitsRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        RateLimitsRepository { db }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RateLimitsRepository {
+    #[instrument(skip(self), err)]
+    async fn take(&self, key: String, quota: Quota, now: DateTime<Local>) -> Result<Take, Error> {
+        let mut response = observe_statements(
+            "rate_limits",
+            "take",
+            self.db
+                .client
+                .query(
+                    "BEGIN TRANSACTION;\n\
+                     LET $id = type::thing('rate_limit', $key);\n\
+                     LET $bucket = (SELECT * FROM ONLY $id);\n\
+                     LET $tokens = IF $bucket THEN math::min([$burst, \
+                     $bucket.tokens + math::max([0, $now - $bucket.updated_ms]) * $rate]) \
+                     ELSE $burst END;\n\
+                     LET $allowed = $tokens >= 1;\n\
+                     LET $left = IF $allowed THEN $tokens - 1 ELSE $tokens END;\n\
+                     UPSERT $id SET tokens = $left, updated_ms = math::max([$now, $bucket.updated_ms ?? $now]), \
+                     refilled_at = time::from::millis(<int> math::ceil($now + ($burst - $left) / $rate));\n\
+                     RETURN { allowed: $allowed, tokens: $left };\n\
+                     COMMIT TRANSACTION;",
+                )
+                .bind(("key", key))
+                .bind(("burst", f64::from(quota.burst)))
+                .bind(("rate", quota.rate()))
+                .bind(("now", now.timestamp_millis())),
+        )
+        .await?;
+        let taken: Option<Taken> = response.take(0)?;
+        let taken =
+            taken.ok_or_else(|| Error::Db(Thrown("Rate limit bucket not updated".to_string())))?;
+        Ok(Take {
+            allowed: taken.allowed,
+            tokens: taken.tokens,
+        })
+    }
+
+    #[instrument(skip(self), err)]
+    async fn prune(&self, now: DateTime<Local>) -> Result<usize, Error> {
+        let mut response = observe_statements(
+            "rate_limits",
+            "prune",
+            self.db
+                .client
+                .query("DELETE rate_limit WHERE refilled_at < <datetime> $now RETURN BEFORE")
+                .bind(("now", now)),
+        )
+        .await?;
+        let pruned: Vec<Thing> = response.take((0, "id"))?;
+        Ok(pruned.len())
+    }
+}
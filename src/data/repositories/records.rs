@@ -0,0 +1,77 @@
+use crate::audit;
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use surrealdb::Error;
+
+/// Creates a record, binding the audit context for the table's audit event.
+pub(crate) async fn create<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    content: T,
+) -> Result<Option<T>, Error>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let mut response = observe_statements(
+        repository,
+        "create",
+        db.client
+            .query("CREATE ONLY type::table($table) CONTENT $content")
+            .bind(("table", table.to_string()))
+            .bind(("content", content))
+            .bind(audit::binding()),
+    )
+    .await?;
+    response.take(0)
+}
+
+/// Replaces the content of record `id`, or returns `None` if it is missing.
+pub(crate) async fn update<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    id: String,
+    content: T,
+) -> Result<Option<T>, Error>
+where
+    T: Serialize + DeserializeOwned + 'static,
+{
+    let mut response = observe_statements(
+        repository,
+        "update",
+        db.client
+            .query("UPDATE type::thing($table, $id) CONTENT $content")
+            .bind(("table", table.to_string()))
+            .bind(("id", id))
+            .bind(("content", content))
+            .bind(audit::binding()),
+    )
+    .await?;
+    response.take(0)
+}
+
+/// Deletes record `id`, returning it, or `None` if it was missing.
+pub(crate) async fn delete<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    id: String,
+) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut response = observe_statements(
+        repository,
+        "delete",
+        db.client
+            .query("DELETE type::thing($table, $id) RETURN BEFORE")
+            .bind(("table", table.to_string()))
+            .bind(("id", id))
+            .bind(audit::binding()),
+    )
+    .await?;
+    response.take(0)
+}
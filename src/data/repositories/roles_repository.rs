@@ -1,8 +1,14 @@
 use crate::db::Database;
+use crate::data::repositories::paging;
+use crate::data::repositories::records;
+use crate::data::stores::RoleStore;
+use crate::metrics::{observe_query, observe_statements};
 use crate::data::models::role::Role;
+use async_trait::async_trait;
 use std::sync::Arc;
 use surrealdb::err::Error::Thrown;
 use surrealdb::Error;
+use tracing::{instrument, Level};
 
 pub struct RolesRepository {
     db: Arc<Database>,
@@ -16,29 +22,48 @@ impl RolesRepository {
             table: String::from("role"),
         }
     }
+}
 
-    pub async fn get_all(&self) -> Result<Vec<Role>, Error> {
-        let records = self.db.client.select(&self.table).await?;
+#[async_trait]
+impl RoleStore for RolesRepository {
+    #[instrument(skip(self), err)]
+    async fn get_all(&self) -> Result<Vec<Role>, Error> {
+        let records = observe_query("roles", "get_all", self.db.client.select(&self.table)).await?;
         Ok(records)
     }
 
-    pub async fn get_by_id(&self, id: String) -> Result<Role, Error> {
-        if let Some(record) = self.db.client.select((&self.table, id.clone())).await? {
+    #[instrument(skip(self), err)]
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<Role>, Error> {
+        paging::page(&self.db, &self.table, "roles", after, limit).await
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_id(&self, id: String) -> Result<Role, Error> {
+        if let Some(record) = observe_query(
+            "roles",
+            "get_by_id",
+            self.db.client.select((&self.table, id.clone())),
+        )
+        .await?
+        {
             return Ok(record);
         }
 
         Err(Error::Db(Thrown(format!("Role with id {} not found", id))))
     }
 
-    pub async fn get_by_name(&self, name: String) -> Result<Role, Error> {
-        if let Some(record) = self
-            .db
-            .client
-            .query("SELECT * FROM role WHERE name = $name")
-            .bind(("name", name.clone()))
-            .await?
-            .take(0)?
-        {
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_name(&self, name: String) -> Result<Role, Error> {
+        let mut response = observe_statements(
+            "roles",
+            "get_by_name",
+            self.db
+                .client
+                .query("SELECT * FROM role WHERE name = $name")
+                .bind(("name", name.clone())),
+        )
+        .await?;
+        if let Some(record) = response.take(0)? {
             return Ok(record);
         }
 
@@ -48,33 +73,25 @@ impl RolesRepository {
         ))))
     }
 
-    pub async fn create(&self, content: Role) -> Result<Role, Error> {
-        let record = self
-            .db
-            .client
-            .create(&self.table)
-            .content(content)
+    #[instrument(skip(self, content), err)]
+    async fn create(&self, content: Role) -> Result<Role, Error> {
+        let record = records::create(&self.db, &self.table, "roles", content)
             .await?
             .ok_or_else(|| Error::Db(Thrown("Failed to create role".to_string())))?;
         Ok(record)
     }
 
-    pub async fn update(&self, id: String, content: Role) -> Result<Role, Error> {
-        let record = self
-            .db
-            .client
-            .update((&self.table, id.clone()))
-            .content(content)
+    #[instrument(skip(self, content), err)]
+    async fn update(&self, id: String, content: Role) -> Result<Role, Error> {
+        let record = records::update(&self.db, &self.table, "roles", id.clone(), content)
             .await?
             .ok_or(Error::Db(Thrown(format!("Role with id {} not found", id))))?;
         Ok(record)
     }
 
-    pub async fn delete(&self, id: String) -> Result<Role, Error> {
-        let record = self
-            .db
-            .client
-            .delete((&self.table, id.clone()))
+    #[instrument(skip(self), err)]
+    async fn delete(&self, id: String) -> Result<Role, Error> {
+        let record = records::delete(&self.db, &self.table, "roles", id.clone())
             .await?
             .ok_or(Error::Db(Thrown(format!("Role with id {} not found", id))))?;
         Ok(record)
@@ -0,0 +1,112 @@
+use crate::data::models::search::{SearchHit, SearchPage};
+use crate::db::Database;
+use crate::metrics::observe_statements;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use surrealdb::Error;
+
+/// A field covered by a `SEARCH` index and its weight in the overall score.
+pub(crate) struct SearchField {
+    pub name: &'static str,
+    pub weight: f64,
+}
+
+/// The indexed fields of a table, and the field that breaks score ties.
+pub(crate) struct SearchIndex {
+    pub fields: &'static [SearchField],
+    pub order: &'static str,
+}
+
+#[derive(Deserialize)]
+struct SearchRow<T> {
+    record: T,
+    score: f64,
+    highlights: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Deserialize)]
+struct Total {
+    total: usize,
+}
+
+/// Runs a BM25 full-text query over the index's fields, best weighted score
+/// first, and counts every match for pagination.
+pub(crate) async fn search<T>(
+    db: &Database,
+    table: &str,
+    repository: &'static str,
+    index: &SearchIndex,
+    query: String,
+    start: usize,
+    limit: usize,
+) -> Result<SearchPage<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let fields = index.fields;
+    let order = index.order;
+    let score = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| format!("search::score({}) * {:.1}", i, field.weight))
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let highlights = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            format!(
+                "{}: search::highlight('<mark>', '</mark>', {})",
+                field.name, i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let matches = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| format!("{} @{}@ $query", field.name, i))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    let sql = format!(
+        "SELECT $this AS record, {order}, {score} AS score, {{ {highlights} }} AS highlights \
+         FROM type::table($table) WHERE {matches} \
+         ORDER BY score DESC, {order} ASC LIMIT $limit START $start;\n\
+         SELECT count() AS total FROM type::table($table) WHERE {matches} GROUP ALL;"
+    );
+    let mut response = observe_statements(
+        repository,
+        "search",
+        db.client
+            .query(sql)
+            .bind(("table", table.to_string()))
+            .bind(("query", query))
+            .bind(("limit", limit))
+            .bind(("start", start)),
+    )
+    .await?;
+
+    let rows: Vec<SearchRow<T>> = response.take(0)?;
+    let total: Option<Total> = response.take(1)?;
+    let hits = rows
+        .into_iter()
+        .map(|row| SearchHit {
+            record: row.record,
+            score: row.score,
+            // Unmatched fields come back unmarked, or as NONE when unset
+            highlights: row
+                .highlights
+                .into_iter()
+                .filter_map(|(field, text)| Some((field, text?)))
+                .filter(|(_, text)| text.contains("<mark>"))
+                .collect(),
+        })
+        .collect();
+
+    Ok(SearchPage {
+        hits,
+        total: total.map_or(0, |total| total.total),
+    })
+}
@@ -1,7 +1,110 @@
+use crate::audit;
+use crate::data::dependencies::Dependency;
+use crate::data::models::history::Version;
+use crate::data::models::list::{ListStats, TodoList};
+use crate::data::models::search::SearchPage;
+use crate::data::models::tag::{TagCount, TagMatch};
+use crate::data::models::todo::{Todo, TodoFilter, TodoPatch};
+use crate::data::repositories::bulk;
+use crate::data::repositories::history;
+use crate::data::repositories::records;
+use crate::data::repositories::search::{self, SearchField, SearchIndex};
+use crate::data::stores::{BulkOutcome, TodoStore};
 use crate::db::Database;
-use crate::data::models::todo::Todo;
+use crate::metrics::{observe_query, observe_statements};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
 use std::sync::Arc;
+use surrealdb::engine::any::Any;
+use surrealdb::method::Query;
+use surrealdb::sql::Thing;
 use surrealdb::{error::Db::Thrown, Error};
+use tracing::{instrument, Level};
+
+/// Matches on the title count double those on the content.
+const SEARCH_INDEX: SearchIndex = SearchIndex {
+    fields: &[
+        SearchField {
+            name: "title",
+            weight: 2.0,
+        },
+        SearchField {
+            name: "content",
+            weight: 1.0,
+        },
+    ],
+    order: "title",
+};
+
+/// Open todos past their due date, as of `$now`.
+const OVERDUE: &str =
+    "(completed ?? false) = false AND due_at != NONE AND <datetime> due_at < <datetime> $now";
+
+/// Raw counts behind `ListStats`.
+#[derive(Deserialize)]
+struct ListCounts {
+    total: usize,
+    completed: usize,
+    overdue: usize,
+}
+
+#[derive(Deserialize)]
+struct UpdatedWithNext {
+    todo: Todo,
+    next: Todo,
+}
+
+/// `WHERE` conditions for every filter set on `filter`, over the parameters
+/// bound by `bind_filter`. The tag condition comes first so the query can
+/// start from the tag index.
+fn filter_conditions(filter: &TodoFilter) -> Vec<String> {
+    let mut conditions = Vec::new();
+    if !filter.tags.is_empty() {
+        conditions.push(match filter.tag_match.unwrap_or_default() {
+            TagMatch::Any => "tags CONTAINSANY $tags".to_string(),
+            TagMatch::All => "tags CONTAINSALL $tags".to_string(),
+        });
+    }
+    match filter.overdue {
+        Some(true) => conditions.push(format!("({})", OVERDUE)),
+        Some(false) => conditions.push(format!("!({})", OVERDUE)),
+        None => {}
+    }
+    if filter.due_before.is_some() {
+        conditions
+            .push("(due_at != NONE AND <datetime> due_at < <datetime> $due_before)".to_string());
+    }
+    if filter.due_after.is_some() {
+        conditions
+            .push("(due_at != NONE AND <datetime> due_at > <datetime> $due_after)".to_string());
+    }
+    if filter.completed.is_some() {
+        conditions.push("(completed ?? false) = $completed".to_string());
+    }
+    if filter.priority.is_some() {
+        conditions.push("(priority ?? 'normal') = $priority".to_string());
+    }
+    if filter.list_id.is_some() {
+        conditions.push("list = $list".to_string());
+    }
+    if filter.parent_id.is_some() {
+        conditions.push("parent = $parent".to_string());
+    }
+    conditions
+}
+
+fn bind_filter<'r>(query: Query<'r, Any>, filter: &TodoFilter) -> Query<'r, Any> {
+    query
+        .bind(("now", Local::now()))
+        .bind(("due_before", filter.due_before))
+        .bind(("due_after", filter.due_after))
+        .bind(("completed", filter.completed))
+        .bind(("priority", filter.priority))
+        .bind(("list", filter.list_id.as_deref().map(TodoList::link)))
+        .bind(("parent", filter.parent_id.as_deref().map(Todo::link)))
+        .bind(("tags", filter.tags.clone()))
+}
 
 pub struct TodosRepository {
     db: Arc<Database>,
@@ -15,29 +118,86 @@ impl TodosRepository {
             table: String::from("todo"),
         }
     }
+}
 
-    pub async fn get_all(&self) -> Result<Vec<Todo>, Error> {
-        let records = self.db.client.select(&self.table).await?;
+#[async_trait]
+impl TodoStore for TodosRepository {
+    #[instrument(skip(self), err)]
+    async fn get_all(&self) -> Result<Vec<Todo>, Error> {
+        let records = observe_query("todos", "get_all", self.db.client.select(&self.table)).await?;
         Ok(records)
     }
 
-    pub async fn get_by_id(&self, id: String) -> Result<Todo, Error> {
-        if let Some(record) = self.db.client.select((&self.table, id.clone())).await? {
+    #[instrument(skip(self), err)]
+    async fn page(
+        &self,
+        filter: TodoFilter,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<Todo>, Error> {
+        let mut conditions = filter_conditions(&filter);
+        conditions.push("($after = NONE OR id > type::thing('todo', $after))".to_string());
+        let sql = format!(
+            "SELECT * FROM todo WHERE {} ORDER BY id LIMIT $limit",
+            conditions.join(" AND ")
+        );
+        let mut response = observe_statements(
+            "todos",
+            "page",
+            bind_filter(self.db.client.query(sql), &filter)
+                .bind(("after", after))
+                .bind(("limit", limit)),
+        )
+        .await?;
+        response.take(0)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn list(&self, filter: TodoFilter) -> Result<Vec<Todo>, Error> {
+        let conditions = filter_conditions(&filter);
+        let mut sql = String::from("SELECT * FROM todo");
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        let mut response = observe_statements(
+            "todos",
+            "list",
+            bind_filter(self.db.client.query(sql), &filter),
+        )
+        .await?;
+        let mut todos: Vec<Todo> = response.take(0)?;
+        filter.sort(&mut todos);
+        Ok(todos)
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_id(&self, id: String) -> Result<Todo, Error> {
+        if let Some(record) = observe_query(
+            "todos",
+            "get_by_id",
+            self.db.client.select((&self.table, id.clone())),
+        )
+        .await?
+        {
             return Ok(record);
         }
 
         Err(Error::Db(Thrown(format!("Todo with id {} not found", id))))
     }
 
-    pub async fn get_by_title(&self, title: String) -> Result<Todo, Error> {
-        if let Some(record) = self
-            .db
-            .client
-            .query("SELECT * FROM todo WHERE title = $title")
-            .bind(("title", title.clone()))
-            .await?
-            .take(0)?
-        {
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_title(&self, title: String) -> Result<Todo, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "get_by_title",
+            self.db
+                .client
+                .query("SELECT * FROM todo WHERE title = $title")
+                .bind(("title", title.clone())),
+        )
+        .await?;
+        if let Some(record) = response.take(0)? {
             return Ok(record);
         }
 
@@ -47,35 +207,296 @@ impl TodosRepository {
         ))))
     }
 
-    pub async fn create(&self, content: Todo) -> Result<Todo, Error> {
-        let record = self
-            .db
-            .client
-            .create(&self.table)
-            .content(content)
+    #[instrument(skip(self), err)]
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<Todo>, Error> {
+        search::search(
+            &self.db,
+            &self.table,
+            "todos",
+            &SEARCH_INDEX,
+            query,
+            start,
+            limit,
+        )
+        .await
+    }
+
+    #[instrument(skip(self, content), err)]
+    async fn create(&self, content: Todo) -> Result<Todo, Error> {
+        let record = records::create(&self.db, &self.table, "todos", content)
             .await?
             .ok_or_else(|| Error::Db(Thrown("Failed to create todo".to_string())))?;
         Ok(record)
     }
 
-    pub async fn update(&self, id: String, content: Todo) -> Result<Todo, Error> {
-        let record = self
-            .db
-            .client
-            .update((&self.table, id.clone()))
-            .content(content)
+    #[instrument(skip(self, content), err)]
+    async fn update(&self, id: String, content: Todo) -> Result<Todo, Error> {
+        let record = records::update(&self.db, &self.table, "todos", id.clone(), content)
             .await?
             .ok_or(Error::Db(Thrown(format!("Todo with id {} not found", id))))?;
         Ok(record)
     }
 
-    pub async fn delete(&self, id: String) -> Result<Todo, Error> {
-        let result = self
-            .db
-            .client
-            .delete((&self.table, id.clone()))
+    #[instrument(skip(self, content, next), err)]
+    async fn update_with_next(
+        &self,
+        id: String,
+        content: Todo,
+        next: Todo,
+    ) -> Result<(Todo, Todo), Error> {
+        let mut response = observe_statements(
+            "todos",
+            "update_with_next",
+            self.db
+                .client
+                .query(
+                    "BEGIN TRANSACTION;\n\
+                     LET $todo = (UPDATE type::thing($table, $id) CONTENT $content)[0];\n\
+                     IF !$todo { THROW 'Todo with id ' + $id + ' not found' };\n\
+                     LET $created = CREATE ONLY $next.id CONTENT $next;\n\
+                     RETURN { todo: $todo, next: $created };\n\
+                     COMMIT TRANSACTION;",
+                )
+                .bind(("table", self.table.clone()))
+                .bind(("id", id.clone()))
+                .bind(("content", content))
+                .bind(("next", next))
+                .bind(audit::binding()),
+        )
+        .await?;
+        let updated: Option<UpdatedWithNext> = response.take(0)?;
+        let updated = updated.ok_or(Error::Db(Thrown(format!("Todo with id {} not found", id))))?;
+        Ok((updated.todo, updated.next))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn delete(&self, id: String) -> Result<Todo, Error> {
+        let result = records::delete(&self.db, &self.table, "todos", id.clone())
             .await?
             .ok_or(Error::Db(Thrown(format!("Todo with id {} not found", id))))?;
         Ok(result)
     }
+
+    #[instrument(skip(self), err)]
+    async fn history(&self, id: String) -> Result<Vec<Version<Todo>>, Error> {
+        history::list(&self.db, &self.table, "todos", id).await
+    }
+
+    #[instrument(skip(self), err)]
+    async fn as_of(&self, id: String, at: DateTime<Local>) -> Result<Option<Version<Todo>>, Error> {
+        history::as_of(&self.db, &self.table, "todos", id, at).await
+    }
+
+    #[instrument(skip(self), err)]
+    async fn add_tags(&self, id: String, tags: Vec<String>) -> Result<Todo, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "add_tags",
+            self.db
+                .client
+                .query(
+                    "UPDATE $todo SET tags = array::union(tags ?? [], $tags), updated_at = $now \
+                     WHERE id != NONE RETURN AFTER",
+                )
+                .bind(("todo", Todo::link(&id)))
+                .bind(("tags", tags))
+                .bind(("now", Local::now()))
+                .bind(audit::binding()),
+        )
+        .await?;
+        let record: Option<Todo> = response.take(0)?;
+        record.ok_or(Error::Db(Thrown(format!("Todo with id {} not found", id))))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn remove_tag(&self, id: String, tag: String) -> Result<Todo, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "remove_tag",
+            self.db
+                .client
+                .query(
+                    "UPDATE $todo SET tags -= $tag, updated_at = $now \
+                     WHERE id != NONE RETURN AFTER",
+                )
+                .bind(("todo", Todo::link(&id)))
+                .bind(("tag", tag))
+                .bind(("now", Local::now()))
+                .bind(audit::binding()),
+        )
+        .await?;
+        let record: Option<Todo> = response.take(0)?;
+        record.ok_or(Error::Db(Thrown(format!("Todo with id {} not found", id))))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn tag_counts(&self) -> Result<Vec<TagCount>, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "tag_counts",
+            self.db.client.query(
+                "SELECT tag, count() AS count FROM \
+                 (SELECT tags AS tag FROM todo WHERE tags != NONE SPLIT tag) \
+                 GROUP BY tag",
+            ),
+        )
+        .await?;
+        let mut counts: Vec<TagCount> = response.take(0)?;
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(counts)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn blockers(&self, id: String) -> Result<Vec<Todo>, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "blockers",
+            self.db
+                .client
+                .query("SELECT * FROM todo WHERE id IN $todo->depends_on->todo")
+                .bind(("todo", Todo::link(&id))),
+        )
+        .await?;
+        let todos = response.take(0)?;
+        Ok(todos)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn dependencies(&self) -> Result<Vec<Dependency>, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "dependencies",
+            self.db
+                .client
+                .query("SELECT record::id(in) AS todo, record::id(out) AS blocker FROM depends_on"),
+        )
+        .await?;
+        let edges = response.take(0)?;
+        Ok(edges)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn add_blocker(&self, id: String, blocker_id: String) -> Result<(), Error> {
+        observe_statements(
+            "todos",
+            "add_blocker",
+            self.db
+                .client
+                .query("RELATE $todo->depends_on->$blocker")
+                .bind(("todo", Todo::link(&id)))
+                .bind(("blocker", Todo::link(&blocker_id))),
+        )
+        .await?
+        .check()?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn remove_blocker(&self, id: String, blocker_id: String) -> Result<(), Error> {
+        let mut response = observe_statements(
+            "todos",
+            "remove_blocker",
+            self.db
+                .client
+                .query("DELETE depends_on WHERE in = $todo AND out = $blocker RETURN BEFORE")
+                .bind(("todo", Todo::link(&id)))
+                .bind(("blocker", Todo::link(&blocker_id))),
+        )
+        .await?;
+        let removed: Vec<Thing> = response.take((0, "id"))?;
+        if removed.is_empty() {
+            return Err(Error::Db(Thrown(format!(
+                "Todo with id {} is not blocked by {}",
+                id, blocker_id
+            ))));
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn list_stats(&self, list_id: String) -> Result<ListStats, Error> {
+        let mut response = observe_statements(
+            "todos",
+            "list_stats",
+            self.db
+                .client
+                .query(format!(
+                    "SELECT count() AS total, \
+                     count((completed ?? false) = true) AS completed, \
+                     count({}) AS overdue \
+                     FROM todo WHERE list = $list GROUP ALL",
+                    OVERDUE
+                ))
+                .bind(("list", TodoList::link(&list_id)))
+                .bind(("now", Local::now())),
+        )
+        .await?;
+        // An empty list has no rows to group
+        let counts: Option<ListCounts> = response.take(0)?;
+        Ok(counts.map_or_else(ListStats::default, |counts| {
+            ListStats::new(counts.total, counts.completed, counts.overdue)
+        }))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn fire_due_reminders(&self, now: DateTime<Local>) -> Result<Vec<Todo>, Error> {
+        // One statement, so overlapping runs cannot fire the same reminder twice
+        let mut response = observe_statements(
+            "todos",
+            "fire_due_reminders",
+            self.db
+                .client
+                .query(
+                    "UPDATE todo SET reminder_fired_at = $now \
+                     WHERE remind_at != NONE AND reminder_fired_at = NONE \
+                     AND (completed ?? false) = false \
+                     AND <datetime> remind_at <= <datetime> $now \
+                     RETURN AFTER",
+                )
+                .bind(("now", now))
+                .bind(audit::binding()),
+        )
+        .await?;
+        let todos = response.take(0)?;
+        Ok(todos)
+    }
+
+    #[instrument(skip(self, items), fields(count = items.len()), err)]
+    async fn create_many(
+        &self,
+        items: Vec<Todo>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        bulk::create_many(&self.db, &self.table, "todos", items, atomic).await
+    }
+
+    #[instrument(skip(self, patches), fields(count = patches.len()), err)]
+    async fn merge_many(
+        &self,
+        patches: Vec<(String, TodoPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        let patches = patches
+            .into_iter()
+            .map(|(id, mut patch)| {
+                let next = patch.next.take();
+                (id, patch, next)
+            })
+            .collect();
+        bulk::merge_creating(&self.db, &self.table, "todos", patches, atomic).await
+    }
+
+    #[instrument(skip(self, ids), fields(count = ids.len()), err)]
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error> {
+        bulk::delete_many(&self.db, &self.table, "todos", ids, atomic).await
+    }
 }
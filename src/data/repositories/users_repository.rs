@@ -1,45 +1,98 @@
+use crate::data::contact::{ContactError, ContactNormalizer};
 use crate::db::Database;
-use crate::data::models::user::User;
+use crate::data::repositories::bulk;
+use crate::data::repositories::history;
+use crate::data::repositories::paging;
+use crate::data::repositories::records;
+use crate::data::repositories::search::{self, SearchField, SearchIndex};
+use crate::data::stores::{BulkOutcome, UserStore};
+use crate::metrics::{observe_query, observe_statements};
+use crate::data::models::history::Version;
+use crate::data::models::search::SearchPage;
+use crate::data::models::user::{User, UserPatch};
+use async_trait::async_trait;
 use std::sync::Arc;
-use chrono::Local;
+use chrono::{DateTime, Local};
 use surrealdb::err::Error::Thrown;
 use surrealdb::Error;
+use tracing::{instrument, Level};
+
+/// Matches on the name count double those on the email.
+const SEARCH_INDEX: SearchIndex = SearchIndex {
+    fields: &[
+        SearchField {
+            name: "name",
+            weight: 2.0,
+        },
+        SearchField {
+            name: "email",
+            weight: 1.0,
+        },
+    ],
+    order: "name",
+};
 
 pub struct UsersRepository {
     db: Arc<Database>,
     table: String,
+    contacts: ContactNormalizer,
 }
 
 impl UsersRepository {
-    pub fn new(db: Arc<Database>) -> Self {
+    pub fn new(db: Arc<Database>, contacts: ContactNormalizer) -> Self {
         UsersRepository {
             db,
             table: String::from("user"),
+            contacts,
         }
     }
+}
+
+fn invalid_contact(error: ContactError) -> Error {
+    Error::Db(Thrown(error.to_string()))
+}
 
-    pub async fn get_all(&self) -> Result<Vec<User>, Error> {
-        let records = self.db.client.select(&self.table).await?;
+#[async_trait]
+impl UserStore for UsersRepository {
+    #[instrument(skip(self), err)]
+    async fn get_all(&self) -> Result<Vec<User>, Error> {
+        let records = observe_query("users", "get_all", self.db.client.select(&self.table)).await?;
         Ok(records)
     }
 
-    pub async fn get_by_id(&self, id: String) -> Result<User, Error> {
-        if let Some(record) = self.db.client.select((&self.table, id.clone())).await? {
+    #[instrument(skip(self), err)]
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<User>, Error> {
+        paging::page(&self.db, &self.table, "users", after, limit).await
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_id(&self, id: String) -> Result<User, Error> {
+        if let Some(record) = observe_query(
+            "users",
+            "get_by_id",
+            self.db.client.select((&self.table, id.clone())),
+        )
+        .await?
+        {
             return Ok(record);
         }
 
         Err(Error::Db(Thrown(format!("User with id {} not found", id))))
     }
 
-    pub async fn get_by_email(&self, email: String) -> Result<User, Error> {
-        if let Some(record) = self
-            .db
-            .client
-            .query("SELECT * FROM user WHERE email = $email")
-            .bind(("email", email.clone()))
-            .await?
-            .take(0)?
-        {
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_email(&self, email: String) -> Result<User, Error> {
+        let email = self.contacts.email(&email);
+        let mut response = observe_statements(
+            "users",
+            "get_by_email",
+            self.db
+                .client
+                .query("SELECT * FROM user WHERE email = $email")
+                .bind(("email", email.clone())),
+        )
+        .await?;
+        if let Some(record) = response.take(0)? {
             return Ok(record);
         }
 
@@ -49,15 +102,19 @@ impl UsersRepository {
         ))))
     }
 
-    pub async fn get_by_phone(&self, phone: String) -> Result<User, Error> {
-        if let Some(record) = self
-            .db
-            .client
-            .query("SELECT * FROM user WHERE phone = $phone")
-            .bind(("phone", phone.clone()))
-            .await?
-            .take(0)?
-        {
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_phone(&self, phone: String) -> Result<User, Error> {
+        let phone = self.contacts.phone(&phone).map_err(invalid_contact)?;
+        let mut response = observe_statements(
+            "users",
+            "get_by_phone",
+            self.db
+                .client
+                .query("SELECT * FROM user WHERE phone = $phone")
+                .bind(("phone", phone.clone())),
+        )
+        .await?;
+        if let Some(record) = response.take(0)? {
             return Ok(record);
         }
 
@@ -67,37 +124,95 @@ impl UsersRepository {
         ))))
     }
 
-    pub async fn create(&self, mut user: User) -> Result<User, Error> {
+    #[instrument(skip(self), err)]
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<User>, Error> {
+        search::search(
+            &self.db,
+            &self.table,
+            "users",
+            &SEARCH_INDEX,
+            query,
+            start,
+            limit,
+        )
+        .await
+    }
+
+    #[instrument(skip(self, user), err)]
+    async fn create(&self, mut user: User) -> Result<User, Error> {
+        user.normalize(&self.contacts).map_err(invalid_contact)?;
         user.created_at = Some(Local::now());
-        let record = self
-            .db
-            .client
-            .create(&self.table)
-            .content(user)
+        let record = records::create(&self.db, &self.table, "users", user)
             .await?
             .ok_or_else(|| Error::Db(Thrown("Failed to create user".to_string())))?;
         Ok(record)
     }
 
-    pub async fn update(&self, id: String, mut user: User) -> Result<User, Error> {
+    #[instrument(skip(self, user), err)]
+    async fn update(&self, id: String, mut user: User) -> Result<User, Error> {
+        user.normalize(&self.contacts).map_err(invalid_contact)?;
         user.updated_at = Some(Local::now());
-        let record = self
-            .db
-            .client
-            .update((&self.table, id.clone()))
-            .content(user)
+        let record = records::update(&self.db, &self.table, "users", id.clone(), user)
             .await?
             .ok_or(Error::Db(Thrown(format!("User with id {} not found", id))))?;
         Ok(record)
     }
 
-    pub async fn delete(&self, id: String) -> Result<User, Error> {
-        let record = self
-            .db
-            .client
-            .delete((&self.table, id.clone()))
+    #[instrument(skip(self), err)]
+    async fn delete(&self, id: String) -> Result<User, Error> {
+        let record = records::delete(&self.db, &self.table, "users", id.clone())
             .await?
             .ok_or(Error::Db(Thrown(format!("User with id {} not found", id))))?;
         Ok(record)
     }
+
+    #[instrument(skip(self), err)]
+    async fn history(&self, id: String) -> Result<Vec<Version<User>>, Error> {
+        history::list(&self.db, &self.table, "users", id).await
+    }
+
+    #[instrument(skip(self), err)]
+    async fn as_of(&self, id: String, at: DateTime<Local>) -> Result<Option<Version<User>>, Error> {
+        history::as_of(&self.db, &self.table, "users", id, at).await
+    }
+
+    #[instrument(skip(self, users), fields(count = users.len()), err)]
+    async fn create_many(
+        &self,
+        mut users: Vec<User>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        let now = Local::now();
+        for user in users.iter_mut() {
+            user.normalize(&self.contacts).map_err(invalid_contact)?;
+            user.created_at = Some(now);
+        }
+        bulk::create_many(&self.db, &self.table, "users", users, atomic).await
+    }
+
+    #[instrument(skip(self, patches), fields(count = patches.len()), err)]
+    async fn merge_many(
+        &self,
+        mut patches: Vec<(String, UserPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        for (_, patch) in patches.iter_mut() {
+            patch.normalize(&self.contacts).map_err(invalid_contact)?;
+        }
+        bulk::merge_many(&self.db, &self.table, "users", patches, atomic).await
+    }
+
+    #[instrument(skip(self, ids), fields(count = ids.len()), err)]
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error> {
+        bulk::delete_many(&self.db, &self.table, "users", ids, atomic).await
+    }
 }
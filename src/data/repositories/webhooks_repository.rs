@@ -0,0 +1,257 @@
+use crate::data::models::outbox::DomainEvent;
+use crate::data::models::webhook::{
+    AttemptOutcome, DeliveryFilter, DeliveryPage, Webhook, WebhookDelivery,
+};
+use crate::data::repositories::records;
+use crate::data::stores::WebhookStore;
+use crate::db::Database;
+use crate::metrics::{observe_query, observe_statements, Statements};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::error::Db::{Serialization, Thrown};
+use surrealdb::Error;
+use tracing::{instrument, Level};
+
+#[derive(Deserialize)]
+struct Total {
+    total: usize,
+}
+
+/// Reads deliveries from statement `index` as plain JSON, since they link to
+/// their webhook and carry records of any table.
+// The error type is dictated by the store traits
+#[allow(clippy::result_large_err)]
+fn take_deliveries(response: &mut Statements, index: usize) -> Result<Vec<WebhookDelivery>, Error> {
+    let deliveries: surrealdb::Value = response.take(index)?;
+    serde_json::from_value(deliveries.into_inner().into_json())
+        .map_err(|e| Error::Db(Serialization(e.to_string())))
+}
+
+pub struct WebhooksRepository {
+    db: Arc<Database>,
+    table: String,
+}
+
+impl WebhooksRepository {
+    pub fn new(db: Arc<Database>) -> Self {
+        WebhooksRepository {
+            db,
+            table: String::from("webhook"),
+        }
+    }
+}
+
+#[async_trait]
+impl WebhookStore for WebhooksRepository {
+    #[instrument(skip(self), err)]
+    async fn get_all(&self) -> Result<Vec<Webhook>, Error> {
+        let records =
+            observe_query("webhooks", "get_all", self.db.client.select(&self.table)).await?;
+        Ok(records)
+    }
+
+    #[instrument(skip(self), err(level = Level::DEBUG))]
+    async fn get_by_id(&self, id: String) -> Result<Webhook, Error> {
+        if let Some(record) = observe_query(
+            "webhooks",
+            "get_by_id",
+            self.db.client.select((&self.table, id.clone())),
+        )
+        .await?
+        {
+            return Ok(record);
+        }
+
+        Err(Error::Db(Thrown(format!(
+            "Webhook with id {} not found",
+            id
+        ))))
+    }
+
+    #[instrument(skip(self, webhook), err)]
+    async fn create(&self, webhook: Webhook) -> Result<Webhook, Error> {
+        let record = records::create(&self.db, &self.table, "webhooks", webhook)
+            .await?
+            .ok_or_else(|| Error::Db(Thrown("Failed to create webhook".to_string())))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self, webhook), err)]
+    async fn update(&self, id: String, webhook: Webhook) -> Result<Webhook, Error> {
+        let record = records::update(&self.db, &self.table, "webhooks", id.clone(), webhook)
+            .await?
+            .ok_or(Error::Db(Thrown(format!(
+                "Webhook with id {} not found",
+                id
+            ))))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn delete(&self, id: String) -> Result<Webhook, Error> {
+        let record = records::delete(&self.db, &self.table, "webhooks", id.clone())
+            .await?
+            .ok_or(Error::Db(Thrown(format!(
+                "Webhook with id {} not found",
+                id
+            ))))?;
+        Ok(record)
+    }
+
+    #[instrument(skip(self), err)]
+    async fn deliveries(
+        &self,
+        filter: DeliveryFilter,
+        start: usize,
+        limit: usize,
+    ) -> Result<DeliveryPage, Error> {
+        let mut conditions = Vec::new();
+        if filter.webhook.is_some() {
+            conditions.push("webhook = type::thing('webhook', $webhook)");
+        }
+        if filter.status.is_some() {
+            conditions.push("status = $status");
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+
+        let mut response = observe_statements(
+            "webhooks",
+            "deliveries",
+            self.db
+                .client
+                .query(format!(
+                    "SELECT * FROM webhook_delivery{condition} \
+                     ORDER BY created_at DESC LIMIT $limit START $start;\n\
+                     SELECT count() AS total FROM webhook_delivery{condition} GROUP ALL;"
+                ))
+                .bind(("webhook", filter.webhook))
+                .bind(("status", filter.status))
+                .bind(("limit", limit))
+                .bind(("start", start)),
+        )
+        .await?;
+        let deliveries = take_deliveries(&mut response, 0)?;
+        let total: Option<Total> = response.take(1)?;
+        Ok(DeliveryPage {
+            deliveries,
+            total: total.map_or(0, |total| total.total),
+        })
+    }
+
+    #[instrument(skip(self), err)]
+    async fn claim_due(
+        &self,
+        now: DateTime<Local>,
+        lease: DateTime<Local>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        let mut response = observe_statements(
+            "webhooks",
+            "claim_due",
+            self.db
+                .client
+                .query(
+                    "UPDATE (SELECT id, next_attempt_at FROM webhook_delivery \
+                     WHERE status = 'pending' AND next_attempt_at <= <datetime> $now \
+                     ORDER BY next_attempt_at LIMIT $limit).id \
+                     SET next_attempt_at = <datetime> $lease RETURN AFTER",
+                )
+                .bind(("now", now))
+                .bind(("lease", lease))
+                .bind(("limit", limit)),
+        )
+        .await?;
+        take_deliveries(&mut response, 0)
+    }
+
+    #[instrument(skip(self, outcome), err)]
+    async fn record_attempt(&self, id: String, outcome: AttemptOutcome) -> Result<(), Error> {
+        observe_statements(
+            "webhooks",
+            "record_attempt",
+            self.db
+                .client
+                .query(
+                    "UPDATE type::thing('webhook_delivery', $id) SET \
+                     attempts += $attempt, \
+                     status = $status, \
+                     next_attempt_at = IF $next THEN <datetime> $next ELSE NONE END, \
+                     delivered_at = IF $status = 'delivered' THEN time::now() ELSE delivered_at END \
+                     WHERE id != NONE",
+                )
+                .bind(("id", id))
+                .bind(("attempt", outcome.attempt))
+                .bind(("status", outcome.status))
+                .bind(("next", outcome.next_attempt_at)),
+        )
+        .await?
+        .check()?;
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn redeliver(&self, id: String, now: DateTime<Local>) -> Result<WebhookDelivery, Error> {
+        let mut response = observe_statements(
+            "webhooks",
+            "redeliver",
+            self.db
+                .client
+                .query(
+                    "UPDATE type::thing('webhook_delivery', $id) \
+                     SET status = 'pending', next_attempt_at = <datetime> $now \
+                     WHERE id != NONE RETURN AFTER",
+                )
+                .bind(("id", id.clone()))
+                .bind(("now", now)),
+        )
+        .await?;
+        take_deliveries(&mut response, 0)?
+            .pop()
+            .ok_or(Error::Db(Thrown(format!(
+                "Delivery with id {} not found",
+                id
+            ))))
+    }
+
+    #[instrument(skip(self, event), fields(event = %event.id), err)]
+    async fn enqueue(&self, event: DomainEvent) -> Result<(), Error> {
+        // Keyed on the event and the webhook, so queuing is idempotent
+        observe_statements(
+            "webhooks",
+            "enqueue",
+            self.db
+                .client
+                .query(
+                    "FOR $webhook IN (SELECT VALUE id FROM webhook \
+                     WHERE active = true AND events CONTAINS $event) { \
+                     LET $delivery = type::thing('webhook_delivery', \
+                     string::concat($key, '_', <string> record::id($webhook))); \
+                     IF !record::exists($delivery) { \
+                     CREATE $delivery CONTENT { \
+                     webhook: $webhook, \
+                     event: $event, \
+                     payload: { event: $event, at: <datetime> $at, data: $data }, \
+                     status: 'pending', \
+                     attempts: [], \
+                     next_attempt_at: time::now(), \
+                     created_at: time::now() \
+                     } \
+                     }; \
+                     };",
+                )
+                .bind(("key", event.key().to_string()))
+                .bind(("event", event.event))
+                .bind(("at", event.at))
+                .bind(("data", event.data)),
+        )
+        .await?
+        .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use crate::data::dependencies::Dependency;
+use crate::data::models::audit::{AuditFilter, AuditPage};
+use crate::data::models::history::Version;
+use crate::data::models::list::{ListDeletion, ListStats, OnDelete, TodoList};
+use crate::data::models::outbox::{DomainEvent, PublishOutcome};
+use crate::data::models::rate_limit::{Quota, Take};
+use crate::data::models::role::Role;
+use crate::data::models::search::SearchPage;
+use crate::data::models::tag::TagCount;
+use crate::data::models::todo::{Todo, TodoFilter, TodoPatch};
+use crate::data::models::user::{User, UserPatch};
+use crate::data::models::webhook::{
+    AttemptOutcome, DeliveryFilter, DeliveryPage, Webhook, WebhookDelivery,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use surrealdb::Error;
+
+/// Result of one item in a bulk operation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkOutcome<T> {
+    Done(T),
+    NotFound,
+    /// Skipped because another item in an all-or-nothing batch failed
+    Aborted,
+    Failed(String),
+}
+
+/// Storage operations for todos, implemented by `TodosRepository` and, under the
+/// `testing` feature, by `InMemoryTodos`.
+#[async_trait]
+pub trait TodoStore: Send + Sync {
+    async fn get_all(&self) -> Result<Vec<Todo>, Error>;
+    /// Up to `limit` todos passing `filter`, ordered by id, after the one
+    /// keyed `after`. The filter's sort is ignored.
+    async fn page(
+        &self,
+        filter: TodoFilter,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<Todo>, Error>;
+    /// Todos passing every filter that is set, in the requested order.
+    async fn list(&self, filter: TodoFilter) -> Result<Vec<Todo>, Error>;
+    async fn get_by_id(&self, id: String) -> Result<Todo, Error>;
+    async fn get_by_title(&self, title: String) -> Result<Todo, Error>;
+    /// Full-text search over title and content, returning `limit` hits from `start`.
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<Todo>, Error>;
+    async fn create(&self, content: Todo) -> Result<Todo, Error>;
+    async fn update(&self, id: String, content: Todo) -> Result<Todo, Error>;
+    /// Replaces todo `id` and creates `next`, the occurrence it links to, in
+    /// one transaction.
+    async fn update_with_next(
+        &self,
+        id: String,
+        content: Todo,
+        next: Todo,
+    ) -> Result<(Todo, Todo), Error>;
+    async fn delete(&self, id: String) -> Result<Todo, Error>;
+    /// Every version of todo `id`, newest first, including after it was deleted.
+    async fn history(&self, id: String) -> Result<Vec<Version<Todo>>, Error>;
+    /// The version of todo `id` that was current at `at`, if it had one yet.
+    async fn as_of(&self, id: String, at: DateTime<Local>) -> Result<Option<Version<Todo>>, Error>;
+    /// Adds `tags` to todo `id`, skipping those it already has.
+    async fn add_tags(&self, id: String, tags: Vec<String>) -> Result<Todo, Error>;
+    async fn remove_tag(&self, id: String, tag: String) -> Result<Todo, Error>;
+    /// Every tag in use and how many todos carry it, most used first.
+    async fn tag_counts(&self) -> Result<Vec<TagCount>, Error>;
+    /// Todos that todo `id` waits on, open or not.
+    async fn blockers(&self, id: String) -> Result<Vec<Todo>, Error>;
+    /// Every dependency edge between todos.
+    async fn dependencies(&self) -> Result<Vec<Dependency>, Error>;
+    /// Makes todo `id` wait on todo `blocker_id`. Callers check that both
+    /// exist and that the edge does not close a cycle.
+    async fn add_blocker(&self, id: String, blocker_id: String) -> Result<(), Error>;
+    async fn remove_blocker(&self, id: String, blocker_id: String) -> Result<(), Error>;
+    /// Completion counts for the todos in list `list_id`.
+    async fn list_stats(&self, list_id: String) -> Result<ListStats, Error>;
+    /// Stamps `reminder_fired_at` on open todos whose reminder is due and has
+    /// not fired yet, returning them.
+    async fn fire_due_reminders(&self, now: DateTime<Local>) -> Result<Vec<Todo>, Error>;
+    async fn create_many(
+        &self,
+        items: Vec<Todo>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error>;
+    /// Merges each patch into the todo with the paired id.
+    async fn merge_many(
+        &self,
+        patches: Vec<(String, TodoPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error>;
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<Todo>>, Error>;
+}
+
+/// Storage operations for users, implemented by `UsersRepository` and, under the
+/// `testing` feature, by `InMemoryUsers`.
+#[async_trait]
+pub trait UserStore: Send + Sync {
+    async fn get_all(&self) -> Result<Vec<User>, Error>;
+    /// Up to `limit` users ordered by id, after the one keyed `after`.
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<User>, Error>;
+    async fn get_by_id(&self, id: String) -> Result<User, Error>;
+    async fn get_by_email(&self, email: String) -> Result<User, Error>;
+    async fn get_by_phone(&self, phone: String) -> Result<User, Error>;
+    /// Full-text search over name and email, returning `limit` hits from `start`.
+    async fn search(
+        &self,
+        query: String,
+        start: usize,
+        limit: usize,
+    ) -> Result<SearchPage<User>, Error>;
+    async fn create(&self, user: User) -> Result<User, Error>;
+    async fn update(&self, id: String, user: User) -> Result<User, Error>;
+    async fn delete(&self, id: String) -> Result<User, Error>;
+    /// Every version of user `id`, newest first, including after it was deleted.
+    async fn history(&self, id: String) -> Result<Vec<Version<User>>, Error>;
+    /// The version of user `id` that was current at `at`, if it had one yet.
+    async fn as_of(&self, id: String, at: DateTime<Local>) -> Result<Option<Version<User>>, Error>;
+    async fn create_many(
+        &self,
+        users: Vec<User>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error>;
+    /// Merges each patch into the user with the paired id.
+    async fn merge_many(
+        &self,
+        patches: Vec<(String, UserPatch)>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error>;
+    async fn delete_many(
+        &self,
+        ids: Vec<String>,
+        atomic: bool,
+    ) -> Result<Vec<BulkOutcome<User>>, Error>;
+}
+
+/// Storage operations for todo lists, implemented by `ListsRepository` and,
+/// under the `testing` feature, by `InMemoryLists`.
+#[async_trait]
+pub trait ListStore: Send + Sync {
+    async fn get_all(&self) -> Result<Vec<TodoList>, Error>;
+    /// Up to `limit` lists ordered by id, after the one keyed `after`.
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<TodoList>, Error>;
+    async fn get_by_id(&self, id: String) -> Result<TodoList, Error>;
+    async fn create(&self, content: TodoList) -> Result<TodoList, Error>;
+    async fn update(&self, id: String, content: TodoList) -> Result<TodoList, Error>;
+    async fn delete(&self, id: String) -> Result<TodoList, Error>;
+    /// Checks for and deletes the list's todos as `on_delete` says, then the
+    /// list, in one transaction.
+    async fn delete_with_todos(
+        &self,
+        id: String,
+        on_delete: OnDelete,
+    ) -> Result<ListDeletion, Error>;
+}
+
+/// Storage operations for roles, implemented by `RolesRepository` and, under the
+/// `testing` feature, by `InMemoryRoles`.
+#[async_trait]
+pub trait RoleStore: Send + Sync {
+    async fn get_all(&self) -> Result<Vec<Role>, Error>;
+    /// Up to `limit` roles ordered by id, after the one keyed `after`.
+    async fn page(&self, after: Option<String>, limit: usize) -> Result<Vec<Role>, Error>;
+    async fn get_by_id(&self, id: String) -> Result<Role, Error>;
+    async fn get_by_name(&self, name: String) -> Result<Role, Error>;
+    async fn create(&self, content: Role) -> Result<Role, Error>;
+    async fn update(&self, id: String, content: Role) -> Result<Role, Error>;
+    async fn delete(&self, id: String) -> Result<Role, Error>;
+}
+
+/// Reads and prunes the audit log, implemented by `AuditRepository` and, under
+/// the `testing` feature, by `InMemoryAudit`. Entries are written by the
+/// database itself, in the transaction of each audited change.
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    /// `limit` entries passing `filter` from `start`, newest first.
+    async fn list(
+        &self,
+        filter: AuditFilter,
+        start: usize,
+        limit: usize,
+    ) -> Result<AuditPage, Error>;
+    /// Deletes entries written before `cutoff`, returning how many.
+    async fn prune(&self, cutoff: DateTime<Local>) -> Result<usize, Error>;
+}
+
+/// Storage for webhooks and their delivery queue, implemented by
+/// `WebhooksRepository` and, under the `testing` feature, by
+/// `InMemoryWebhooks`. Deliveries are queued from the outbox by
+/// `outbox::WebhookSink`.
+#[async_trait]
+pub trait WebhookStore: Send + Sync {
+    async fn get_all(&self) -> Result<Vec<Webhook>, Error>;
+    async fn get_by_id(&self, id: String) -> Result<Webhook, Error>;
+    async fn create(&self, webhook: Webhook) -> Result<Webhook, Error>;
+    async fn update(&self, id: String, webhook: Webhook) -> Result<Webhook, Error>;
+    async fn delete(&self, id: String) -> Result<Webhook, Error>;
+    /// `limit` deliveries passing `filter` from `start`, newest first.
+    async fn deliveries(
+        &self,
+        filter: DeliveryFilter,
+        start: usize,
+        limit: usize,
+    ) -> Result<DeliveryPage, Error>;
+    /// Takes up to `limit` pending deliveries due by `now`, oldest first,
+    /// deferring each to `lease` so no other worker takes it meanwhile.
+    async fn claim_due(
+        &self,
+        now: DateTime<Local>,
+        lease: DateTime<Local>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, Error>;
+    /// Appends an attempt to delivery `id` and sets its status.
+    async fn record_attempt(&self, id: String, outcome: AttemptOutcome) -> Result<(), Error>;
+    /// Queues delivery `id` again, due at `now`.
+    async fn redeliver(&self, id: String, now: DateTime<Local>) -> Result<WebhookDelivery, Error>;
+    /// Queues `event` for every active webhook subscribed to it. Queuing the
+    /// same event twice queues nothing more.
+    async fn enqueue(&self, event: DomainEvent) -> Result<(), Error>;
+}
+
+/// The transactional outbox, implemented by `OutboxRepository` and, under the
+/// `testing` feature, by `InMemoryOutbox`. Events are written by the database
+/// itself, in the transaction of each todo, user and role change.
+#[async_trait]
+pub trait OutboxStore: Send + Sync {
+    /// Takes up to `limit` pending events due by `now`, in the order they
+    /// were written, deferring each to `lease` so no other worker takes it
+    /// meanwhile. Stops short of any pending event that is not yet due, so
+    /// nothing overtakes an event waiting to be retried.
+    async fn claim_due(
+        &self,
+        now: DateTime<Local>,
+        lease: DateTime<Local>,
+        limit: usize,
+    ) -> Result<Vec<DomainEvent>, Error>;
+    /// Records an attempt at publishing event `id`.
+    async fn record_attempt(&self, id: String, outcome: PublishOutcome) -> Result<(), Error>;
+    /// Makes claimed events `ids` due at `now` again, untried.
+    async fn release(&self, ids: Vec<String>, now: DateTime<Local>) -> Result<(), Error>;
+    /// Deletes events published before `cutoff`, returning how many.
+    async fn prune(&self, cutoff: DateTime<Local>) -> Result<usize, Error>;
+}
+
+/// Token buckets behind the rate limiter, implemented by `MemoryRateLimits`
+/// for a single instance and by `RateLimitsRepository` for instances sharing
+/// one database.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Refills bucket `key` at `quota`'s rate, starting it full, then takes a
+    /// token from it if one is left.
+    async fn take(&self, key: String, quota: Quota, now: DateTime<Local>) -> Result<Take, Error>;
+    /// Deletes buckets that have refilled by `now`, as they are no different
+    /// from new ones, returning how many.
+    async fn prune(&self, now: DateTime<Local>) -> Result<usize, Error>;
+}
@@ -0,0 +1,290 @@
+use crate::data::models::transfer::TransferFormat;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// How a CSV cell maps onto the row's JSON value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Text,
+    /// `true` or `false`
+    Flag,
+    /// Comma-separated items in one cell
+    List,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+impl Column {
+    pub const fn text(name: &'static str) -> Self {
+        Column {
+            name,
+            kind: ColumnKind::Text,
+        }
+    }
+
+    pub const fn flag(name: &'static str) -> Self {
+        Column {
+            name,
+            kind: ColumnKind::Flag,
+        }
+    }
+
+    pub const fn list(name: &'static str) -> Self {
+        Column {
+            name,
+            kind: ColumnKind::List,
+        }
+    }
+}
+
+/// A record that can be exported and imported as flat rows.
+pub trait Transfer {
+    /// The flat form of a record; also what imports deserialize
+    type Row: Serialize + DeserializeOwned;
+    /// Every field of `Row`, in CSV column order
+    const COLUMNS: &'static [Column];
+
+    fn to_row(&self) -> Self::Row;
+    /// Record key that export pages continue after
+    fn key(&self) -> Option<String>;
+}
+
+/// The CSV header line, or nothing for NDJSON.
+pub fn header(format: TransferFormat, columns: &[Column]) -> Option<String> {
+    match format {
+        TransferFormat::Csv => Some(csv_line(
+            columns.iter().map(|column| column.name.to_string()),
+        )),
+        TransferFormat::Ndjson => None,
+    }
+}
+
+/// One row as a CSV or NDJSON line, including the line ending.
+pub fn encode<R: Serialize>(format: TransferFormat, columns: &[Column], row: &R) -> String {
+    let value = serde_json::to_value(row).unwrap_or(Value::Null);
+    match format {
+        TransferFormat::Csv => csv_line(columns.iter().map(|column| cell(&value[column.name]))),
+        TransferFormat::Ndjson => format!("{}\n", value),
+    }
+}
+
+/// Reads every row of `text`. The outer error rejects the file as a whole,
+/// e.g. an unknown CSV column; the inner ones reject single rows.
+pub fn decode<R: DeserializeOwned>(
+    format: TransferFormat,
+    columns: &[Column],
+    text: &str,
+) -> Result<Vec<Result<R, String>>, String> {
+    let values = match format {
+        TransferFormat::Csv => csv_values(columns, text)?,
+        TransferFormat::Ndjson => text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e)))
+            .collect(),
+    };
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            value.and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+        })
+        .collect())
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+fn csv_line(cells: impl Iterator<Item = String>) -> String {
+    let mut line = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// CSV records as JSON objects keyed by the header, leaving out empty cells.
+fn csv_values(columns: &[Column], text: &str) -> Result<Vec<Result<Value, String>>, String> {
+    let mut records = parse_csv(text)?.into_iter();
+    let header = records.next().ok_or("CSV has no header row")?;
+    let header = header
+        .iter()
+        .map(|name| {
+            let name = name.trim();
+            columns
+                .iter()
+                .find(|column| column.name == name)
+                .ok_or_else(|| format!("Unknown column `{}`", name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(records
+        .map(|record| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "Expected {} fields but found {}",
+                    header.len(),
+                    record.len()
+                ));
+            }
+            let mut row = Map::new();
+            for (column, cell) in header.iter().zip(record) {
+                if cell.is_empty() {
+                    continue;
+                }
+                let value = match column.kind {
+                    ColumnKind::Text => Value::String(cell),
+                    ColumnKind::Flag => match cell.to_ascii_lowercase().as_str() {
+                        "true" => Value::Bool(true),
+                        "false" => Value::Bool(false),
+                        _ => return Err(format!("`{}` must be true or false", column.name)),
+                    },
+                    ColumnKind::List => Value::Array(
+                        cell.split(',')
+                            .map(str::trim)
+                            .filter(|item| !item.is_empty())
+                            .map(|item| Value::String(item.to_string()))
+                            .collect(),
+                    ),
+                };
+                row.insert(column.name.to_string(), value);
+            }
+            Ok(Value::Object(row))
+        })
+        .collect())
+}
+
+/// Splits RFC 4180 CSV into records, skipping blank lines.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("CSV ends inside a quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Row {
+        name: String,
+        active: Option<bool>,
+        tags: Option<Vec<String>>,
+    }
+
+    const COLUMNS: &[Column] = &[
+        Column::text("name"),
+        Column::flag("active"),
+        Column::list("tags"),
+    ];
+
+    #[test]
+    fn csv_round_trips_quotes_and_lists() {
+        let row = Row {
+            name: "Smith, \"Jo\"\nJr".to_string(),
+            active: Some(true),
+            tags: Some(vec!["a".to_string(), "b".to_string()]),
+        };
+        let text = header(TransferFormat::Csv, COLUMNS).unwrap()
+            + &encode(TransferFormat::Csv, COLUMNS, &row);
+        assert_eq!(
+            text,
+            "name,active,tags\r\n\"Smith, \"\"Jo\"\"\nJr\",true,\"a,b\"\r\n"
+        );
+        let rows: Vec<Result<Row, String>> = decode(TransferFormat::Csv, COLUMNS, &text).unwrap();
+        assert_eq!(rows, vec![Ok(row)]);
+    }
+
+    #[test]
+    fn reports_bad_files_and_rows() {
+        assert!(decode::<Row>(TransferFormat::Csv, COLUMNS, "name,colour\nx,red\n").is_err());
+        assert!(decode::<Row>(TransferFormat::Csv, COLUMNS, "name\n\"open").is_err());
+
+        let rows = decode::<Row>(
+            TransferFormat::Csv,
+            COLUMNS,
+            "active,name\n\nmaybe,x\ntrue\nfalse,y",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], Err("`active` must be true or false".to_string()));
+        assert!(rows[1].is_err());
+        assert_eq!(
+            rows[2],
+            Ok(Row {
+                name: "y".to_string(),
+                active: Some(false),
+                tags: None
+            })
+        );
+
+        let rows = decode::<Row>(
+            TransferFormat::Ndjson,
+            COLUMNS,
+            "{\"name\":\"x\"}\n\nnot json\n{\"active\":true}\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].is_ok());
+        assert!(rows[1].as_ref().unwrap_err().starts_with("Invalid JSON"));
+        assert!(rows[2]
+            .as_ref()
+            .unwrap_err()
+            .contains("missing field `name`"));
+    }
+}
@@ -1,14 +1,92 @@
 use std::env;
 use std::sync::Arc;
 use surrealdb::{
-    engine::remote::ws::{Client, Ws},
+    engine::any::{self, Any},
     opt::auth::Root,
     Result, Surreal,
 };
 
+/// Analyzers and full-text indexes behind the search endpoints, the tag index,
+/// the graph edges for todo dependencies, the events that audit every write
+/// to todos, users and roles, those that keep numbered versions of todos and
+/// users, and those that write each change to the outbox. Every statement is
+/// idempotent so it runs on each connect.
+const SCHEMA: &str = "
+DEFINE ANALYZER IF NOT EXISTS todo_text TOKENIZERS blank,class,punct FILTERS lowercase,ascii,snowball(english);
+DEFINE ANALYZER IF NOT EXISTS user_text TOKENIZERS blank,class,punct FILTERS lowercase,ascii;
+DEFINE INDEX IF NOT EXISTS todo_title_search ON todo FIELDS title SEARCH ANALYZER todo_text BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS todo_content_search ON todo FIELDS content SEARCH ANALYZER todo_text BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS user_name_search ON user FIELDS name SEARCH ANALYZER user_text BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS user_email_search ON user FIELDS email SEARCH ANALYZER user_text BM25 HIGHLIGHTS;
+DEFINE INDEX IF NOT EXISTS todo_tags ON todo FIELDS tags;
+DEFINE TABLE IF NOT EXISTS depends_on TYPE RELATION IN todo OUT todo;
+DEFINE INDEX IF NOT EXISTS depends_on_unique ON depends_on FIELDS in, out UNIQUE;
+DEFINE EVENT IF NOT EXISTS todo_orphan_subtasks ON todo WHEN $event = 'DELETE' THEN (UPDATE todo SET parent = NONE WHERE parent = $before.id);
+DEFINE INDEX IF NOT EXISTS audit_resource ON audit FIELDS resource;
+DEFINE INDEX IF NOT EXISTS audit_at ON audit FIELDS at;
+DEFINE FUNCTION IF NOT EXISTS fn::audit($event: string, $record: record, $before: any, $after: any, $context: any) {
+    CREATE audit CONTENT {
+        at: time::now(),
+        action: string::lowercase($event),
+        table: record::tb($record),
+        resource: string::concat(record::tb($record), ':', <string> record::id($record)),
+        actor: $context.actor,
+        request_id: $context.request_id,
+        before: $before,
+        after: $after,
+    };
+};
+DEFINE EVENT IF NOT EXISTS todo_audit ON todo THEN fn::audit($event, $value.id, $before, $after, $audit);
+DEFINE EVENT IF NOT EXISTS user_audit ON user THEN fn::audit($event, $value.id, $before, $after, $audit);
+DEFINE EVENT IF NOT EXISTS role_audit ON role THEN fn::audit($event, $value.id, $before, $after, $audit);
+DEFINE INDEX IF NOT EXISTS history_version ON history FIELDS resource, version UNIQUE;
+DEFINE FUNCTION IF NOT EXISTS fn::snapshot($event: string, $record: record, $after: any) {
+    LET $latest = math::max(SELECT VALUE version FROM history WHERE resource = $record) ?? 0;
+    CREATE history CONTENT {
+        resource: $record,
+        version: $latest + 1,
+        at: time::now(),
+        action: string::lowercase($event),
+        record: IF $event = 'DELETE' THEN NONE ELSE $after END,
+    };
+};
+DEFINE EVENT IF NOT EXISTS todo_history ON todo THEN fn::snapshot($event, $value.id, $after);
+DEFINE EVENT IF NOT EXISTS user_history ON user THEN fn::snapshot($event, $value.id, $after);
+DEFINE INDEX IF NOT EXISTS webhook_delivery_due ON webhook_delivery FIELDS status, next_attempt_at;
+DEFINE INDEX IF NOT EXISTS webhook_delivery_webhook ON webhook_delivery FIELDS webhook;
+DEFINE INDEX IF NOT EXISTS outbox_due ON outbox FIELDS status, next_attempt_at;
+DEFINE INDEX IF NOT EXISTS outbox_sequence ON outbox FIELDS sequence UNIQUE;
+DEFINE FUNCTION IF NOT EXISTS fn::outbox($event: string, $record: record, $before: any, $after: any, $context: any) {
+    LET $table = record::tb($record);
+    LET $types = IF $event = 'CREATE' THEN [string::concat($table, '.created')]
+        ELSE IF $event = 'DELETE' THEN [string::concat($table, '.deleted')]
+        ELSE IF $table = 'todo' AND $after.completed = true AND ($before.completed ?? false) != true
+            THEN [string::concat($table, '.updated'), 'todo.completed']
+        ELSE [string::concat($table, '.updated')] END;
+    FOR $type IN $types {
+        LET $sequence = (UPSERT outbox_sequence:counter SET last += 1 RETURN VALUE last)[0];
+        CREATE type::thing('outbox', rand::ulid()) CONTENT {
+            sequence: $sequence,
+            event: $type,
+            resource: $record,
+            data: $after ?? $before,
+            request_id: $context.request_id,
+            at: time::now(),
+            status: 'pending',
+            attempts: 0,
+            published_to: [],
+            next_attempt_at: time::now(),
+        };
+    };
+};
+DEFINE EVENT IF NOT EXISTS todo_outbox ON todo THEN fn::outbox($event, $value.id, $before, $after, $audit);
+DEFINE EVENT IF NOT EXISTS user_outbox ON user THEN fn::outbox($event, $value.id, $before, $after, $audit);
+DEFINE EVENT IF NOT EXISTS role_outbox ON role THEN fn::outbox($event, $value.id, $before, $after, $audit);
+";
+
 #[derive(Debug, Clone)]
 pub struct Database {
-    pub client: Surreal<Client>,
+    pub client: Surreal<Any>,
     pub namespace: String,
     pub database: String,
 }
@@ -27,27 +105,52 @@ impl Database {
         let surreal_database =
             env::var("SURREAL_DATABASE").expect("SURREAL_DATABASE environment variable is not set");
 
+        let credentials = Root {
+            username: &surreal_user,
+            password: &surreal_password,
+        };
+        Self::connect(
+            &surreal_address,
+            Some(credentials),
+            &surreal_namespace,
+            &surreal_database,
+        )
+        .await
+    }
+
+    /// Connects to any engine SurrealDB supports, e.g. `ws://host:port` or `mem://`.
+    ///
+    /// Addresses without a scheme are treated as WebSocket endpoints.
+    pub async fn connect(
+        address: &str,
+        credentials: Option<Root<'_>>,
+        namespace: &str,
+        database: &str,
+    ) -> Result<Arc<Self>> {
+        let address = if address.contains("://") {
+            address.to_string()
+        } else {
+            format!("ws://{}", address)
+        };
+
         // Establish database connection
-        let client = Surreal::new::<Ws>(&surreal_address).await?;
+        let client = any::connect(address).await?;
 
         // Authenticate with provided credentials
-        client
-            .signin(Root {
-                username: &surreal_user,
-                password: &surreal_password,
-            })
-            .await?;
+        if let Some(credentials) = credentials {
+            client.signin(credentials).await?;
+        }
 
         // Set namespace and database context
-        client
-            .use_ns(&surreal_namespace)
-            .use_db(&surreal_database)
-            .await?;
+        client.use_ns(namespace).use_db(database).await?;
+
+        // Define search analyzers and indexes
+        client.query(SCHEMA).await?.check()?;
 
         Ok(Arc::new(Self {
             client,
-            namespace: surreal_namespace,
-            database: surreal_database,
+            namespace: namespace.to_string(),
+            database: database.to_string(),
         }))
     }
-}
\ No newline at end of file
+}
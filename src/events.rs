@@ -0,0 +1,48 @@
+use crate::data::models::outbox::DomainEvent;
+use crate::data::models::todo::Todo;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// How many events a slow subscriber can fall behind before it starts
+/// missing them.
+const CAPACITY: usize = 256;
+
+/// Something that happened in the application, published to in-process
+/// subscribers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    ReminderFired {
+        todo: Todo,
+    },
+    /// A todo, user or role change, relayed from the outbox
+    Domain {
+        event: DomainEvent,
+    },
+}
+
+/// Fan-out channel for `Event`s. Publishing never blocks, and events sent
+/// while nobody is subscribed are dropped.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, event: Event) {
+        // An error only means there are no subscribers right now
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
@@ -1,3 +1,18 @@
+pub mod audit;
+pub mod config;
 pub mod db;
 pub mod data;
-pub mod routers;
\ No newline at end of file
+pub mod events;
+pub mod live;
+pub mod metrics;
+pub mod middleware;
+pub mod outbox;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod rate_limit;
+pub mod reminders;
+pub mod retry;
+pub mod routers;
+pub mod state;
+pub mod telemetry;
+pub mod webhooks;
\ No newline at end of file
@@ -0,0 +1,310 @@
+use crate::data::models::role::Role;
+use crate::data::models::todo::Todo;
+use crate::data::models::user::User;
+use crate::db::Database;
+use chrono::{DateTime, Local};
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use surrealdb::Action;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+/// How many recent changes are kept for clients resuming after a disconnect.
+const BACKLOG: usize = 1000;
+
+/// How long to wait before restarting a live query that failed.
+const RETRY: Duration = Duration::from_secs(5);
+
+/// A table whose changes are published.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Resource {
+    Todo,
+    User,
+    Role,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 3] = [Resource::Todo, Resource::User, Resource::Role];
+
+    pub fn table(self) -> &'static str {
+        match self {
+            Resource::Todo => "todo",
+            Resource::User => "user",
+            Resource::Role => "role",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|resource| resource.table() == name.trim())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl ChangeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeAction::Create => "create",
+            ChangeAction::Update => "update",
+            ChangeAction::Delete => "delete",
+        }
+    }
+}
+
+/// One created, updated or deleted record.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Change {
+    /// Increases with every change; pass it back to resume after it
+    pub id: u64,
+    pub resource: Resource,
+    pub action: ChangeAction,
+    /// The record as the REST endpoints return it; for a delete, as it was
+    #[schema(value_type = Object)]
+    pub record: serde_json::Value,
+    pub at: DateTime<Local>,
+}
+
+/// What a subscriber receives.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedItem {
+    Change(Change),
+    /// Changes were missed, e.g. the resume id is older than the backlog.
+    /// Refetch, then carry on from `latest`.
+    Reset {
+        latest: u64,
+    },
+}
+
+#[derive(Debug)]
+struct Backlog {
+    next_id: u64,
+    recent: VecDeque<Change>,
+}
+
+/// Every change seen by the live queries, fanned out to subscribers. Recent
+/// changes are kept so a client can resume from the last id it saw.
+#[derive(Clone, Debug)]
+pub struct ChangeFeed {
+    backlog: Arc<Mutex<Backlog>>,
+    sender: broadcast::Sender<Change>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        // Ids start at the startup time so they keep increasing across restarts
+        let start = Local::now().timestamp_millis().max(0) as u64;
+        ChangeFeed {
+            backlog: Arc::new(Mutex::new(Backlog {
+                next_id: start,
+                recent: VecDeque::with_capacity(BACKLOG),
+            })),
+            sender: broadcast::channel(BACKLOG).0,
+        }
+    }
+}
+
+impl ChangeFeed {
+    pub fn publish(
+        &self,
+        resource: Resource,
+        action: ChangeAction,
+        record: serde_json::Value,
+    ) -> Change {
+        let mut backlog = self.backlog.lock().unwrap();
+        let change = Change {
+            id: backlog.next_id,
+            resource,
+            action,
+            record,
+            at: Local::now(),
+        };
+        backlog.next_id += 1;
+        if backlog.recent.len() == BACKLOG {
+            backlog.recent.pop_front();
+        }
+        backlog.recent.push_back(change.clone());
+        // Sent under the lock so a new subscriber sees each change exactly once
+        let _ = self.sender.send(change.clone());
+        change
+    }
+
+    /// Changes from now on, first replaying those after `after` when given.
+    /// Yields a reset first when the changes after `after` are no longer
+    /// all kept, and whenever the subscriber falls too far behind.
+    pub fn subscribe(
+        &self,
+        after: Option<u64>,
+        keep: impl Fn(&Change) -> bool + Send + 'static,
+    ) -> impl Stream<Item = FeedItem> + Send {
+        let backlog = self.backlog.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let latest = backlog.next_id - 1;
+        let oldest = backlog.recent.front().map_or(backlog.next_id, |c| c.id);
+        let replay = match after {
+            None => Vec::new(),
+            Some(after) if after > latest || after.saturating_add(1) < oldest => {
+                vec![FeedItem::Reset { latest }]
+            }
+            Some(after) => backlog
+                .recent
+                .iter()
+                .filter(|change| change.id > after)
+                .cloned()
+                .map(FeedItem::Change)
+                .collect(),
+        };
+        drop(backlog);
+
+        let feed = self.clone();
+        let live = stream::unfold(receiver, move |mut receiver| {
+            let feed = feed.clone();
+            async move {
+                let item = match receiver.recv().await {
+                    Ok(change) => FeedItem::Change(change),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        // Skip straight to the present; the client refetches
+                        let backlog = feed.backlog.lock().unwrap();
+                        receiver = feed.sender.subscribe();
+                        FeedItem::Reset {
+                            latest: backlog.next_id - 1,
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                };
+                Some((item, receiver))
+            }
+        });
+        stream::iter(replay).chain(live).filter(move |item| {
+            let pass = match item {
+                FeedItem::Change(change) => keep(change),
+                FeedItem::Reset { .. } => true,
+            };
+            async move { pass }
+        })
+    }
+}
+
+/// Runs a `LIVE SELECT` on every `Resource` table, publishing each change to
+/// `feed`. A query that fails is restarted after a pause.
+pub fn spawn(db: Arc<Database>, feed: ChangeFeed) -> Vec<JoinHandle<()>> {
+    vec![
+        tokio::spawn(watch::<Todo>(db.clone(), feed.clone(), Resource::Todo)),
+        tokio::spawn(watch::<User>(db.clone(), feed.clone(), Resource::User)),
+        tokio::spawn(watch::<Role>(db, feed, Resource::Role)),
+    ]
+}
+
+async fn watch<T>(db: Arc<Database>, feed: ChangeFeed, resource: Resource)
+where
+    T: Serialize + DeserializeOwned + Unpin + Send + 'static,
+{
+    loop {
+        match db.client.select::<Vec<T>>(resource.table()).live().await {
+            Ok(mut notifications) => {
+                while let Some(notification) = notifications.next().await {
+                    let notification = match notification {
+                        Ok(notification) => notification,
+                        Err(e) => {
+                            tracing::warn!(error = %e, table = resource.table(), "Skipped unreadable change");
+                            continue;
+                        }
+                    };
+                    let action = match notification.action {
+                        Action::Create => ChangeAction::Create,
+                        Action::Update => ChangeAction::Update,
+                        Action::Delete => ChangeAction::Delete,
+                        _ => continue,
+                    };
+                    match serde_json::to_value(&notification.data) {
+                        Ok(record) => {
+                            feed.publish(resource, action, record);
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = %e, table = resource.table(), "Skipped unreadable change")
+                        }
+                    }
+                }
+                tracing::warn!(table = resource.table(), "Live query ended");
+            }
+            Err(e) => {
+                tracing::error!(error = %e, table = resource.table(), "Failed to start live query")
+            }
+        }
+        tokio::time::sleep(RETRY).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ids(items: Vec<FeedItem>) -> Vec<Option<u64>> {
+        items
+            .into_iter()
+            .map(|item| match item {
+                FeedItem::Change(change) => Some(change.id),
+                FeedItem::Reset { .. } => None,
+            })
+            .collect()
+    }
+
+    async fn next(stream: impl Stream<Item = FeedItem>, count: usize) -> Vec<FeedItem> {
+        tokio::time::timeout(Duration::from_secs(1), stream.take(count).collect())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn resumes_after_a_known_id() {
+        let feed = ChangeFeed::default();
+        let first = feed.publish(Resource::Todo, ChangeAction::Create, json!({}));
+        let second = feed.publish(Resource::User, ChangeAction::Update, json!({}));
+        let third = feed.publish(Resource::Todo, ChangeAction::Delete, json!({}));
+
+        let todos = feed.subscribe(Some(first.id - 1), |c| c.resource == Resource::Todo);
+        assert_eq!(
+            ids(next(todos, 2).await),
+            vec![Some(first.id), Some(third.id)]
+        );
+
+        let all = Box::pin(feed.subscribe(Some(second.id), |_| true));
+        let fourth = feed.publish(Resource::Role, ChangeAction::Create, json!({}));
+        assert_eq!(
+            ids(next(all, 2).await),
+            vec![Some(third.id), Some(fourth.id)]
+        );
+    }
+
+    #[tokio::test]
+    async fn resets_when_changes_were_missed() {
+        let feed = ChangeFeed::default();
+        let first = feed.publish(Resource::Todo, ChangeAction::Create, json!({}));
+        for _ in 0..BACKLOG {
+            feed.publish(Resource::Todo, ChangeAction::Update, json!({}));
+        }
+
+        let stale = feed.subscribe(Some(first.id - 1), |_| true);
+        match next(stale, 1).await.remove(0) {
+            FeedItem::Reset { latest } => assert_eq!(latest, first.id + BACKLOG as u64),
+            item => panic!("expected a reset, got {:?}", item),
+        }
+        let unknown = feed.subscribe(Some(u64::MAX - 1), |_| true);
+        assert_eq!(ids(next(unknown, 1).await), vec![None]);
+    }
+}
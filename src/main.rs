@@ -1,60 +1,151 @@
 use axum::http::{
-    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
     HeaderValue, Method,
 };
+use rss_boilerplate::audit;
+use rss_boilerplate::config::Config;
+use rss_boilerplate::data::data_context::DataContext;
+use rss_boilerplate::data::migrations;
 use rss_boilerplate::db::Database;
+use rss_boilerplate::live;
+use rss_boilerplate::metrics;
+use rss_boilerplate::outbox;
+use rss_boilerplate::rate_limit;
+use rss_boilerplate::reminders;
+use rss_boilerplate::middleware::metrics::track_http;
+use rss_boilerplate::middleware::rate_limit::{
+    rate_limit, API_KEY_HEADER, RATELIMIT_LIMIT, RATELIMIT_POLICY, RATELIMIT_REMAINING,
+    RATELIMIT_RESET,
+};
+use rss_boilerplate::middleware::request_id::{request_id, REQUEST_ID_HEADER};
 use std::net::SocketAddr;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 extern crate dotenv;
-use axum::{Extension, Router};
+use axum::{middleware, Router};
 use dotenv::dotenv;
 use rss_boilerplate::routers::api_router::api_router;
-use std::env;
+use rss_boilerplate::state::AppState;
+use rss_boilerplate::telemetry;
+use rss_boilerplate::webhooks::{self, Dispatcher};
 
 #[tokio::main]
 async fn main() {
     // Initialize the environment variables
     dotenv().ok();
 
+    // Initialize logging and tracing
+    let _telemetry = telemetry::init();
+
     // Load the environment variables
-    let host = env::var("HOST").unwrap_or("0.0.0.0".to_string());
-    let port = env::var("PORT").unwrap_or("8080".to_string());
-    let allowed_origins =
-        env::var("ALLOWED_ORIGINS").unwrap_or("http://localhost:3000".to_string());
+    let config = Config::from_env();
 
     // Connect to the database
     let db = Database::init()
         .await
         .expect("Failed to connect to the database");
-    println!("🚀 Database connected successfully");
+    tracing::info!("Database connected successfully");
+
+    // Bring stored emails and phone numbers into their canonical forms, once
+    migrations::run(&db, &config.contacts)
+        .await
+        .expect("Failed to run migrations");
 
     // Setup the CORS layer
     let cors = CorsLayer::new()
         .allow_origin(
-            allowed_origins
+            config
+                .allowed_origins
                 .parse::<HeaderValue>()
                 .expect("Invalid ALLOWED_ORIGINS header value"),
         )
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
         .allow_credentials(true)
-        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
+        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, REQUEST_ID_HEADER, API_KEY_HEADER])
+        .expose_headers([
+            REQUEST_ID_HEADER,
+            RATELIMIT_LIMIT,
+            RATELIMIT_REMAINING,
+            RATELIMIT_RESET,
+            RATELIMIT_POLICY,
+            RETRY_AFTER,
+        ]);
+
+    // Build the shared application state
+    let state = AppState::new(DataContext::new(db.clone(), &config), config.clone());
+
+    // Fire todo reminders in the background
+    reminders::spawn(state.data.todos(), state.events.clone(), config.reminder_interval);
+
+    // Drop audit entries past their retention once an hour
+    audit::spawn(
+        state.data.audit(),
+        config.audit_retention,
+        Duration::from_secs(60 * 60),
+    );
+
+    // Publish outbox events to their sinks, in order, retrying failures
+    let sinks = config.outbox_sinks.iter().map(|kind| kind.build(&state)).collect();
+    outbox::spawn(
+        outbox::Dispatcher::new(state.data.outbox(), sinks, config.outbox_retry.clone()),
+        config.outbox_interval,
+        config.outbox_retention,
+    );
+
+    // Send queued webhook deliveries, retrying failures
+    webhooks::spawn(
+        Dispatcher::new(
+            state.data.webhooks(),
+            config.webhook_retry.clone(),
+            config.webhook_allow_private_targets,
+        ),
+        config.webhook_interval,
+    );
+
+    // Forget rate limit buckets once they have refilled
+    rate_limit::spawn(state.data.rate_limits(), Duration::from_secs(10 * 60));
+
+    // Publish todo, user and role changes to live feed subscribers
+    live::spawn(db, state.changes.clone());
 
     // Create the router
     let app = Router::new()
         .nest("/api", api_router())
+        .layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .layer(middleware::from_fn(track_http))
+        .layer(telemetry::trace_layer())
+        .layer(middleware::from_fn(request_id))
         .layer(cors)
-        .layer(Extension(db));
+        .with_state(state);
+
+    // Serve metrics on their own port so they aren't exposed alongside the API
+    let metrics_addr: SocketAddr = format!("{}:{}", config.host, config.metrics_port)
+        .parse()
+        .expect("Invalid host or metrics port");
+    let metrics_listener = tokio::net::TcpListener::bind(metrics_addr)
+        .await
+        .expect("Failed to bind metrics address");
+    tracing::info!(%metrics_addr, "Metrics server starting");
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(metrics_listener, metrics::router()).await {
+            tracing::error!(error = %e, "Metrics server stopped");
+        }
+    });
 
     // Start the server
-    let addr: SocketAddr = format!("{}:{}", host, port)
+    let addr: SocketAddr = format!("{}:{}", config.host, config.port)
         .parse()
         .expect("Invalid host or port");
 
-    println!("🚀 Server starting at {}", addr);
+    tracing::info!(%addr, "Server starting");
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("Failed to bind address");
-    axum::serve(listener, app)
-        .await
-        .expect("Server failed to start");
+    // Connection info gives the rate limiter each client's address
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("Server failed to start");
 }
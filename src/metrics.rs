@@ -0,0 +1,231 @@
+use axum::http::{header::CONTENT_TYPE, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, Encoder, HistogramVec,
+    IntCounterVec, IntGauge, TextEncoder,
+};
+use serde::de::DeserializeOwned;
+use std::future::IntoFuture;
+use std::time::Instant;
+use surrealdb::opt::QueryResult;
+use surrealdb::Response;
+
+pub static HTTP_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "http_requests_total",
+        "Number of HTTP requests handled",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register http_requests_total")
+});
+
+pub static HTTP_REQUEST_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latency in seconds",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register http_request_duration_seconds")
+});
+
+pub static HTTP_REQUESTS_IN_FLIGHT: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "http_requests_in_flight",
+        "Number of HTTP requests currently being handled"
+    )
+    .expect("Failed to register http_requests_in_flight")
+});
+
+pub static HTTP_REQUESTS_RATE_LIMITED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "http_requests_rate_limited_total",
+        "Number of HTTP requests rejected by the rate limiter",
+        &["rule"]
+    )
+    .expect("Failed to register http_requests_rate_limited_total")
+});
+
+pub static DB_QUERY_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "db_query_duration_seconds",
+        "SurrealDB query latency in seconds",
+        &["repository", "method"]
+    )
+    .expect("Failed to register db_query_duration_seconds")
+});
+
+pub static DB_QUERY_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "db_query_errors_total",
+        "Number of SurrealDB queries that returned an error",
+        &["repository", "method"]
+    )
+    .expect("Failed to register db_query_errors_total")
+});
+
+/// Runs a SurrealDB query, recording its latency and whether it failed.
+pub async fn observe_query<T, F>(
+    repository: &'static str,
+    method: &'static str,
+    query: F,
+) -> Result<T, surrealdb::Error>
+where
+    F: IntoFuture<Output = Result<T, surrealdb::Error>>,
+{
+    let start = Instant::now();
+    let result = query.await;
+    DB_QUERY_DURATION_SECONDS
+        .with_label_values(&[repository, method])
+        .observe(start.elapsed().as_secs_f64());
+    if result.is_err() {
+        DB_QUERY_ERRORS_TOTAL
+            .with_label_values(&[repository, method])
+            .inc();
+    }
+    result
+}
+
+/// Runs raw SurrealQL like `observe_query`, also counting the query as
+/// failed when any of its statements is. Statements fail without failing the
+/// query, so their errors only surface once the response is read.
+pub async fn observe_statements<F>(
+    repository: &'static str,
+    method: &'static str,
+    query: F,
+) -> Result<Statements, surrealdb::Error>
+where
+    F: IntoFuture<Output = Result<Response, surrealdb::Error>>,
+{
+    let response = observe_query(repository, method, query).await?;
+    Ok(Statements {
+        response,
+        labels: [repository, method],
+        failed: false,
+    })
+}
+
+/// A query response that counts the query as failed, once, when a statement
+/// error is taken or checked, or is left unread when it is dropped.
+pub struct Statements {
+    response: Response,
+    labels: [&'static str; 2],
+    failed: bool,
+}
+
+// Errors are surrealdb's own, as `Response` returns them
+#[allow(clippy::result_large_err)]
+impl Statements {
+    /// Takes the result of a statement, as `Response::take` does.
+    pub fn take<R>(&mut self, index: impl QueryResult<R>) -> Result<R, surrealdb::Error>
+    where
+        R: DeserializeOwned,
+    {
+        let result = self.response.take(index);
+        if result.is_err() {
+            self.fail();
+        }
+        result
+    }
+
+    /// Returns the first statement error, if any, as `Response::check` does.
+    pub fn check(mut self) -> Result<Self, surrealdb::Error> {
+        let mut errors = self.response.take_errors();
+        match errors.keys().min().copied() {
+            Some(first) => {
+                self.fail();
+                Err(errors.remove(&first).expect("first is a key of errors"))
+            }
+            None => Ok(self),
+        }
+    }
+
+    fn fail(&mut self) {
+        if !self.failed {
+            self.failed = true;
+            DB_QUERY_ERRORS_TOTAL.with_label_values(&self.labels).inc();
+        }
+    }
+}
+
+impl Drop for Statements {
+    fn drop(&mut self) {
+        if !self.response.take_errors().is_empty() {
+            self.fail();
+        }
+    }
+}
+
+/// Router serving the Prometheus scrape endpoint, meant to be bound to its own port.
+pub fn router() -> Router {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+pub async fn metrics_handler() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, encoder.format_type().to_string())],
+            buffer,
+        ),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to encode metrics");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(CONTENT_TYPE, "text/plain".to_string())],
+                Vec::new(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use surrealdb::engine::any::connect;
+
+    #[tokio::test]
+    async fn failed_statements_count_once_per_query() {
+        let db = connect("mem://").await.unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        let errors = || {
+            DB_QUERY_ERRORS_TOTAL
+                .with_label_values(&["metrics", "statements"])
+                .get()
+        };
+        let query = || db.query("RETURN 1; THROW 'first'; THROW 'second'");
+
+        let mut response = observe_statements("metrics", "statements", query())
+            .await
+            .unwrap();
+        assert_eq!(response.take::<Option<i64>>(0).unwrap(), Some(1));
+        assert_eq!(errors(), 0);
+        assert!(response.take::<Option<i64>>(1).is_err());
+        assert!(response.take::<Option<i64>>(2).is_err());
+        assert_eq!(errors(), 1);
+
+        let response = observe_statements("metrics", "statements", query())
+            .await
+            .unwrap();
+        assert!(response.check().is_err());
+        assert_eq!(errors(), 2);
+
+        // Errors nobody reads are counted too
+        drop(
+            observe_statements("metrics", "statements", query())
+                .await
+                .unwrap(),
+        );
+        assert_eq!(errors(), 3);
+
+        let response = observe_statements("metrics", "statements", db.query("RETURN 1"))
+            .await
+            .unwrap();
+        drop(response.check().unwrap());
+        assert_eq!(errors(), 3);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::metrics::{HTTP_REQUESTS_IN_FLIGHT, HTTP_REQUESTS_TOTAL, HTTP_REQUEST_DURATION_SECONDS};
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::time::Instant;
+
+/// Counts a request as in flight until dropped, which also happens when the
+/// client goes away or the request times out before a response.
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        HTTP_REQUESTS_IN_FLIGHT.inc();
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        HTTP_REQUESTS_IN_FLIGHT.dec();
+    }
+}
+
+/// Records request count, latency and in-flight requests per route template.
+pub async fn track_http(request: Request, next: Next) -> Response {
+    // Unmatched paths share one label so scanners can't blow up the series count
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = request.method().to_string();
+
+    let in_flight = InFlight::start();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    drop(in_flight);
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    HTTP_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(start.elapsed().as_secs_f64());
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn abandoned_requests_leave_the_in_flight_count() {
+        let app = Router::new()
+            .route("/", get(std::future::pending::<()>))
+            .layer(middleware::from_fn(track_http));
+        let before = HTTP_REQUESTS_IN_FLIGHT.get();
+
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        let abandoned = tokio::time::timeout(Duration::from_millis(50), app.oneshot(request));
+        assert!(abandoned.await.is_err());
+        assert_eq!(HTTP_REQUESTS_IN_FLIGHT.get(), before);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::audit::AuditContext;
+use crate::metrics::HTTP_REQUESTS_RATE_LIMITED_TOTAL;
+use crate::rate_limit::RateLimitConfig;
+use crate::state::AppState;
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+pub const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+const FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Tries at taking a token before giving up, as concurrent takes on a shared
+/// bucket can conflict.
+const TAKE_ATTEMPTS: u32 = 3;
+
+/// Who a request counts against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Client {
+    /// The authenticated caller
+    User(String),
+    /// A digest of the API key, so keys aren't stored
+    ApiKey(String),
+    Ip(IpAddr),
+    /// No address is known, e.g. when the server isn't given connection info
+    Unknown,
+}
+
+impl Client {
+    /// The authenticated user when there is one, else the API key when
+    /// trusted and sent, else the client address.
+    pub fn identify(config: &RateLimitConfig, request: &Request) -> Self {
+        if let Some(actor) = AuditContext::caller() {
+            return Client::User(actor);
+        }
+        let headers = request.headers();
+        if config.by_api_key {
+            if let Some(key) = header(headers, &API_KEY_HEADER) {
+                let digest = Sha256::digest(key.as_bytes());
+                return Client::ApiKey(hex::encode(&digest[..16]));
+            }
+        }
+        let forwarded = config
+            .trust_forwarded
+            .then(|| headers.get_all(&FORWARDED_FOR).iter().next_back())
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            // Our proxy appends the address it saw; entries before it are
+            // whatever the client sent, so only the last can be trusted
+            .and_then(|value| value.rsplit(',').next()?.trim().parse().ok());
+        let connected = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip());
+        forwarded.or(connected).map_or(Client::Unknown, Client::Ip)
+    }
+}
+
+impl fmt::Display for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Client::User(user) => write!(f, "user:{}", user),
+            Client::ApiKey(digest) => write!(f, "key:{}", digest),
+            Client::Ip(ip) => write!(f, "ip:{}", ip),
+            Client::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Takes a token from the client's bucket for the route, answering 429 with
+/// `Retry-After` once it is empty. Every limited response carries the
+/// `RateLimit-*` headers. Requests are refused with 503 if the buckets can't
+/// be read.
+pub async fn rate_limit(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let limits = &state.config.rate_limit;
+    // Unmatched paths are limited under their own path, by the default quota
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| request.uri().path().to_owned());
+    let Some((rule, quota)) = limits.quota_for(request.method(), &route) else {
+        return next.run(request).await;
+    };
+    let key = format!("{}|{}", rule, Client::identify(limits, &request));
+
+    let store = state.data.rate_limits();
+    let mut attempt = 1;
+    let take = loop {
+        match store.take(key.clone(), quota, Local::now()).await {
+            Ok(take) => break take,
+            Err(e) if attempt < TAKE_ATTEMPTS => {
+                tracing::debug!(error = %e, attempt, "Retrying rate limit check");
+                tokio::time::sleep(Duration::from_millis(10 * u64::from(attempt))).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to check rate limit; refusing the request");
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(RETRY_AFTER, HeaderValue::from(1))],
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": "Rate limit could not be checked; retry shortly"
+                    })),
+                )
+                    .into_response();
+            }
+        }
+    };
+
+    let seconds = |wait: Duration| {
+        // Round up so clients never come back too early
+        HeaderValue::from(wait.as_millis().div_ceil(1000) as u64)
+    };
+    let mut headers = HeaderMap::new();
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(quota.burst));
+    headers.insert(
+        RATELIMIT_REMAINING,
+        HeaderValue::from(take.tokens.floor() as u64),
+    );
+    headers.insert(
+        RATELIMIT_RESET,
+        seconds(quota.wait(take.tokens, f64::from(quota.burst))),
+    );
+    headers.insert(
+        RATELIMIT_POLICY,
+        HeaderValue::from_str(&format!("{};w={}", quota.burst, quota.period.as_secs()))
+            .expect("policy is a valid header value"),
+    );
+
+    let mut response = if take.allowed {
+        next.run(request).await
+    } else {
+        HTTP_REQUESTS_RATE_LIMITED_TOTAL
+            .with_label_values(&[rule.as_str()])
+            .inc();
+        let retry_after = quota.wait(take.tokens, 1.0);
+        headers.insert(RETRY_AFTER, seconds(retry_after));
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!(
+                    "Too many requests; retry in {} seconds",
+                    retry_after.as_millis().div_ceil(1000)
+                )
+            })),
+        )
+            .into_response()
+    };
+    response.headers_mut().extend(headers);
+    response
+}
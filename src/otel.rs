@@ -0,0 +1,100 @@
+use axum::http::HeaderMap;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{ExporterBuildError, Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::env;
+use tracing::Span;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// OTLP exporter settings, read from the standard `OTEL_*` environment variables.
+#[derive(Clone, Debug)]
+pub struct OtelConfig {
+    pub service_name: String,
+    pub protocol: Protocol,
+    /// Full collector URL; when unset the exporter falls back to
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` or its built-in default.
+    pub endpoint: Option<String>,
+}
+
+impl OtelConfig {
+    pub fn from_env() -> Self {
+        let protocol = match env::var("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+            Ok("grpc") => Protocol::Grpc,
+            _ => Protocol::HttpBinary,
+        };
+
+        OtelConfig {
+            service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or(env!("CARGO_PKG_NAME").to_string()),
+            protocol,
+            endpoint: env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").ok(),
+        }
+    }
+}
+
+/// Builds a tracer provider that batches spans to the configured OTLP collector.
+pub fn tracer_provider(config: &OtelConfig) -> Result<SdkTracerProvider, ExporterBuildError> {
+    let exporter = match config.protocol {
+        Protocol::Grpc => {
+            let builder = SpanExporter::builder().with_tonic();
+            match &config.endpoint {
+                Some(endpoint) => builder.with_endpoint(endpoint).build()?,
+                None => builder.build()?,
+            }
+        }
+        protocol => {
+            let builder = SpanExporter::builder().with_http().with_protocol(protocol);
+            match &config.endpoint {
+                Some(endpoint) => builder.with_endpoint(endpoint).build()?,
+                None => builder.build()?,
+            }
+        }
+    };
+
+    let resource = Resource::builder()
+        .with_service_name(config.service_name.clone())
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+/// Bridges `tracing` spans into the given provider and installs the W3C
+/// `traceparent` propagator.
+pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider.clone());
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+}
+
+/// Continues the caller's trace when the request carries a `traceparent` header.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let context =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    if let Err(e) = span.set_parent(context) {
+        tracing::debug!(error = %e, "Failed to attach remote trace context");
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
@@ -0,0 +1,276 @@
+use crate::data::models::outbox::{DomainEvent, OutboxStatus, PublishOutcome};
+use crate::data::stores::{OutboxStore, WebhookStore};
+use crate::events::{Event, EventBus};
+use crate::retry::RetryPolicy;
+use crate::state::AppState;
+use async_trait::async_trait;
+use chrono::{Local, TimeDelta};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use surrealdb::Error;
+use tokio::task::JoinHandle;
+
+/// Most events published per poll.
+pub const BATCH: usize = 100;
+
+/// How often published events past their retention are deleted.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Retries for events, overridden by the `OUTBOX_*` variables. The timeout
+/// is how long each sink has to take an event.
+pub const DEFAULT_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: 10,
+    backoff: Duration::from_secs(1),
+    max_backoff: Duration::from_secs(5 * 60),
+    timeout: Duration::from_secs(10),
+};
+
+/// Somewhere outbox events are published to.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// Recorded on the events the sink has taken, so must be unique.
+    fn name(&self) -> &str;
+    async fn publish(&self, event: &DomainEvent) -> Result<(), String>;
+}
+
+/// Publishes events to in-process subscribers on the `EventBus`.
+pub struct ChannelSink(pub EventBus);
+
+#[async_trait]
+impl Sink for ChannelSink {
+    fn name(&self) -> &str {
+        "channel"
+    }
+
+    async fn publish(&self, event: &DomainEvent) -> Result<(), String> {
+        self.0.publish(Event::Domain {
+            event: event.clone(),
+        });
+        Ok(())
+    }
+}
+
+/// Queues events for the webhooks subscribed to them.
+pub struct WebhookSink(pub Arc<dyn WebhookStore>);
+
+#[async_trait]
+impl Sink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn publish(&self, event: &DomainEvent) -> Result<(), String> {
+        self.0
+            .enqueue(event.clone())
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Logs each event.
+pub struct LogSink;
+
+#[async_trait]
+impl Sink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn publish(&self, event: &DomainEvent) -> Result<(), String> {
+        tracing::info!(
+            id = %event.id,
+            event = %event.event,
+            resource = %event.resource,
+            request_id = ?event.request_id,
+            "Domain event"
+        );
+        Ok(())
+    }
+}
+
+/// The sinks that can be switched on with `OUTBOX_SINKS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkKind {
+    Channel,
+    Webhook,
+    Log,
+}
+
+impl SinkKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "channel" => Some(SinkKind::Channel),
+            "webhook" => Some(SinkKind::Webhook),
+            "log" => Some(SinkKind::Log),
+            _ => None,
+        }
+    }
+
+    /// The comma-separated sinks in `OUTBOX_SINKS`, `channel,webhook` when
+    /// unset. Unknown names are skipped with a warning.
+    pub fn from_env() -> Vec<Self> {
+        let names = env::var("OUTBOX_SINKS").unwrap_or("channel,webhook".to_string());
+        names
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .filter_map(|name| {
+                let kind = SinkKind::parse(name);
+                if kind.is_none() {
+                    tracing::warn!(sink = name.trim(), "Skipped unknown outbox sink");
+                }
+                kind
+            })
+            .collect()
+    }
+
+    pub fn build(self, state: &AppState) -> Arc<dyn Sink> {
+        match self {
+            SinkKind::Channel => Arc::new(ChannelSink(state.events.clone())),
+            SinkKind::Webhook => Arc::new(WebhookSink(state.data.webhooks())),
+            SinkKind::Log => Arc::new(LogSink),
+        }
+    }
+}
+
+/// Publishes outbox events to every sink in the order they were written,
+/// retrying failures with exponential backoff. An event waiting to be
+/// retried holds back those after it, until it is published or given up on.
+#[derive(Clone)]
+pub struct Dispatcher {
+    store: Arc<dyn OutboxStore>,
+    sinks: Vec<Arc<dyn Sink>>,
+    policy: RetryPolicy,
+}
+
+impl Dispatcher {
+    pub fn new(
+        store: Arc<dyn OutboxStore>,
+        sinks: Vec<Arc<dyn Sink>>,
+        policy: RetryPolicy,
+    ) -> Self {
+        Dispatcher {
+            store,
+            sinks,
+            policy,
+        }
+    }
+
+    /// Publishes every event that is due, returning how many were attempted.
+    pub async fn publish_due(&self) -> Result<usize, Error> {
+        let now = Local::now();
+        // Long enough for every sink to time out on a whole batch
+        let Some(lease) = self.policy.lease(now, BATCH * self.sinks.len().max(1) + 1) else {
+            tracing::error!(timeout = ?self.policy.timeout, "Outbox timeout is too long to lease a batch");
+            return Ok(0);
+        };
+        let due = self.store.claim_due(now, lease, BATCH).await?;
+        let mut attempted = 0;
+        let mut due = due.into_iter();
+        while let Some(event) = due.next() {
+            attempted += 1;
+            if !self.attempt(event).await? {
+                // Keep the order: the rest wait for the failed event
+                let rest: Vec<String> = due.map(|event| event.key().to_string()).collect();
+                if !rest.is_empty() {
+                    self.store.release(rest, Local::now()).await?;
+                }
+                break;
+            }
+        }
+        Ok(attempted)
+    }
+
+    /// Offers `event` to each sink that has not taken it yet, returning
+    /// whether it is done with, published or dead.
+    async fn attempt(&self, event: DomainEvent) -> Result<bool, Error> {
+        let mut published_to = event.published_to.clone();
+        let mut errors = Vec::new();
+        for sink in &self.sinks {
+            if published_to.iter().any(|name| name == sink.name()) {
+                continue;
+            }
+            let result = tokio::time::timeout(self.policy.timeout, sink.publish(&event))
+                .await
+                .unwrap_or_else(|_| Err("Timed out".to_string()));
+            match result {
+                Ok(()) => published_to.push(sink.name().to_string()),
+                Err(e) => errors.push(format!("{}: {}", sink.name(), e)),
+            }
+        }
+
+        let failures = event.attempts + 1;
+        let (status, next_attempt_at) = if errors.is_empty() {
+            (OutboxStatus::Published, None)
+        } else if let Some(next) = self.policy.retry_at(Local::now(), failures) {
+            (OutboxStatus::Pending, Some(next))
+        } else {
+            tracing::warn!(
+                id = %event.id,
+                event = %event.event,
+                attempts = failures,
+                errors = ?errors,
+                "Outbox event dead-lettered"
+            );
+            (OutboxStatus::Dead, None)
+        };
+        let outcome = PublishOutcome {
+            status,
+            published_to,
+            next_attempt_at,
+            error: (!errors.is_empty()).then(|| errors.join("; ")),
+        };
+        self.store
+            .record_attempt(event.key().to_string(), outcome)
+            .await?;
+        Ok(status != OutboxStatus::Pending)
+    }
+}
+
+/// Deletes events published more than `retention` ago, returning how many.
+pub async fn prune(outbox: &dyn OutboxStore, retention: Duration) -> Result<usize, Error> {
+    // A retention reaching back before dates begin keeps everything
+    let Some(cutoff) = TimeDelta::from_std(retention)
+        .ok()
+        .and_then(|retention| Local::now().checked_sub_signed(retention))
+    else {
+        return Ok(0);
+    };
+    let pruned = outbox.prune(cutoff).await?;
+    if pruned > 0 {
+        tracing::info!(pruned, %cutoff, "Pruned outbox");
+    }
+    Ok(pruned)
+}
+
+/// Publishes due events every `interval`, draining full batches at once, and
+/// prunes published ones hourly in a task of its own, until the runtime shuts
+/// down. Returns the publishing task.
+pub fn spawn(dispatcher: Dispatcher, interval: Duration, retention: Duration) -> JoinHandle<()> {
+    let store = dispatcher.store.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = prune(store.as_ref(), retention).await {
+                tracing::error!(error = %e, "Failed to prune outbox");
+            }
+        }
+    });
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            loop {
+                match dispatcher.publish_due().await {
+                    Ok(BATCH) => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        tracing::error!(error = %e, "Failed to publish outbox events");
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
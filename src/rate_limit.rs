@@ -0,0 +1,253 @@
+use crate::data::models::rate_limit::{Bucket, Quota, Take};
+use crate::data::stores::RateLimitStore;
+use async_trait::async_trait;
+use axum::http::Method;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use surrealdb::Error;
+use tokio::task::JoinHandle;
+
+/// Where the buckets are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// In this process; each instance limits on its own
+    Memory,
+    /// In SurrealDB, shared by every instance using the database
+    Surreal,
+}
+
+/// A quota for requests with `method`, or any when unset, to `path`. The
+/// path is a route template such as `/api/users/:id`, or a prefix ending in
+/// `*`. Requests matching a rule without a quota are not limited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub method: Option<Method>,
+    pub path: String,
+    pub quota: Option<Quota>,
+}
+
+impl Rule {
+    /// Parses `[METHOD ]path=quota`, e.g. `POST /api/users=10/min`, where the
+    /// quota is `off` or as `Quota::parse` takes it.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (route, quota) = value.trim().rsplit_once('=')?;
+        let quota = match quota.trim() {
+            "off" => None,
+            quota => Some(Quota::parse(quota)?),
+        };
+        let (method, path) = match route.trim().split_once(' ') {
+            Some((method, path)) => (
+                Some(method.trim().to_uppercase().parse().ok()?),
+                path.trim(),
+            ),
+            None => (None, route.trim()),
+        };
+        if !path.starts_with('/') {
+            return None;
+        }
+        Some(Rule {
+            method,
+            path: path.to_string(),
+            quota,
+        })
+    }
+
+    fn matches(&self, method: &Method, route: &str) -> bool {
+        let path = match self.path.strip_suffix('*') {
+            Some(prefix) => route.starts_with(prefix),
+            None => route == self.path,
+        };
+        path && self.method.as_ref().is_none_or(|m| m == method)
+    }
+
+    /// Names the buckets of requests under this rule.
+    fn name(&self) -> String {
+        match &self.method {
+            Some(method) => format!("{} {}", method, self.path),
+            None => format!("* {}", self.path),
+        }
+    }
+}
+
+/// Rate limit settings loaded from the environment.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Applies to requests no rule matches
+    pub default: Quota,
+    /// Checked in order; the first that matches applies
+    pub rules: Vec<Rule>,
+    pub backend: Backend,
+    /// Count requests against their `X-Api-Key` rather than their address.
+    /// Off by default: the API doesn't verify keys yet, so a client could
+    /// send a new one with every request.
+    pub by_api_key: bool,
+    /// Take the client address from the last `X-Forwarded-For` entry, for
+    /// deployments behind a single proxy that appends it
+    pub trust_forwarded: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            default: Quota {
+                burst: 300,
+                period: Duration::from_secs(60),
+            },
+            rules: DEFAULT_RULES.split(';').filter_map(Rule::parse).collect(),
+            backend: Backend::Memory,
+            by_api_key: false,
+            trust_forwarded: false,
+        }
+    }
+}
+
+const DEFAULT_RULES: &str = "POST /api/users=10/min";
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let defaults = RateLimitConfig::default();
+        let flag = |name: &str, default: bool| {
+            env::var(name)
+                .map(|value| value.trim() == "true")
+                .unwrap_or(default)
+        };
+        RateLimitConfig {
+            enabled: env::var("RATE_LIMIT_ENABLED")
+                .map(|value| value.trim() != "false")
+                .unwrap_or(defaults.enabled),
+            default: env::var("RATE_LIMIT_DEFAULT")
+                .ok()
+                .and_then(|quota| {
+                    let parsed = Quota::parse(&quota);
+                    if parsed.is_none() {
+                        tracing::warn!(quota, "Ignored invalid RATE_LIMIT_DEFAULT");
+                    }
+                    parsed
+                })
+                .unwrap_or(defaults.default),
+            rules: match env::var("RATE_LIMIT_ROUTES") {
+                Ok(rules) => rules
+                    .split(';')
+                    .filter(|rule| !rule.trim().is_empty())
+                    .filter_map(|rule| {
+                        let parsed = Rule::parse(rule);
+                        if parsed.is_none() {
+                            tracing::warn!(rule = rule.trim(), "Skipped invalid rate limit rule");
+                        }
+                        parsed
+                    })
+                    .collect(),
+                Err(_) => defaults.rules,
+            },
+            backend: match env::var("RATE_LIMIT_STORE").as_deref().map(str::trim) {
+                Ok("surrealdb") => Backend::Surreal,
+                _ => Backend::Memory,
+            },
+            by_api_key: flag("RATE_LIMIT_BY_API_KEY", defaults.by_api_key),
+            trust_forwarded: flag("RATE_LIMIT_TRUST_FORWARDED", defaults.trust_forwarded),
+        }
+    }
+
+    /// The bucket name and quota for a request to `route`, or `None` when it
+    /// is not limited.
+    pub fn quota_for(&self, method: &Method, route: &str) -> Option<(String, Quota)> {
+        if !self.enabled {
+            return None;
+        }
+        match self.rules.iter().find(|rule| rule.matches(method, route)) {
+            Some(rule) => rule.quota.map(|quota| (rule.name(), quota)),
+            None => Some(("default".to_string(), self.default)),
+        }
+    }
+}
+
+/// Buckets held in this process, for a single instance.
+#[derive(Debug, Default)]
+pub struct MemoryRateLimits {
+    buckets: Mutex<HashMap<String, (Bucket, Quota)>>,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimits {
+    async fn take(&self, key: String, quota: Quota, now: DateTime<Local>) -> Result<Take, Error> {
+        let now = now.timestamp_millis();
+        let mut buckets = self.buckets.lock().unwrap();
+        let (bucket, _) = buckets
+            .entry(key)
+            .or_insert_with(|| (Bucket::full(quota, now), quota));
+        Ok(bucket.take(quota, now))
+    }
+
+    async fn prune(&self, now: DateTime<Local>) -> Result<usize, Error> {
+        let now = now.timestamp_millis();
+        let mut buckets = self.buckets.lock().unwrap();
+        let before = buckets.len();
+        buckets.retain(|_, (bucket, quota)| {
+            let elapsed = (now - bucket.updated_ms).max(0) as f64;
+            bucket.tokens + elapsed * quota.rate() < f64::from(quota.burst)
+        });
+        Ok(before - buckets.len())
+    }
+}
+
+/// Drops refilled buckets every `interval` until the runtime shuts down.
+pub fn spawn(store: Arc<dyn RateLimitStore>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = store.prune(Local::now()).await {
+                tracing::error!(error = %e, "Failed to prune rate limit buckets");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let rule = Rule::parse("POST /api/users=10/min").unwrap();
+        assert_eq!(rule.method, Some(Method::POST));
+        assert_eq!(rule.path, "/api/users");
+        assert_eq!(rule.quota, Quota::parse("10/min"));
+        let rule = Rule::parse(" /api/healthchecker = off ").unwrap();
+        assert_eq!(rule.method, None);
+        assert_eq!(rule.quota, None);
+        assert_eq!(Rule::parse("POST api/users=10/min"), None);
+        assert_eq!(Rule::parse("POST /api/users"), None);
+        assert_eq!(Rule::parse("POST /api/users=lots"), None);
+    }
+
+    #[test]
+    fn first_matching_rule_applies_before_the_default() {
+        let config = RateLimitConfig {
+            rules: vec![
+                Rule::parse("POST /api/users=10/min").unwrap(),
+                Rule::parse("/api/healthchecker=off").unwrap(),
+                Rule::parse("GET /api/todos/*=60/min").unwrap(),
+            ],
+            ..RateLimitConfig::default()
+        };
+        let (name, quota) = config.quota_for(&Method::POST, "/api/users").unwrap();
+        assert_eq!((name.as_str(), quota.burst), ("POST /api/users", 10));
+        let (name, _) = config.quota_for(&Method::GET, "/api/users").unwrap();
+        assert_eq!(name, "default");
+        assert_eq!(config.quota_for(&Method::GET, "/api/healthchecker"), None);
+        let (name, _) = config.quota_for(&Method::GET, "/api/todos/:id").unwrap();
+        assert_eq!(name, "GET /api/todos/*");
+
+        let disabled = RateLimitConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(disabled.quota_for(&Method::POST, "/api/users"), None);
+    }
+}
@@ -0,0 +1,36 @@
+use crate::data::stores::TodoStore;
+use crate::events::{Event, EventBus};
+use chrono::Local;
+use std::sync::Arc;
+use std::time::Duration;
+use surrealdb::Error;
+use tokio::task::JoinHandle;
+
+/// Fires every reminder that has come due and publishes an event for each,
+/// returning how many fired.
+pub async fn fire_due(todos: &dyn TodoStore, events: &EventBus) -> Result<usize, Error> {
+    let fired = todos.fire_due_reminders(Local::now()).await?;
+    let count = fired.len();
+    for todo in fired {
+        tracing::info!(
+            todo_id = ?todo.id.as_ref().map(|id| id.id.to_raw()),
+            title = %todo.title,
+            "Reminder fired"
+        );
+        events.publish(Event::ReminderFired { todo });
+    }
+    Ok(count)
+}
+
+/// Checks for due reminders every `interval` until the runtime shuts down.
+pub fn spawn(todos: Arc<dyn TodoStore>, events: EventBus, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = fire_due(todos.as_ref(), &events).await {
+                tracing::error!(error = %e, "Failed to fire reminders");
+            }
+        }
+    })
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Local, TimeDelta};
+use std::env;
+use std::time::Duration;
+
+/// Longest wait read from the environment, keeping the deadlines computed
+/// from it well within what a date can hold.
+const LONGEST: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// How failed background work, such as webhook deliveries and outbox
+/// events, is retried.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Attempts before giving up
+    pub max_attempts: usize,
+    /// Wait after the first failure, doubling with each further one
+    pub backoff: Duration,
+    /// Longest wait between attempts
+    pub max_backoff: Duration,
+    /// How long a single attempt may take
+    pub timeout: Duration,
+}
+
+impl RetryPolicy {
+    /// Reads `{prefix}_MAX_ATTEMPTS`, `{prefix}_BACKOFF_SECS`,
+    /// `{prefix}_MAX_BACKOFF_SECS` and `{prefix}_TIMEOUT_SECS`, keeping the
+    /// value from `defaults` for any that is unset. Waits are capped at a year.
+    pub fn from_env(prefix: &str, defaults: RetryPolicy) -> Self {
+        let var = |name: &str| {
+            env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        let wait = |name: &str, default: Duration| {
+            var(name).map_or(default, |secs| Duration::from_secs(secs).min(LONGEST))
+        };
+        RetryPolicy {
+            max_attempts: var("MAX_ATTEMPTS").map_or(defaults.max_attempts, |n| n as usize),
+            backoff: wait("BACKOFF_SECS", defaults.backoff),
+            max_backoff: wait("MAX_BACKOFF_SECS", defaults.max_backoff),
+            timeout: wait("TIMEOUT_SECS", defaults.timeout),
+        }
+    }
+
+    /// The wait before the next attempt, after `failures` failed ones.
+    pub fn delay(&self, failures: usize) -> Duration {
+        let doublings = failures.saturating_sub(1).min(31) as u32;
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(doublings))
+            .min(self.max_backoff)
+    }
+
+    /// When a claim made at `now` on work that takes up to `attempts`
+    /// timeouts lapses, or None if that is past what a date can hold.
+    pub fn lease(&self, now: DateTime<Local>, attempts: usize) -> Option<DateTime<Local>> {
+        let timeout = self.timeout.checked_mul(u32::try_from(attempts).ok()?)?;
+        now.checked_add_signed(TimeDelta::from_std(timeout).ok()?)
+    }
+
+    /// When to try again after `failures` failed attempts, or None once they
+    /// are used up or the wait is past what a date can hold.
+    pub fn retry_at(&self, now: DateTime<Local>, failures: usize) -> Option<DateTime<Local>> {
+        if failures >= self.max_attempts {
+            return None;
+        }
+        now.checked_add_signed(TimeDelta::from_std(self.delay(failures)).ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 8,
+            backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(300),
+            timeout: Duration::from_secs(10),
+        };
+        let delays = (1..=6).map(|failures| policy.delay(failures).as_secs());
+        assert_eq!(delays.collect::<Vec<_>>(), [30, 60, 120, 240, 300, 300]);
+    }
+
+    #[test]
+    fn deadlines_past_what_a_date_holds_are_none() {
+        let policy = RetryPolicy {
+            max_attempts: 8,
+            backoff: Duration::MAX,
+            max_backoff: Duration::MAX,
+            timeout: Duration::MAX,
+        };
+        let now = Local::now();
+        assert_eq!(policy.lease(now, 2), None);
+        assert_eq!(policy.retry_at(now, 1), None);
+
+        let policy = RetryPolicy {
+            backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            ..policy
+        };
+        assert_eq!(policy.lease(now, 3), Some(now + TimeDelta::seconds(30)));
+        assert_eq!(policy.retry_at(now, 1), Some(now + TimeDelta::seconds(30)));
+        assert_eq!(policy.retry_at(now, 8), None);
+    }
+}
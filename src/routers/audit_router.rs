@@ -0,0 +1,79 @@
+pub mod audit_router {
+    use crate::data::models::audit::{AuditFilter, AuditParams, MAX_AUDIT_PAGE};
+    use crate::routers::gate;
+    use crate::routers::openapi::{AuditListResponse, ErrorResponse};
+    use crate::routers::search::page_start;
+    use crate::state::AppState;
+    use axum::extract::{Query, State};
+    use axum::http::StatusCode;
+    use axum::{response::IntoResponse, routing::get, Json, Router};
+
+    pub fn router() -> Router<AppState> {
+        Router::new().route("/", get(get_audit_log))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/audit",
+        tag = "audit",
+        params(AuditParams),
+        responses(
+            (status = 200, description = "Audit entries, newest first", body = AuditListResponse),
+            (status = 400, description = "Invalid page", body = ErrorResponse),
+            (status = 404, description = "AUDIT_API_ENABLED is off", body = ErrorResponse),
+            (status = 500, description = "Failed to read the audit log", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_audit_log(
+        State(state): State<AppState>,
+        Query(params): Query<AuditParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.audit_api)?;
+        if params.page == 0 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Query parameter page starts at 1"
+                })),
+            ));
+        }
+        let per_page = params.per_page.clamp(1, MAX_AUDIT_PAGE);
+        let Some(start) = page_start(params.page, per_page) else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Query parameter page is too large"
+                })),
+            ));
+        };
+        let filter = AuditFilter {
+            resource: params
+                .resource
+                .map(|resource| resource.trim().to_string())
+                .filter(|resource| !resource.is_empty()),
+            action: params.action,
+            request_id: params.request_id,
+        };
+
+        let repository = state.data.audit();
+        match repository.list(filter, start, per_page).await {
+            Ok(page) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "page": params.page,
+                "per_page": per_page,
+                "total": page.total,
+                "count": page.entries.len(),
+                "entries": page.entries,
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to read the audit log"
+                })),
+            )),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::data::models::bulk::BulkMode;
+use crate::data::stores::BulkOutcome;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use validator::{Validate, ValidationErrors};
+
+/// Largest number of items accepted by one bulk request.
+pub const MAX_BULK_ITEMS: usize = 1000;
+
+/// Why an item was rejected before reaching the database.
+#[derive(Debug)]
+pub struct ItemError {
+    pub(super) status: StatusCode,
+    pub(super) message: String,
+    pub(super) errors: Option<ValidationErrors>,
+}
+
+impl ItemError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        ItemError {
+            status,
+            message: message.into(),
+            errors: None,
+        }
+    }
+
+    pub fn invalid(errors: ValidationErrors) -> Self {
+        ItemError {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: "Validation failed".to_string(),
+            errors: Some(errors),
+        }
+    }
+}
+
+/// A single-item handler answers with the error as it would for the item.
+impl From<ItemError> for (StatusCode, Json<serde_json::Value>) {
+    fn from(e: ItemError) -> Self {
+        let mut body = serde_json::json!({ "status": "error", "message": e.message });
+        if let Some(errors) = e.errors {
+            body["errors"] = serde_json::json!(errors);
+        }
+        (e.status, Json(body))
+    }
+}
+
+/// Names used when rendering the results of a bulk request.
+pub struct BulkResource {
+    /// Field holding each successful record, e.g. `todo`
+    pub key: &'static str,
+    /// Capitalised record name, e.g. `Todo`
+    pub name: &'static str,
+    /// Verb for failure messages, e.g. `create`
+    pub action: &'static str,
+    /// Per-item status of a successful item
+    pub success: StatusCode,
+}
+
+/// Rejects empty and oversized batches outright.
+pub fn check_size<T>(items: &[T]) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if items.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Bulk request must contain at least one item"
+            })),
+        ));
+    }
+    if items.len() > MAX_BULK_ITEMS {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Bulk request exceeds {} items", MAX_BULK_ITEMS)
+            })),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate<T: Validate>(item: &T) -> Result<(), ItemError> {
+    item.validate().map_err(ItemError::invalid)
+}
+
+/// Flags every repeat of an id already seen earlier in the batch.
+pub fn reject_duplicate_ids<'a>(ids: impl Iterator<Item = &'a str>) -> Vec<Result<(), ItemError>> {
+    let mut seen = HashSet::new();
+    ids.map(|id| {
+        if seen.insert(id) {
+            Ok(())
+        } else {
+            Err(ItemError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate ID: {} in request", id),
+            ))
+        }
+    })
+    .collect()
+}
+
+/// Sends the items that passed validation to the store and slots the outcomes
+/// back into request order. An atomic batch with a rejected item sends nothing.
+pub async fn execute<I, T, F, Fut>(
+    mode: BulkMode,
+    checked: Vec<Result<I, ItemError>>,
+    store: F,
+) -> Result<Vec<Result<BulkOutcome<T>, ItemError>>, surrealdb::Error>
+where
+    F: FnOnce(Vec<I>, bool) -> Fut,
+    Fut: Future<Output = Result<Vec<BulkOutcome<T>>, surrealdb::Error>>,
+{
+    let atomic = mode == BulkMode::Atomic;
+    let rejected = checked.iter().any(Result::is_err);
+
+    let mut valid = Vec::new();
+    let mut slots = Vec::with_capacity(checked.len());
+    for item in checked {
+        match item {
+            Ok(item) => {
+                valid.push(item);
+                slots.push(None);
+            }
+            Err(e) => slots.push(Some(e)),
+        }
+    }
+
+    let mut outcomes = if valid.is_empty() || (atomic && rejected) {
+        Vec::new()
+    } else {
+        store(valid, atomic).await?
+    }
+    .into_iter();
+
+    Ok(slots
+        .into_iter()
+        .map(|slot| match slot {
+            Some(e) => Err(e),
+            None => Ok(outcomes.next().unwrap_or(BulkOutcome::Aborted)),
+        })
+        .collect())
+}
+
+/// Renders per-item results and picks the overall status: the resource's
+/// success status when every item succeeded, `207 Multi-Status` for a partial
+/// batch with failures, and the first real failure's status for an atomic one.
+pub fn respond<T: Serialize>(
+    resource: &BulkResource,
+    mode: BulkMode,
+    results: Vec<Result<BulkOutcome<T>, ItemError>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let mut succeeded = 0;
+    let mut first_failure = None;
+    let items = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let (status, body) = match result {
+                Ok(BulkOutcome::Done(record)) => {
+                    succeeded += 1;
+                    (
+                        resource.success,
+                        serde_json::json!({ resource.key: record }),
+                    )
+                }
+                Ok(BulkOutcome::NotFound) => (
+                    StatusCode::NOT_FOUND,
+                    serde_json::json!({ "message": format!("{} not found", resource.name) }),
+                ),
+                Ok(BulkOutcome::Aborted) => (
+                    StatusCode::FAILED_DEPENDENCY,
+                    serde_json::json!({ "message": "Not applied because another item failed" }),
+                ),
+                Ok(BulkOutcome::Failed(_)) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    serde_json::json!({
+                        "message": format!("Failed to {} {}", resource.action, resource.key)
+                    }),
+                ),
+                Err(e) => (
+                    e.status,
+                    serde_json::json!({ "message": e.message, "errors": e.errors }),
+                ),
+            };
+            if !status.is_success() && status != StatusCode::FAILED_DEPENDENCY {
+                first_failure.get_or_insert(status);
+            }
+
+            let mut item = serde_json::json!({ "index": index, "status": status.as_u16() });
+            if let (Some(item), Some(body)) = (item.as_object_mut(), body.as_object()) {
+                item.extend(
+                    body.iter()
+                        .filter(|(_, v)| !v.is_null())
+                        .map(|(k, v)| (k.clone(), v.clone())),
+                );
+            }
+            item
+        })
+        .collect::<Vec<_>>();
+
+    let failed = items.len() - succeeded;
+    let (status, label) = match (mode, failed) {
+        (_, 0) => (resource.success, "success"),
+        (BulkMode::Partial, _) if succeeded > 0 => (StatusCode::MULTI_STATUS, "partial"),
+        (BulkMode::Partial, _) => (StatusCode::MULTI_STATUS, "error"),
+        (BulkMode::Atomic, _) => (
+            first_failure.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            "error",
+        ),
+    };
+
+    let mut body = serde_json::json!({
+        "status": label,
+        "mode": mode,
+        "succeeded": succeeded,
+        "failed": failed,
+        "results": items,
+    });
+    if mode == BulkMode::Atomic && failed > 0 {
+        body["message"] = serde_json::json!("No changes were applied");
+    }
+    (status, Json(body))
+}
+
+/// Response for a batch the store could not run at all.
+pub fn store_error(resource: &BulkResource) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({
+            "status": "error",
+            "message": format!("Failed to {} {}s", resource.action, resource.key)
+        })),
+    )
+}
@@ -0,0 +1,16 @@
+use axum::http::StatusCode;
+use axum::Json;
+
+/// Answers as if the route did not exist unless its config flag is on.
+pub fn require(enabled: bool) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if enabled {
+        return Ok(());
+    }
+    Err((
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "status": "error",
+            "message": "Not found"
+        })),
+    ))
+}
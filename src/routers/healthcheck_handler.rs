@@ -1,4 +1,14 @@
+use crate::routers::openapi::MessageResponse;
 use axum::{response::IntoResponse, Json};
+
+#[utoipa::path(
+    get,
+    path = "/api/healthcheck",
+    tag = "healthcheck",
+    responses(
+        (status = 200, description = "Service is up", body = MessageResponse),
+    )
+)]
 pub async fn healthcheck_handler() -> impl IntoResponse {
     const MESSAGE: &str = "Working fine, thanks!";
 
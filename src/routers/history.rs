@@ -0,0 +1,27 @@
+use crate::data::models::history::Version;
+use axum::Json;
+use serde::Serialize;
+
+/// One version, with the record under `key`.
+pub fn entry<T: Serialize>(key: &str, version: Version<T>) -> serde_json::Value {
+    serde_json::json!({
+        "version": version.version,
+        "at": version.at,
+        "action": version.action,
+        key: version.record,
+    })
+}
+
+/// Renders a record's versions, newest first, with each record under `key`.
+pub fn respond<T: Serialize>(key: &str, versions: Vec<Version<T>>) -> Json<serde_json::Value> {
+    let versions = versions
+        .into_iter()
+        .map(|version| entry(key, version))
+        .collect::<Vec<_>>();
+
+    Json(serde_json::json!({
+        "status": "success",
+        "count": versions.len(),
+        "versions": versions,
+    }))
+}
@@ -0,0 +1,415 @@
+pub mod lists_router {
+    use crate::data::ical;
+    use crate::data::models::bulk::BulkParams;
+    use crate::data::models::list::{
+        CreateList, DeleteListParams, ListDeletion, TodoList, UpdateList,
+    };
+    use crate::data::models::stream::StreamParams;
+    use crate::data::models::todo::{TodoFilter, TodoPatch};
+    use crate::routers::bulk::{self, BulkResource};
+    use crate::routers::openapi::{
+        BulkResponse, ErrorResponse, ListResponse, ListStatsResponse, ListsResponse,
+        TodoListResponse,
+    };
+    use crate::routers::streaming;
+    use crate::routers::todo_query::TodoQuery;
+    use crate::state::AppState;
+    use axum::extract::{Path, Query, State};
+    use axum::http::{header, StatusCode};
+    use axum::{
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use chrono::Local;
+    use validator::Validate;
+
+    const MOVE_TODOS: BulkResource = BulkResource {
+        key: "todo",
+        name: "Todo",
+        action: "move",
+        success: StatusCode::OK,
+    };
+
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", post(create_list).get(get_all_lists))
+            .route(
+                "/:id",
+                get(get_list_by_id).put(update_list).delete(delete_list),
+            )
+            .route("/:id/todos", get(get_list_todos).post(move_todos))
+            .route("/:id/todos.ics", get(get_list_calendar))
+            .route("/:id/stats", get(get_list_stats))
+    }
+
+    fn list_not_found(id: &str) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("List with ID: {} not found", id)
+            })),
+        )
+    }
+
+    fn invalid(errors: validator::ValidationErrors) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Validation failed",
+                "errors": errors
+            })),
+        )
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/lists",
+        tag = "lists",
+        params(StreamParams),
+        responses(
+            (status = 200, description = "List all todo lists, or stream them", content(
+                (ListsResponse = "application/json"),
+                (TodoList = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn get_all_lists(
+        State(state): State<AppState>,
+        Query(params): Query<StreamParams>,
+    ) -> Response {
+        let repository = state.data.lists();
+        if let Some(format) = params.stream {
+            return streaming::respond(
+                format,
+                move |after, limit| {
+                    let repository = repository.clone();
+                    async move { repository.page(after, limit).await }
+                },
+                |list: &TodoList| list.id.as_ref().map(|id| id.id.to_raw()),
+            );
+        }
+
+        let lists = repository.get_all().await.unwrap_or_default();
+        Json(serde_json::json!({
+            "status": "success",
+            "count": lists.len(),
+            "lists": lists
+        }))
+        .into_response()
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/lists/{id}",
+        tag = "lists",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "List found", body = TodoList),
+            (status = 404, description = "List not found", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_list_by_id(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.lists();
+        match repository.get_by_id(id.clone()).await {
+            Ok(list) => Ok((StatusCode::OK, Json(list))),
+            Err(_) => Err(list_not_found(&id)),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/lists/{id}/todos",
+        tag = "lists",
+        params(
+            ("id" = String, Path, description = "Record id"),
+            TodoFilter,
+            ("tag" = Option<Vec<String>>, Query, description = "Only todos with this tag; repeat for several"),
+        ),
+        responses(
+            (status = 200, description = "Todos in the list, optionally filtered and sorted", body = TodoListResponse),
+            (status = 404, description = "List not found", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_list_todos(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        TodoQuery(mut filter): TodoQuery,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        if state.data.lists().get_by_id(id.clone()).await.is_err() {
+            return Err(list_not_found(&id));
+        }
+
+        filter.list_id = Some(id);
+        let todos = state.data.todos().list(filter).await.unwrap_or_default();
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "count": todos.len(),
+            "todos": todos,
+        })))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/lists/{id}/todos.ics",
+        tag = "lists",
+        params(
+            ("id" = String, Path, description = "Record id"),
+            TodoFilter,
+            ("tag" = Option<Vec<String>>, Query, description = "Only todos with this tag; repeat for several"),
+        ),
+        responses(
+            (status = 200, description = "The list's todos as iCalendar VTODO components", body = String, content_type = "text/calendar"),
+            (status = 404, description = "List not found", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_list_calendar(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        TodoQuery(mut filter): TodoQuery,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        if state.data.lists().get_by_id(id.clone()).await.is_err() {
+            return Err(list_not_found(&id));
+        }
+
+        filter.list_id = Some(id);
+        let todos = state.data.todos().list(filter).await.unwrap_or_default();
+        Ok((
+            [(header::CONTENT_TYPE, ical::CONTENT_TYPE)],
+            ical::render(&todos, Local::now()),
+        ))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/lists/{id}/stats",
+        tag = "lists",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Completion counts for the list", body = ListStatsResponse),
+            (status = 404, description = "List not found", body = ErrorResponse),
+            (status = 500, description = "Failed to count todos", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_list_stats(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        if state.data.lists().get_by_id(id.clone()).await.is_err() {
+            return Err(list_not_found(&id));
+        }
+
+        match state.data.todos().list_stats(id).await {
+            Ok(stats) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "stats": stats
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to count todos"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/lists",
+        tag = "lists",
+        request_body = CreateList,
+        responses(
+            (status = 201, description = "List created", body = ListResponse),
+            (status = 422, description = "Invalid list", body = ErrorResponse),
+            (status = 500, description = "Failed to create list", body = ErrorResponse),
+        )
+    )]
+    pub async fn create_list(
+        State(state): State<AppState>,
+        Json(body): Json<CreateList>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        body.validate().map_err(invalid)?;
+        let repository = state.data.lists();
+
+        let list = TodoList {
+            id: None,
+            name: body.name,
+            description: body.description,
+            created_at: Some(Local::now()),
+            updated_at: None,
+        };
+        match repository.create(list).await {
+            Ok(list) => Ok((
+                StatusCode::CREATED,
+                Json(serde_json::json!({
+                    "status": "success",
+                    "list": list
+                })),
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to create list"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        put,
+        path = "/api/lists/{id}",
+        tag = "lists",
+        params(("id" = String, Path, description = "Record id")),
+        request_body = UpdateList,
+        responses(
+            (status = 200, description = "List updated", body = ListResponse),
+            (status = 404, description = "List not found", body = ErrorResponse),
+            (status = 422, description = "Invalid list", body = ErrorResponse),
+            (status = 500, description = "Failed to update list", body = ErrorResponse),
+        )
+    )]
+    pub async fn update_list(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Json(body): Json<UpdateList>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        body.validate().map_err(invalid)?;
+        let repository = state.data.lists();
+
+        let Ok(mut list) = repository.get_by_id(id.clone()).await else {
+            return Err(list_not_found(&id));
+        };
+        list.name = body.name.unwrap_or(list.name);
+        list.description = body.description.or(list.description);
+        list.updated_at = Some(Local::now());
+
+        match repository.update(id, list).await {
+            Ok(list) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "list": list
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to update list"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/lists/{id}",
+        tag = "lists",
+        params(("id" = String, Path, description = "Record id"), DeleteListParams),
+        responses(
+            (status = 204, description = "List deleted, along with its todos when cascading"),
+            (status = 404, description = "List not found", body = ErrorResponse),
+            (status = 409, description = "List still has todos", body = ErrorResponse),
+            (status = 500, description = "Failed to delete list", body = ErrorResponse),
+        )
+    )]
+    pub async fn delete_list(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Query(params): Query<DeleteListParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.lists();
+        if repository.get_by_id(id.clone()).await.is_err() {
+            return Err(list_not_found(&id));
+        }
+
+        let deletion = repository
+            .delete_with_todos(id.clone(), params.on_delete)
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": "Failed to delete list"
+                    })),
+                )
+            })?;
+        if let ListDeletion::Restricted(total) = deletion {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!(
+                        "List with ID: {} still has {} todos; move them or delete with on_delete=cascade",
+                        id, total
+                    )
+                })),
+            ));
+        }
+        Ok((
+            StatusCode::NO_CONTENT,
+            Json(serde_json::json!({
+                "status": "success",
+                "message": "List deleted successfully"
+            })),
+        ))
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/lists/{id}/todos",
+        tag = "lists",
+        params(("id" = String, Path, description = "Record id of the destination list"), BulkParams),
+        request_body(content = Vec<String>, description = "Record ids of the todos to move"),
+        responses(
+            (status = 200, description = "Every todo moved", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch or repeated id", body = ErrorResponse),
+            (status = 404, description = "List not found, or a todo was missing and nothing was moved", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+        )
+    )]
+    pub async fn move_todos(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<String>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        if state.data.lists().get_by_id(id.clone()).await.is_err() {
+            return Err(list_not_found(&id));
+        }
+        let repository = state.data.todos();
+
+        let datetime = Local::now();
+        let unique = bulk::reject_duplicate_ids(body.iter().map(String::as_str));
+        let checked = body
+            .into_iter()
+            .zip(unique)
+            .map(|(todo_id, unique)| {
+                unique.map(|_| {
+                    let patch = TodoPatch {
+                        list: Some(TodoList::link(&id)),
+                        updated_at: Some(datetime),
+                        ..TodoPatch::default()
+                    };
+                    (todo_id, patch)
+                })
+            })
+            .collect();
+
+        match bulk::execute(params.mode, checked, |patches, atomic| {
+            repository.merge_many(patches, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&MOVE_TODOS, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&MOVE_TODOS)),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::data::models::live::SocketParams;
+use crate::live::{FeedItem, Resource};
+use crate::routers::openapi::ErrorResponse;
+use crate::state::AppState;
+use axum::extract::ws::rejection::WebSocketUpgradeRejection;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures::stream::{Stream, StreamExt};
+use std::time::Duration;
+
+/// The change id to resume after: the `Last-Event-ID` header an
+/// `EventSource` sends when it reconnects, else `?last_event_id=`.
+pub fn resume_from(
+    headers: &HeaderMap,
+    last_event_id: Option<u64>,
+) -> Result<Option<u64>, (StatusCode, Json<serde_json::Value>)> {
+    match headers.get("last-event-id") {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|id| id.trim().parse().ok())
+            .map(Some)
+            .ok_or_else(|| bad_request("Last-Event-ID must be a change id")),
+        None => Ok(last_event_id),
+    }
+}
+
+/// Server-Sent Events for every change to `resource`. Each event is named
+/// after its action and carries the change as JSON, with the change id as
+/// the event id.
+pub fn sse(state: &AppState, resource: Resource, after: Option<u64>) -> Response {
+    let events = state
+        .changes
+        .subscribe(after, move |change| change.resource == resource)
+        .map(|item| match item {
+            FeedItem::Change(change) => Event::default()
+                .id(change.id.to_string())
+                .event(change.action.as_str())
+                .json_data(&change),
+            FeedItem::Reset { latest } => Event::default()
+                .id(latest.to_string())
+                .event("reset")
+                .json_data(serde_json::json!({ "latest": latest })),
+        });
+    Sse::new(events)
+        .keep_alive(
+            KeepAlive::new()
+                .interval(state.config.live_heartbeat)
+                .text("heartbeat"),
+        )
+        .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/live",
+    tag = "live",
+    params(SocketParams),
+    responses(
+        (status = 101, description = "WebSocket opened; each text frame is a FeedItem", body = FeedItem),
+        (status = 400, description = "Bad handshake, resume id or resource", body = ErrorResponse),
+        (status = 426, description = "Not a WebSocket upgrade request", body = ErrorResponse),
+    )
+)]
+pub async fn live_socket(
+    State(state): State<AppState>,
+    Query(params): Query<SocketParams>,
+    headers: HeaderMap,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let upgrade = upgrade.map_err(|rejection| {
+        let status = match rejection {
+            // Not asking to upgrade at all, rather than a bad handshake
+            WebSocketUpgradeRejection::InvalidConnectionHeader(_)
+            | WebSocketUpgradeRejection::InvalidUpgradeHeader(_) => StatusCode::UPGRADE_REQUIRED,
+            _ => rejection.status(),
+        };
+        (
+            status,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": rejection.body_text()
+            })),
+        )
+    })?;
+    let after = resume_from(&headers, params.last_event_id)?;
+    // User changes carry contact details, so are only sent when enabled
+    let served = |resource: &Resource| *resource != Resource::User || state.config.user_live_api;
+    let resources = match params.resources.as_deref() {
+        Some(names) => names
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| {
+                Resource::parse(name)
+                    .filter(served)
+                    .ok_or_else(|| bad_request(&format!("Unknown resource: {}", name.trim())))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Resource::ALL.into_iter().filter(served).collect(),
+    };
+
+    let items = state
+        .changes
+        .subscribe(after, move |change| resources.contains(&change.resource));
+    let heartbeat = state.config.live_heartbeat;
+    Ok(upgrade
+        .on_failed_upgrade(|e| tracing::warn!(error = %e, "WebSocket upgrade failed"))
+        .on_upgrade(move |socket| relay(socket, Box::pin(items), heartbeat)))
+}
+
+/// Sends each item as a JSON text frame and a ping every `heartbeat`, until
+/// either side closes.
+async fn relay(
+    mut socket: WebSocket,
+    mut items: impl Stream<Item = FeedItem> + Unpin,
+    heartbeat: Duration,
+) {
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + heartbeat, heartbeat);
+    loop {
+        let sent = tokio::select! {
+            item = items.next() => match item {
+                Some(item) => match serde_json::to_string(&item) {
+                    Ok(text) => socket.send(Message::Text(text)).await,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Skipped unserializable change");
+                        Ok(())
+                    }
+                },
+                None => break,
+            },
+            _ = ticker.tick() => socket.send(Message::Ping(Vec::new())).await,
+            message = socket.next() => match message {
+                // Pings are answered by the socket itself; clients only listen
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => Ok(()),
+            },
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+    let _ = socket.close().await;
+}
+
+fn bad_request(message: &str) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "status": "error", "message": message })),
+    )
+}
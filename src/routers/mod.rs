@@ -1,19 +1,47 @@
+#![allow(clippy::module_inception)]
+
+pub mod audit_router;
+pub mod bulk;
+pub mod gate;
 pub mod healthcheck_handler;
+pub mod history;
+pub mod lists_router;
+pub mod live;
+pub mod openapi;
 pub mod roles_router;
+pub mod search;
+pub mod streaming;
+pub mod tags_router;
+pub mod todo_query;
 pub mod todos_router;
+pub mod transfer;
 pub mod users_router;
+pub mod webhooks_router;
 
 pub mod api_router {
     use crate::routers::healthcheck_handler::healthcheck_handler;
-    use crate::routers::{roles_router::roles_router, todos_router::todos_router, users_router::users_router};
+    use crate::routers::live::live_socket;
+    use crate::routers::openapi;
+    use crate::routers::{
+        audit_router::audit_router, lists_router::lists_router, roles_router::roles_router, tags_router::tags_router,
+        todos_router::todos_router, users_router::users_router, webhooks_router::webhooks_router,
+    };
+    use crate::state::AppState;
     use axum::routing::get;
     use axum::Router;
 
-    pub fn api_router() -> Router {
+    pub fn api_router() -> Router<AppState> {
         Router::new()
             .route("/healthcheck", get(healthcheck_handler))
+            .route("/todos.ics", get(todos_router::get_todos_calendar))
+            .route("/live", get(live_socket))
             .nest("/todos", todos_router::router())
             .nest("/users", users_router::router())
             .nest("/roles", roles_router::router())
+            .nest("/lists", lists_router::router())
+            .nest("/tags", tags_router::router())
+            .nest("/audit", audit_router::router())
+            .nest("/webhooks", webhooks_router::router())
+            .merge(openapi::router())
     }
 }
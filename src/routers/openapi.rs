@@ -0,0 +1,489 @@
+use crate::data::models::audit::{AuditAction, AuditEntry, FieldChange};
+use crate::data::models::bulk::BulkMode;
+use crate::data::models::list::{CreateList, ListStats, OnDelete, TodoList, UpdateList};
+use crate::data::models::role::Role;
+use crate::data::models::stream::StreamFormat;
+use crate::data::models::tag::{AddTags, TagCount, TagMatch};
+use crate::data::models::todo::{
+    AddBlocker, BulkTodoPatch, CreateTodo, Priority, SortOrder, Todo, TodoSort, TodoWithBlockers,
+    UpdateTodo,
+};
+use crate::data::models::transfer::{RoleRow, TodoRow, TransferFormat, UserRow};
+use crate::data::models::user::{BulkUserPatch, CreateUser, UpdateUser, User};
+use crate::data::models::webhook::{
+    CreateWebhook, DeliveryAttempt, DeliveryStatus, UpdateWebhook, Webhook, WebhookDelivery,
+};
+use crate::live::{Change, ChangeAction, FeedItem, Resource};
+use crate::routers::healthcheck_handler;
+use crate::routers::{
+    audit_router::audit_router, lists_router::lists_router, live, roles_router::roles_router,
+    tags_router::tags_router, todos_router::todos_router, users_router::users_router,
+    webhooks_router::webhooks_router,
+};
+use crate::state::AppState;
+use axum::extract::Path;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::{routing::get, Json, Router};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::Config;
+
+/// Envelope returned by every failing endpoint.
+#[derive(ToSchema)]
+pub struct ErrorResponse {
+    #[schema(example = "error")]
+    pub status: String,
+    pub message: String,
+    /// Echo of the `X-Request-Id` header for correlating with server logs
+    pub request_id: Option<String>,
+}
+
+#[derive(ToSchema)]
+pub struct MessageResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub message: String,
+}
+
+#[derive(ToSchema)]
+pub struct TodoResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub todo: Todo,
+}
+
+#[derive(ToSchema)]
+pub struct UpdateTodoResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub todo: Todo,
+    /// The todo created for the next occurrence when this one repeats and was just completed
+    pub next_occurrence: Option<Todo>,
+}
+
+/// Upcoming due dates of a recurring todo's series.
+#[derive(ToSchema)]
+pub struct OccurrencesResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    /// The series' RRULE
+    #[schema(example = "FREQ=WEEKLY;BYDAY=MO")]
+    pub recurrence: String,
+    pub count: usize,
+    pub occurrences: Vec<DateTime<Local>>,
+}
+
+#[derive(ToSchema)]
+pub struct TodoListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub todos: Vec<Todo>,
+}
+
+#[derive(ToSchema)]
+pub struct UserResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub user: User,
+}
+
+#[derive(ToSchema)]
+pub struct UserListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub users: Vec<User>,
+}
+
+/// A search match; `highlights` maps each matched field to its text with the
+/// matched terms wrapped in `<mark>` tags.
+#[derive(ToSchema)]
+pub struct TodoSearchHit {
+    pub todo: Todo,
+    pub score: f64,
+    pub highlights: HashMap<String, String>,
+}
+
+#[derive(ToSchema)]
+pub struct TodoSearchResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub query: String,
+    pub page: usize,
+    pub per_page: usize,
+    /// Matches across all pages
+    pub total: usize,
+    pub count: usize,
+    pub results: Vec<TodoSearchHit>,
+}
+
+/// A search match; `highlights` maps each matched field to its text with the
+/// matched terms wrapped in `<mark>` tags.
+#[derive(ToSchema)]
+pub struct UserSearchHit {
+    pub user: User,
+    pub score: f64,
+    pub highlights: HashMap<String, String>,
+}
+
+#[derive(ToSchema)]
+pub struct UserSearchResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub query: String,
+    pub page: usize,
+    pub per_page: usize,
+    /// Matches across all pages
+    pub total: usize,
+    pub count: usize,
+    pub results: Vec<UserSearchHit>,
+}
+
+#[derive(ToSchema)]
+pub struct RoleResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub role: Role,
+}
+
+#[derive(ToSchema)]
+pub struct RoleListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub roles: Vec<Role>,
+}
+
+#[derive(ToSchema)]
+pub struct ListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub list: TodoList,
+}
+
+#[derive(ToSchema)]
+pub struct ListsResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub lists: Vec<TodoList>,
+}
+
+#[derive(ToSchema)]
+pub struct ListStatsResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub stats: ListStats,
+}
+
+#[derive(ToSchema)]
+pub struct AuditListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub page: usize,
+    pub per_page: usize,
+    /// Entries matching the filter across all pages
+    pub total: usize,
+    pub count: usize,
+    pub entries: Vec<AuditEntry>,
+}
+
+/// A todo as it was after one of its writes.
+#[derive(ToSchema)]
+pub struct TodoVersion {
+    /// Numbered from 1 per todo
+    pub version: u64,
+    pub at: DateTime<Local>,
+    pub action: AuditAction,
+    /// Unset for the version that deleted the todo
+    pub todo: Option<Todo>,
+}
+
+#[derive(ToSchema)]
+pub struct TodoHistoryResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    /// Newest first
+    pub versions: Vec<TodoVersion>,
+}
+
+/// A user as it was after one of its writes.
+#[derive(ToSchema)]
+pub struct UserVersion {
+    /// Numbered from 1 per user
+    pub version: u64,
+    pub at: DateTime<Local>,
+    pub action: AuditAction,
+    /// Unset for the version that deleted the user
+    pub user: Option<User>,
+}
+
+#[derive(ToSchema)]
+pub struct UserHistoryResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    /// Newest first
+    pub versions: Vec<UserVersion>,
+}
+
+#[derive(ToSchema)]
+pub struct WebhookResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub webhook: Webhook,
+}
+
+#[derive(ToSchema)]
+pub struct WebhookListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(ToSchema)]
+pub struct DeliveryResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub delivery: WebhookDelivery,
+}
+
+#[derive(ToSchema)]
+pub struct DeliveryListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub page: usize,
+    pub per_page: usize,
+    /// Deliveries matching the filter across all pages
+    pub total: usize,
+    pub count: usize,
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+#[derive(ToSchema)]
+pub struct TagListResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub count: usize,
+    pub tags: Vec<TagCount>,
+}
+
+/// Result of one item in a bulk request. Successful items also carry the
+/// record under `todo` or `user`.
+#[derive(ToSchema)]
+pub struct BulkItemResult {
+    /// Position of the item in the request body
+    pub index: usize,
+    #[schema(example = 201)]
+    pub status: u16,
+    pub message: Option<String>,
+    /// Field-level validation errors, for status 422
+    #[schema(value_type = Option<Object>)]
+    pub errors: Option<serde_json::Value>,
+}
+
+#[derive(ToSchema)]
+pub struct BulkResponse {
+    /// `success`, `partial` or `error`
+    #[schema(example = "success")]
+    pub status: String,
+    pub mode: BulkMode,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Set when an atomic batch was rolled back
+    pub message: Option<String>,
+    pub results: Vec<BulkItemResult>,
+}
+
+/// Result of one row of an import.
+#[derive(ToSchema)]
+pub struct ImportRowResult {
+    /// Position of the row in the file, from 1, not counting a CSV header
+    pub row: usize,
+    #[schema(example = 201)]
+    pub status: u16,
+    /// `create` or `update`, for rows that were or would be applied
+    pub action: Option<String>,
+    /// Id of the record written, or of the record a dry run would update
+    pub id: Option<String>,
+    pub message: Option<String>,
+    /// Field-level validation errors, for status 422
+    #[schema(value_type = Option<Object>)]
+    pub errors: Option<serde_json::Value>,
+}
+
+#[derive(ToSchema)]
+pub struct ImportResponse {
+    /// `success`, `partial` or `error`
+    #[schema(example = "success")]
+    pub status: String,
+    /// Whether the rows were only checked
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "RSS Boilerplate API"),
+    paths(
+        healthcheck_handler::healthcheck_handler,
+        todos_router::get_all_todos,
+        todos_router::get_todos_calendar,
+        todos_router::get_todo_by_id,
+        todos_router::get_todo_by_title,
+        todos_router::search_todos,
+        todos_router::create_todo,
+        todos_router::update_todo,
+        todos_router::add_tags,
+        todos_router::remove_tag,
+        todos_router::get_occurrences,
+        todos_router::stop_recurrence,
+        todos_router::get_subtasks,
+        todos_router::get_blockers,
+        todos_router::add_blocker,
+        todos_router::remove_blocker,
+        todos_router::get_todo_history,
+        todos_router::revert_todo,
+        todos_router::delete_todo,
+        todos_router::bulk_create_todos,
+        todos_router::export_todos,
+        todos_router::import_todos,
+        todos_router::live_todos,
+        todos_router::bulk_update_todos,
+        todos_router::bulk_delete_todos,
+        users_router::get_all_users,
+        users_router::get_user_by_id,
+        users_router::get_user_by_email,
+        users_router::get_user_by_phone,
+        users_router::search_users,
+        users_router::create_user,
+        users_router::update_user,
+        users_router::get_user_history,
+        users_router::delete_user,
+        users_router::bulk_create_users,
+        users_router::bulk_update_users,
+        users_router::bulk_delete_users,
+        users_router::export_users,
+        users_router::import_users,
+        users_router::live_users,
+        roles_router::get_all_roles,
+        roles_router::get_role_by_id,
+        roles_router::get_role_by_name,
+        roles_router::create_role,
+        roles_router::update_role,
+        roles_router::delete_role,
+        roles_router::export_roles,
+        roles_router::import_roles,
+        roles_router::live_roles,
+        lists_router::get_all_lists,
+        lists_router::get_list_by_id,
+        lists_router::get_list_todos,
+        lists_router::get_list_calendar,
+        lists_router::get_list_stats,
+        lists_router::create_list,
+        lists_router::update_list,
+        lists_router::delete_list,
+        lists_router::move_todos,
+        tags_router::get_all_tags,
+        audit_router::get_audit_log,
+        webhooks_router::get_all_webhooks,
+        webhooks_router::get_webhook_by_id,
+        webhooks_router::create_webhook,
+        webhooks_router::update_webhook,
+        webhooks_router::delete_webhook,
+        webhooks_router::get_webhook_deliveries,
+        webhooks_router::get_all_deliveries,
+        webhooks_router::retry_delivery,
+        live::live_socket,
+    ),
+    components(schemas(
+        Todo,
+        CreateTodo,
+        UpdateTodo,
+        BulkTodoPatch,
+        TodoWithBlockers,
+        AddBlocker,
+        AddTags,
+        TagCount,
+        TagMatch,
+        Priority,
+        TodoSort,
+        SortOrder,
+        User,
+        CreateUser,
+        UpdateUser,
+        BulkUserPatch,
+        Role,
+        TodoList,
+        CreateList,
+        UpdateList,
+        ListStats,
+        OnDelete,
+        ErrorResponse,
+        MessageResponse,
+        BulkMode,
+        BulkItemResult,
+        BulkResponse,
+        StreamFormat,
+        TransferFormat,
+        TodoRow,
+        UserRow,
+        RoleRow,
+        ImportRowResult,
+        ImportResponse,
+        Resource,
+        ChangeAction,
+        Change,
+        FeedItem,
+        AuditAction,
+        AuditEntry,
+        FieldChange,
+        TodoVersion,
+        UserVersion,
+        Webhook,
+        CreateWebhook,
+        UpdateWebhook,
+        WebhookDelivery,
+        DeliveryAttempt,
+        DeliveryStatus,
+    ))
+)]
+pub struct ApiDoc;
+
+/// Serves a file of the Swagger UI bundled into the binary, pointed at the
+/// generated document, so the page loads nothing from third parties.
+async fn docs(file: Option<Path<String>>) -> Response {
+    let file = file.as_ref().map_or("", |Path(file)| file.as_str());
+    let config = Arc::new(Config::from("/api/openapi.json"));
+    match utoipa_swagger_ui::serve(file, config) {
+        Ok(Some(file)) => (
+            [(header::CONTENT_TYPE, file.content_type)],
+            file.bytes.into_owned(),
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to serve Swagger UI");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Serves the generated document at `/openapi.json` and Swagger UI at `/docs/`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+        // The page loads its assets relative to the trailing slash
+        .route("/docs", get(|| async { Redirect::permanent("/api/docs/") }))
+        .route("/docs/", get(docs))
+        .route("/docs/*file", get(docs))
+}
@@ -1,19 +1,33 @@
 pub mod roles_router {
-    use crate::data::repositories::roles_repository::RolesRepository;
-    use crate::db::Database;
+    use crate::data::models::live::LiveParams;
     use crate::data::models::role::Role;
-    use axum::extract::Path;
-    use axum::http::StatusCode;
+    use crate::data::models::stream::StreamParams;
+    use crate::data::models::transfer::{ExportParams, ImportParams, RoleRow};
+    use crate::data::stores::RoleStore;
+    use crate::data::transfer::Transfer;
+    use crate::live::{Change, Resource};
+    use crate::routers::bulk::{self, ItemError};
+    use crate::routers::live;
+    use crate::routers::openapi::{ErrorResponse, ImportResponse, RoleListResponse, RoleResponse};
+    use crate::routers::streaming;
+    use crate::routers::transfer::{self, Planned};
+    use crate::state::AppState;
+    use axum::extract::{Path, Query, State};
+    use axum::http::{HeaderMap, StatusCode};
     use axum::{
-        response::IntoResponse,
+        response::{IntoResponse, Response},
         routing::{get, post},
-        Extension, Json, Router,
+        Json, Router,
     };
-    use std::sync::Arc;
+    use chrono::{DateTime, Local};
+    use std::collections::HashSet;
 
-    pub fn router() -> Router {
+    pub fn router() -> Router<AppState> {
         Router::new()
             .route("/", post(create_role).get(get_all_roles))
+            .route("/export", get(export_roles))
+            .route("/live", get(live_roles))
+            .route("/import", post(import_roles))
             .route(
                 "/:id",
                 get(get_role_by_id).put(update_role).delete(delete_role),
@@ -21,21 +35,58 @@ pub mod roles_router {
             .route("/name/:name", get(get_role_by_name))
     }
 
-    pub async fn get_all_roles(Extension(db): Extension<Arc<Database>>) -> impl IntoResponse {
-        let repository = RolesRepository::new(db);
+    #[utoipa::path(
+        get,
+        path = "/api/roles",
+        tag = "roles",
+        params(StreamParams),
+        responses(
+            (status = 200, description = "List all roles, or stream them", content(
+                (RoleListResponse = "application/json"),
+                (Role = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn get_all_roles(
+        State(state): State<AppState>,
+        Query(params): Query<StreamParams>,
+    ) -> Response {
+        let repository = state.data.roles();
+        if let Some(format) = params.stream {
+            return streaming::respond(
+                format,
+                move |after, limit| {
+                    let repository = repository.clone();
+                    async move { repository.page(after, limit).await }
+                },
+                Role::key,
+            );
+        }
+
         let roles = repository.get_all().await.unwrap_or_default();
         Json(serde_json::json!({
             "status": "success",
             "count": roles.len(),
             "roles": roles
         }))
+        .into_response()
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/roles/{id}",
+        tag = "roles",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Role found", body = Role),
+            (status = 404, description = "Role not found", body = ErrorResponse),
+        )
+    )]
     pub async fn get_role_by_id(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = RolesRepository::new(db);
+        let repository = state.data.roles();
         match repository.get_by_id(id.clone()).await {
             Ok(role) => Ok((StatusCode::OK, Json(role))),
             Err(_) => Err((
@@ -48,11 +99,21 @@ pub mod roles_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/roles/name/{name}",
+        tag = "roles",
+        params(("name" = String, Path, description = "Exact role name")),
+        responses(
+            (status = 200, description = "Role found", body = Role),
+            (status = 404, description = "Role not found", body = ErrorResponse),
+        )
+    )]
     pub async fn get_role_by_name(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(name): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = RolesRepository::new(db);
+        let repository = state.data.roles();
         match repository.get_by_name(name.clone()).await {
             Ok(role) => Ok((StatusCode::OK, Json(role))),
             Err(_) => Err((
@@ -65,11 +126,22 @@ pub mod roles_router {
         }
     }
 
+    #[utoipa::path(
+        post,
+        path = "/api/roles",
+        tag = "roles",
+        request_body = Role,
+        responses(
+            (status = 201, description = "Role created", body = RoleResponse),
+            (status = 400, description = "Role already exists", body = ErrorResponse),
+            (status = 500, description = "Failed to create role", body = ErrorResponse),
+        )
+    )]
     pub async fn create_role(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Json(body): Json<Role>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = RolesRepository::new(db);
+        let repository = state.data.roles();
         if let Ok(role) = repository.get_by_name(body.name.clone()).await {
             let json_response = serde_json::json!({
                 "status": "error",
@@ -96,12 +168,33 @@ pub mod roles_router {
         }
     }
 
+    #[utoipa::path(
+        put,
+        path = "/api/roles/{id}",
+        tag = "roles",
+        params(("id" = String, Path, description = "Record id")),
+        request_body = Role,
+        responses(
+            (status = 200, description = "Role updated", body = RoleResponse),
+            (status = 404, description = "Role not found", body = ErrorResponse),
+            (status = 500, description = "Failed to update role", body = ErrorResponse),
+        )
+    )]
     pub async fn update_role(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
         Json(body): Json<Role>,
     ) -> impl IntoResponse {
-        let repository = RolesRepository::new(db);
+        let repository = state.data.roles();
+        if repository.get_by_id(id.clone()).await.is_err() {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Role with ID: {} not found", id)
+                })),
+            );
+        }
         match repository.update(id.clone(), body.clone()).await {
             Ok(role) => (
                 StatusCode::OK,
@@ -120,11 +213,21 @@ pub mod roles_router {
         }
     }
 
+    #[utoipa::path(
+        delete,
+        path = "/api/roles/{id}",
+        tag = "roles",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 204, description = "Role deleted"),
+            (status = 404, description = "Role not found", body = ErrorResponse),
+        )
+    )]
     pub async fn delete_role(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
     ) -> impl IntoResponse {
-        let repository = RolesRepository::new(db);
+        let repository = state.data.roles();
         match repository.delete(id.clone()).await {
             Ok(_) => (
                 StatusCode::NO_CONTENT,
@@ -142,4 +245,128 @@ pub mod roles_router {
             ),
         }
     }
+
+    #[utoipa::path(
+        get,
+        path = "/api/roles/export",
+        tag = "roles",
+        params(ExportParams),
+        responses(
+            (status = 200, description = "Every role, one row each", content(
+                (String = "text/csv"),
+                (String = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn export_roles(
+        State(state): State<AppState>,
+        Query(params): Query<ExportParams>,
+    ) -> Response {
+        let repository = state.data.roles();
+        transfer::export(params.format, "roles", move |after, limit| {
+            let repository = repository.clone();
+            async move { repository.page(after, limit).await }
+        })
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/roles/live",
+        tag = "roles",
+        params(LiveParams),
+        responses(
+            (status = 200, description = "Server-Sent Events named create, update, delete or reset; each change carries its id for resuming", content(
+                (Change = "text/event-stream"),
+            )),
+            (status = 400, description = "Invalid Last-Event-ID", body = ErrorResponse),
+        )
+    )]
+    pub async fn live_roles(
+        State(state): State<AppState>,
+        Query(params): Query<LiveParams>,
+        headers: HeaderMap,
+    ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+        let after = live::resume_from(&headers, params.last_event_id)?;
+        Ok(live::sse(&state, Resource::Role, after))
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/roles/import",
+        tag = "roles",
+        params(ImportParams),
+        request_body(
+            description = "CSV or NDJSON rows as exported. A row updates the role of the same name, or creates one.",
+            content((String = "text/csv"), (String = "application/x-ndjson"))
+        ),
+        responses(
+            (status = 200, description = "Every row imported", body = ImportResponse),
+            (status = 207, description = "Some rows failed", body = ImportResponse),
+            (status = 400, description = "The file is unreadable or empty", body = ErrorResponse),
+            (status = 413, description = "Too many rows", body = ErrorResponse),
+            (status = 415, description = "Unknown format", body = ErrorResponse),
+            (status = 422, description = "No row was valid", body = ImportResponse),
+        )
+    )]
+    pub async fn import_roles(
+        State(state): State<AppState>,
+        Query(params): Query<ImportParams>,
+        headers: HeaderMap,
+        body: String,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let format = transfer::import_format(params.format, &headers)?;
+        let rows = transfer::decode::<Role>(format, &body)?;
+        let repository = state.data.roles();
+
+        let datetime = Local::now();
+        let mut seen = HashSet::new();
+        let mut planned = Vec::with_capacity(rows.len());
+        for row in rows {
+            planned.push(match row {
+                Ok(row) => plan_row(repository.as_ref(), &mut seen, row, datetime).await,
+                Err(e) => Err(e),
+            });
+        }
+
+        Ok(transfer::import(params.dry_run, planned, |plan| async {
+            match plan {
+                Planned::Create(role) => repository.create(role).await,
+                Planned::Update(id, role) => repository.update(id, role).await,
+            }
+        })
+        .await)
+    }
+
+    /// Matches an import row to the stored role of the same name, if any.
+    async fn plan_row(
+        roles: &dyn RoleStore,
+        seen: &mut HashSet<String>,
+        row: RoleRow,
+        now: DateTime<Local>,
+    ) -> Result<Planned<Role>, ItemError> {
+        bulk::validate(&row)?;
+        if !seen.insert(row.name.clone()) {
+            return Err(ItemError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate name: {} in import", row.name),
+            ));
+        }
+        Ok(match roles.get_by_name(row.name.clone()).await {
+            Ok(role) => Planned::Update(
+                role.key().unwrap_or_default(),
+                Role {
+                    name: row.name,
+                    updated_at: Some(now),
+                    ..role
+                },
+            ),
+            Err(_) => Planned::Create(Role {
+                id: None,
+                name: row.name,
+                users: None,
+                created_at: Some(now),
+                updated_at: None,
+            }),
+        })
+    }
 }
@@ -0,0 +1,94 @@
+use crate::data::models::search::{SearchPage, SearchParams, MAX_PER_PAGE};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Serialize;
+
+/// Search parameters after trimming the query and clamping the page size.
+pub struct SearchRequest {
+    pub query: String,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+impl SearchRequest {
+    /// Offset of the first hit; `parse` rejects pages where this would overflow.
+    pub fn start(&self) -> usize {
+        (self.page - 1) * self.per_page
+    }
+}
+
+/// Offset of the first item on page `page`, counting from 1, or `None` when
+/// it overflows the signed 64-bit offset SurrealDB's START takes.
+pub fn page_start(page: usize, per_page: usize) -> Option<usize> {
+    page.checked_sub(1)?
+        .checked_mul(per_page)
+        .filter(|start| i64::try_from(*start).is_ok())
+}
+
+pub fn parse(params: SearchParams) -> Result<SearchRequest, (StatusCode, Json<serde_json::Value>)> {
+    let query = params.q.trim().to_string();
+    if query.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Query parameter q must not be empty"
+            })),
+        ));
+    }
+    if params.page == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Query parameter page starts at 1"
+            })),
+        ));
+    }
+
+    let per_page = params.per_page.clamp(1, MAX_PER_PAGE);
+    if page_start(params.page, per_page).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Query parameter page is too large"
+            })),
+        ));
+    }
+
+    Ok(SearchRequest {
+        query,
+        page: params.page,
+        per_page,
+    })
+}
+
+/// Renders a page of hits with each record under `key`.
+pub fn respond<T: Serialize>(
+    key: &str,
+    request: &SearchRequest,
+    page: SearchPage<T>,
+) -> Json<serde_json::Value> {
+    let results = page
+        .hits
+        .into_iter()
+        .map(|hit| {
+            serde_json::json!({
+                key: hit.record,
+                "score": hit.score,
+                "highlights": hit.highlights,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Json(serde_json::json!({
+        "status": "success",
+        "query": request.query,
+        "page": request.page,
+        "per_page": request.per_page,
+        "total": page.total,
+        "count": results.len(),
+        "results": results,
+    }))
+}
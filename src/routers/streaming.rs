@@ -0,0 +1,74 @@
+use crate::data::models::stream::StreamFormat;
+use axum::body::{Body, Bytes};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::BoxError;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::future::Future;
+
+/// Records fetched per query while streaming.
+pub const PAGE_SIZE: usize = 500;
+
+/// Every record, read `PAGE_SIZE` at a time: each query continues after the
+/// `key` of the last record of the one before, so only one page is held in
+/// memory. A store error is logged and ends the stream.
+pub fn pages<T, F, Fut>(
+    fetch: F,
+    key: fn(&T) -> Option<String>,
+) -> impl Stream<Item = Result<Vec<T>, BoxError>> + Send
+where
+    T: Send + 'static,
+    F: Fn(Option<String>, usize) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<T>, surrealdb::Error>> + Send + 'static,
+{
+    stream::try_unfold(Some(None), move |cursor| {
+        let page = cursor.map(|after| fetch(after, PAGE_SIZE));
+        async move {
+            let Some(page) = page else { return Ok(None) };
+            let records = page.await.map_err(|e| {
+                tracing::error!(error = %e, "Stream stopped early");
+                BoxError::from(e)
+            })?;
+            if records.is_empty() {
+                return Ok(None);
+            }
+            let next = (records.len() == PAGE_SIZE)
+                .then(|| records.last().and_then(key))
+                .flatten()
+                .map(Some);
+            Ok(Some((records, next)))
+        }
+    })
+}
+
+/// Streams every record as NDJSON or as the items of one JSON array.
+pub fn respond<T, F, Fut>(format: StreamFormat, fetch: F, key: fn(&T) -> Option<String>) -> Response
+where
+    T: Serialize + Send + 'static,
+    F: Fn(Option<String>, usize) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<T>, surrealdb::Error>> + Send + 'static,
+{
+    let chunks = pages(fetch, key).enumerate().map(move |(page, records)| {
+        let mut chunk = String::new();
+        for (index, record) in records?.iter().enumerate() {
+            if format == StreamFormat::Json && (page > 0 || index > 0) {
+                chunk.push(',');
+            }
+            chunk.push_str(&serde_json::to_string(record)?);
+            if format == StreamFormat::Ndjson {
+                chunk.push('\n');
+            }
+        }
+        Ok::<_, BoxError>(Bytes::from(chunk))
+    });
+    let body = match format {
+        StreamFormat::Ndjson => Body::from_stream(chunks),
+        StreamFormat::Json => Body::from_stream(
+            stream::once(async { Ok(Bytes::from_static(b"[")) })
+                .chain(chunks)
+                .chain(stream::once(async { Ok(Bytes::from_static(b"]")) })),
+        ),
+    };
+    ([(header::CONTENT_TYPE, format.content_type())], body).into_response()
+}
@@ -0,0 +1,40 @@
+pub mod tags_router {
+    use crate::routers::openapi::{ErrorResponse, TagListResponse};
+    use crate::state::AppState;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::{response::IntoResponse, routing::get, Json, Router};
+
+    pub fn router() -> Router<AppState> {
+        Router::new().route("/", get(get_all_tags))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/tags",
+        tag = "tags",
+        responses(
+            (status = 200, description = "Every tag in use with how many todos carry it, most used first", body = TagListResponse),
+            (status = 500, description = "Failed to count tags", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_all_tags(
+        State(state): State<AppState>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        match repository.tag_counts().await {
+            Ok(tags) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "count": tags.len(),
+                "tags": tags
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to count tags"
+                })),
+            )),
+        }
+    }
+}
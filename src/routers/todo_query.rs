@@ -0,0 +1,28 @@
+use crate::data::models::tag::normalize_tag;
+use crate::data::models::todo::TodoFilter;
+use axum::async_trait;
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+
+/// Extracts a `TodoFilter` from the query string, collecting repeated `tag`
+/// parameters, which `Query` alone cannot deserialize into a list.
+#[derive(Debug, Clone, Default)]
+pub struct TodoQuery(pub TodoFilter);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for TodoQuery {
+    type Rejection = QueryRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(mut filter) = Query::<TodoFilter>::from_request_parts(parts, state).await?;
+        let Query(pairs) = Query::<Vec<(String, String)>>::from_request_parts(parts, state).await?;
+        filter.tags = pairs
+            .into_iter()
+            .filter(|(key, _)| key == "tag")
+            .map(|(_, tag)| normalize_tag(&tag))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        Ok(TodoQuery(filter))
+    }
+}
@@ -1,47 +1,384 @@
 pub mod todos_router {
-    use crate::data::repositories::todos_repository::TodosRepository;
-    use crate::db::Database;
-    use crate::data::models::todo::{CreateTodo, Todo, UpdateTodo};
-    use axum::extract::Path;
-    use axum::http::StatusCode;
-    use axum::response::IntoResponse;
+    use crate::data::dependencies::dependency_path;
+    use crate::data::ical;
+    use crate::data::models::bulk::BulkParams;
+    use crate::data::models::history::AsOfParams;
+    use crate::data::models::live::LiveParams;
+    use crate::data::models::search::SearchParams;
+    use crate::data::models::stream::StreamParams;
+    use crate::data::models::tag::{normalize_tag, normalize_tags, AddTags};
+    use crate::data::models::todo::{
+        completed_at_change, AddBlocker, BulkTodoPatch, CompletionParams, CreateTodo,
+        OccurrenceParams, Todo, TodoFilter, TodoPatch, TodoWithBlockers, UpdateTodo,
+        MAX_OCCURRENCES,
+    };
+    use crate::data::models::transfer::{ExportParams, ImportParams, TodoRow};
+    use crate::data::recurrence::Recurrence;
+    use crate::data::stores::{ListStore, TodoStore};
+    use crate::data::transfer::Transfer;
+    use crate::live::{Change, Resource};
+    use crate::routers::bulk::{self, BulkResource, ItemError};
+    use crate::routers::history;
+    use crate::routers::live;
+    use crate::routers::openapi::{
+        BulkResponse, ErrorResponse, ImportResponse, OccurrencesResponse, TodoHistoryResponse,
+        TodoListResponse, TodoResponse, TodoSearchResponse, UpdateTodoResponse,
+    };
+    use crate::routers::search;
+    use crate::routers::streaming;
+    use crate::routers::todo_query::TodoQuery;
+    use crate::routers::transfer::{self, Planned};
+    use crate::state::AppState;
+    use axum::extract::{Path, Query, State};
+    use axum::http::{header, HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
     use axum::{
-        routing::{get, post},
-        Extension, Json, Router,
+        routing::{delete, get, post},
+        Json, Router,
     };
-    use chrono::Local;
-    use std::sync::Arc;
+    use chrono::{DateTime, Local};
+    use std::collections::HashSet;
 
-    pub fn router() -> Router {
+    const BULK_CREATE: BulkResource = BulkResource {
+        key: "todo",
+        name: "Todo",
+        action: "create",
+        success: StatusCode::CREATED,
+    };
+    const BULK_UPDATE: BulkResource = BulkResource {
+        key: "todo",
+        name: "Todo",
+        action: "update",
+        success: StatusCode::OK,
+    };
+    const IMPORT: BulkResource = BulkResource {
+        key: "todo",
+        name: "Todo",
+        action: "import",
+        success: StatusCode::CREATED,
+    };
+    const BULK_DELETE: BulkResource = BulkResource {
+        key: "todo",
+        name: "Todo",
+        action: "delete",
+        success: StatusCode::OK,
+    };
+
+    pub fn router() -> Router<AppState> {
         Router::new()
             .route("/", post(create_todo).get(get_all_todos))
+            .route(
+                "/bulk",
+                post(bulk_create_todos)
+                    .patch(bulk_update_todos)
+                    .delete(bulk_delete_todos),
+            )
+            .route("/export", get(export_todos))
+            .route("/live", get(live_todos))
+            .route("/import", post(import_todos))
             .route(
                 "/:id",
                 get(get_todo_by_id).put(update_todo).delete(delete_todo),
             )
+            .route("/:id/history", get(get_todo_history))
+            .route("/:id/revert/:version", post(revert_todo))
+            .route("/:id/tags", post(add_tags))
+            .route("/:id/tags/:tag", delete(remove_tag))
+            .route("/:id/occurrences", get(get_occurrences))
+            .route("/:id/recurrence", delete(stop_recurrence))
+            .route("/:id/subtasks", get(get_subtasks))
+            .route("/:id/blockers", get(get_blockers).post(add_blocker))
+            .route("/:id/blockers/:blocker_id", delete(remove_blocker))
             .route("/title/:title", get(get_todo_by_title))
+            .route("/search", get(search_todos))
+    }
+
+    /// Rejects a todo that names a list that does not exist.
+    async fn check_list(lists: &dyn ListStore, list_id: Option<&String>) -> Result<(), String> {
+        match list_id {
+            Some(id) if lists.get_by_id(id.clone()).await.is_err() => {
+                Err(format!("List with ID: {} not found", id))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects a subtask whose parent does not exist.
+    async fn check_parent(todos: &dyn TodoStore, parent_id: Option<&String>) -> Result<(), String> {
+        match parent_id {
+            Some(id) if todos.get_by_id(id.clone()).await.is_err() => {
+                Err(format!("Parent todo with ID: {} not found", id))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether making `id` a subtask of `parent_id` would make it its own ancestor.
+    async fn creates_parent_cycle(todos: &dyn TodoStore, id: &str, parent_id: &str) -> bool {
+        let mut seen = HashSet::new();
+        let mut current = Some(parent_id.to_string());
+        while let Some(step) = current {
+            if step == id {
+                return true;
+            }
+            if !seen.insert(step.clone()) {
+                return false;
+            }
+            current = match todos.get_by_id(step).await {
+                Ok(todo) => todo.parent.map(|parent| parent.id.to_raw()),
+                Err(_) => None,
+            };
+        }
+        false
+    }
+
+    /// Parses `rule` into its canonical form, rejecting rules that are invalid
+    /// or lack a due date to repeat from.
+    fn check_recurrence(
+        rule: Option<&str>,
+        due_at: Option<DateTime<Local>>,
+    ) -> Result<Option<String>, String> {
+        let Some(rule) = rule else { return Ok(None) };
+        let recurrence = Recurrence::parse(rule)?;
+        if due_at.is_none() {
+            return Err("A recurring todo needs a due_at".to_string());
+        }
+        Ok(Some(recurrence.to_string()))
     }
 
-    pub async fn get_all_todos(Extension(db): Extension<Arc<Database>>) -> impl IntoResponse {
-        let repository = TodosRepository::new(db);
+    /// Turns one item of a batch into a todo to create: normalises tags and
+    /// recurrence, validates it, and checks the list and parent it names.
+    async fn prepare_create(
+        todos: &dyn TodoStore,
+        lists: &dyn ListStore,
+        mut item: CreateTodo,
+        now: DateTime<Local>,
+    ) -> Result<Todo, ItemError> {
+        let normalized = item
+            .tags
+            .as_deref()
+            .map(normalize_tags)
+            .transpose()
+            .and_then(|tags| {
+                check_recurrence(item.recurrence.as_deref(), item.due_at)
+                    .map(|recurrence| (tags, recurrence))
+            });
+        let (tags, recurrence) = normalized
+            .map_err(|message| ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, message))?;
+        item.tags = tags;
+        item.recurrence = recurrence;
+
+        bulk::validate(&item)?;
+        let links = match check_list(lists, item.list_id.as_ref()).await {
+            Ok(()) => check_parent(todos, item.parent_id.as_ref()).await,
+            Err(message) => Err(message),
+        };
+        links.map_err(|message| ItemError::new(StatusCode::BAD_REQUEST, message))?;
+        Ok(Todo::from_create(item, now))
+    }
+
+    async fn open_subtasks(todos: &dyn TodoStore, id: &str) -> Vec<Todo> {
+        let filter = TodoFilter {
+            parent_id: Some(id.to_string()),
+            completed: Some(false),
+            ..TodoFilter::default()
+        };
+        todos.list(filter).await.unwrap_or_default()
+    }
 
-        let todos = repository.get_all().await.unwrap_or_default();
+    async fn with_open_blockers(todos: &dyn TodoStore, todo: Todo) -> TodoWithBlockers {
+        let id = todo
+            .id
+            .as_ref()
+            .map(|id| id.id.to_raw())
+            .unwrap_or_default();
+        let open_blockers = todos
+            .blockers(id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|blocker| blocker.completed != Some(true))
+            .collect();
+        TodoWithBlockers {
+            todo,
+            open_blockers,
+        }
+    }
+
+    fn raw_ids(todos: &[Todo]) -> Vec<String> {
+        todos
+            .iter()
+            .filter_map(|todo| todo.id.as_ref().map(|id| id.id.to_raw()))
+            .collect()
+    }
+
+    fn todo_not_found(id: &str) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Todo with ID: {} not found", id)
+            })),
+        )
+    }
+
+    fn not_recurring(id: &str) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Todo with ID: {} does not repeat", id)
+            })),
+        )
+    }
+
+    fn unprocessable(message: String) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })),
+        )
+    }
+
+    fn conflict(message: String) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })),
+        )
+    }
+
+    fn bad_request(message: String) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": message
+            })),
+        )
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos",
+        tag = "todos",
+        params(
+            TodoFilter,
+            StreamParams,
+            ("tag" = Option<Vec<String>>, Query, description = "Only todos with this tag; repeat for several"),
+        ),
+        responses(
+            (status = 200, description = "List todos, optionally filtered and sorted, or stream them", content(
+                (TodoListResponse = "application/json"),
+                (Todo = "application/x-ndjson"),
+            )),
+            (status = 400, description = "Invalid filter value, or a sort on a streamed list", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_all_todos(
+        State(state): State<AppState>,
+        TodoQuery(filter): TodoQuery,
+        Query(params): Query<StreamParams>,
+    ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        if let Some(format) = params.stream {
+            if filter.sort.is_some() {
+                return Err(bad_request(
+                    "Streamed todos come in id order and cannot be sorted".to_string(),
+                ));
+            }
+            return Ok(streaming::respond(
+                format,
+                move |after, limit| {
+                    let repository = repository.clone();
+                    let filter = filter.clone();
+                    async move { repository.page(filter, after, limit).await }
+                },
+                Todo::key,
+            ));
+        }
+
+        let todos = repository.list(filter).await.unwrap_or_default();
         let json_response = serde_json::json!({
             "status": "success",
             "count": todos.len(),
             "todos": todos,
         });
 
-        Json(json_response)
+        Ok(Json(json_response).into_response())
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos.ics",
+        tag = "todos",
+        params(
+            TodoFilter,
+            ("tag" = Option<Vec<String>>, Query, description = "Only todos with this tag; repeat for several"),
+        ),
+        responses(
+            (status = 200, description = "Todos as iCalendar VTODO components", body = String, content_type = "text/calendar"),
+            (status = 400, description = "Invalid filter value"),
+        )
+    )]
+    pub async fn get_todos_calendar(
+        State(state): State<AppState>,
+        TodoQuery(filter): TodoQuery,
+    ) -> impl IntoResponse {
+        let todos = state.data.todos().list(filter).await.unwrap_or_default();
+        (
+            [(header::CONTENT_TYPE, ical::CONTENT_TYPE)],
+            ical::render(&todos, Local::now()),
+        )
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/todos/{id}",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id"), AsOfParams),
+        responses(
+            (status = 200, description = "Todo found, with the blockers still open; with `as_of`, the TodoVersion current at that time instead", body = TodoWithBlockers),
+            (status = 404, description = "Todo not found, or did not exist at `as_of`", body = ErrorResponse),
+        )
+    )]
     pub async fn get_todo_by_id(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
+        Query(params): Query<AsOfParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = TodosRepository::new(db);
+        let repository = state.data.todos();
+        if let Some(at) = params.as_of {
+            return match repository.as_of(id.clone(), at).await {
+                Ok(Some(version)) if version.record.is_some() => {
+                    Ok((StatusCode::OK, Json(history::entry("todo", version))).into_response())
+                }
+                Ok(_) => Err((
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": format!("Todo with ID: {} did not exist at {}", id, at.to_rfc3339())
+                    })),
+                )),
+                Err(_) => Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": "Failed to fetch todo history"
+                    })),
+                )),
+            };
+        }
         match repository.get_by_id(id.clone()).await {
-            Ok(todo) => Ok((StatusCode::OK, Json(todo))),
+            Ok(todo) => Ok((
+                StatusCode::OK,
+                Json(with_open_blockers(repository.as_ref(), todo).await),
+            )
+                .into_response()),
             Err(_) => Err((
                 StatusCode::NOT_FOUND,
                 Json(serde_json::json!({
@@ -52,11 +389,21 @@ pub mod todos_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/todos/title/{title}",
+        tag = "todos",
+        params(("title" = String, Path, description = "Exact todo title")),
+        responses(
+            (status = 200, description = "Todo found", body = Todo),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+        )
+    )]
     pub async fn get_todo_by_title(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(title): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = TodosRepository::new(db);
+        let repository = state.data.todos();
         match repository.get_by_title(title.clone()).await {
             Ok(todo) => Ok((StatusCode::OK, Json(todo))),
             Err(_) => Err((
@@ -69,19 +416,58 @@ pub mod todos_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/todos/search",
+        tag = "todos",
+        params(SearchParams),
+        responses(
+            (status = 200, description = "Matching todos, best match first", body = TodoSearchResponse),
+            (status = 400, description = "Missing query or invalid page", body = ErrorResponse),
+            (status = 500, description = "Search failed", body = ErrorResponse),
+        )
+    )]
+    pub async fn search_todos(
+        State(state): State<AppState>,
+        Query(params): Query<SearchParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let request = search::parse(params)?;
+        let repository = state.data.todos();
+
+        match repository
+            .search(request.query.clone(), request.start(), request.per_page)
+            .await
+        {
+            Ok(page) => Ok(search::respond("todo", &request, page)),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to search todos"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos",
+        tag = "todos",
+        request_body = CreateTodo,
+        responses(
+            (status = 201, description = "Todo created", body = TodoResponse),
+            (status = 400, description = "List or parent not found", body = ErrorResponse),
+            (status = 422, description = "Empty title, or invalid tag or recurrence rule", body = ErrorResponse),
+            (status = 500, description = "Failed to create todo", body = ErrorResponse),
+        )
+    )]
     pub async fn create_todo(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Json(body): Json<CreateTodo>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = TodosRepository::new(db);
-        let todo = Todo {
-            id: None,
-            title: body.title.clone(),
-            content: Some(body.content.clone().unwrap_or("".to_string())),
-            completed: Some(body.completed.unwrap_or(false)),
-            created_at: Some(Local::now()),
-            updated_at: None,
-        };
+        let repository = state.data.todos();
+        let lists = state.data.lists();
+        let todo = prepare_create(repository.as_ref(), lists.as_ref(), body, Local::now()).await?;
         match repository.create(todo).await {
             Ok(todo) => {
                 let json_response = serde_json::json!({
@@ -100,31 +486,108 @@ pub mod todos_router {
         }
     }
 
-
-
+    #[utoipa::path(
+        put,
+        path = "/api/todos/{id}",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id"), CompletionParams),
+        request_body = UpdateTodo,
+        responses(
+            (status = 200, description = "Todo updated, with the next occurrence when completing a recurring todo", body = UpdateTodoResponse),
+            (status = 400, description = "List or parent not found", body = ErrorResponse),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+            (status = 409, description = "Subtasks are still open, or the parent would loop", body = ErrorResponse),
+            (status = 422, description = "Invalid tag or recurrence rule", body = ErrorResponse),
+            (status = 500, description = "Failed to update todo", body = ErrorResponse),
+        )
+    )]
     pub async fn update_todo(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
+        Query(params): Query<CompletionParams>,
         Json(body): Json<UpdateTodo>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = TodosRepository::new(db);
+        let repository = state.data.todos();
+        let tags = match body.tags.as_deref() {
+            Some(tags) => Some(normalize_tags(tags).map_err(unprocessable)?),
+            None => None,
+        };
+        check_list(state.data.lists().as_ref(), body.list_id.as_ref())
+            .await
+            .map_err(bad_request)?;
+        check_parent(repository.as_ref(), body.parent_id.as_ref())
+            .await
+            .map_err(bad_request)?;
 
         match repository.get_by_id(id.clone()).await {
             Ok(mut todo) => {
+                let recurrence =
+                    check_recurrence(body.recurrence.as_deref(), body.due_at.or(todo.due_at))
+                        .map_err(unprocessable)?;
+                if let Some(parent_id) = body.parent_id.as_deref() {
+                    if creates_parent_cycle(repository.as_ref(), &id, parent_id).await {
+                        return Err((
+                            StatusCode::CONFLICT,
+                            Json(serde_json::json!({
+                                "status": "error",
+                                "message": format!(
+                                    "Todo with ID: {} cannot be a subtask of its own subtask {}",
+                                    id, parent_id
+                                )
+                            })),
+                        ));
+                    }
+                }
+                if body.completed == Some(true) && todo.completed != Some(true) && !params.force {
+                    let open = open_subtasks(repository.as_ref(), &id).await;
+                    if !open.is_empty() {
+                        return Err((
+                            StatusCode::CONFLICT,
+                            Json(serde_json::json!({
+                                "status": "error",
+                                "message": format!(
+                                    "Todo with ID: {} has {} open subtasks; complete them first or pass force=true",
+                                    id,
+                                    open.len()
+                                ),
+                                "open_subtasks": raw_ids(&open)
+                            })),
+                        ));
+                    }
+                }
+
                 let datetime = Local::now();
-                todo.title = body.title.clone().unwrap_or(todo.title);
-                todo.content = body.content.or(todo.content);
-                todo.completed = body.completed.or(todo.completed);
-                todo.updated_at = Some(datetime);
-
-                match repository.update(id.clone(), todo.clone()).await {
-                    Ok(todo_response) => Ok((
-                        StatusCode::OK,
-                        Json(serde_json::json!({
+                let was_completed = todo.completed == Some(true);
+                todo.apply_update(
+                    UpdateTodo {
+                        tags,
+                        recurrence,
+                        ..body
+                    },
+                    datetime,
+                );
+
+                let updated = match todo.link_next_occurrence(was_completed, datetime) {
+                    Some(next) => repository
+                        .update_with_next(id.clone(), todo, next)
+                        .await
+                        .map(|(todo, next)| (todo, Some(next))),
+                    None => repository
+                        .update(id.clone(), todo)
+                        .await
+                        .map(|todo| (todo, None)),
+                };
+                match updated {
+                    Ok((todo_response, next)) => {
+                        let mut response = serde_json::json!({
                             "status": "success",
                             "todo": todo_response
-                        })),
-                    )),
+                        });
+                        if let Some(next) = next {
+                            response["next_occurrence"] = serde_json::json!(next);
+                        }
+                        Ok((StatusCode::OK, Json(response)))
+                    }
                     Err(_) => Err((
                         StatusCode::INTERNAL_SERVER_ERROR,
                         Json(serde_json::json!({
@@ -134,21 +597,475 @@ pub mod todos_router {
                     )),
                 }
             }
+            Err(_) => Err(todo_not_found(&id)),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos/{id}/tags",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id")),
+        request_body = AddTags,
+        responses(
+            (status = 200, description = "Tags added", body = TodoResponse),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+            (status = 422, description = "Invalid tag", body = ErrorResponse),
+            (status = 500, description = "Failed to add tags", body = ErrorResponse),
+        )
+    )]
+    pub async fn add_tags(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Json(body): Json<AddTags>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let tags = normalize_tags(&body.tags).map_err(unprocessable)?;
+        let repository = state.data.todos();
+        if repository.get_by_id(id.clone()).await.is_err() {
+            return Err(todo_not_found(&id));
+        }
+
+        match repository.add_tags(id, tags).await {
+            Ok(todo) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "todo": todo
+            }))),
             Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to add tags"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/todos/{id}/tags/{tag}",
+        tag = "todos",
+        params(
+            ("id" = String, Path, description = "Record id"),
+            ("tag" = String, Path, description = "Tag to remove"),
+        ),
+        responses(
+            (status = 200, description = "Tag removed", body = TodoResponse),
+            (status = 404, description = "Todo not found, or it does not have the tag", body = ErrorResponse),
+            (status = 500, description = "Failed to remove tag", body = ErrorResponse),
+        )
+    )]
+    pub async fn remove_tag(
+        State(state): State<AppState>,
+        Path((id, tag)): Path<(String, String)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let tag = normalize_tag(&tag);
+        let repository = state.data.todos();
+        let Ok(todo) = repository.get_by_id(id.clone()).await else {
+            return Err(todo_not_found(&id));
+        };
+        if !todo.tags.unwrap_or_default().contains(&tag) {
+            return Err((
                 StatusCode::NOT_FOUND,
                 Json(serde_json::json!({
                     "status": "error",
-                    "message": format!("Todo with ID: {} not found", id)
+                    "message": format!("Todo with ID: {} has no tag {}", id, tag)
+                })),
+            ));
+        }
+
+        match repository.remove_tag(id, tag).await {
+            Ok(todo) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "todo": todo
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to remove tag"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos/{id}/occurrences",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id"), OccurrenceParams),
+        responses(
+            (status = 200, description = "Upcoming due dates of the series after this todo", body = OccurrencesResponse),
+            (status = 404, description = "Todo not found, or it does not repeat", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_occurrences(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Query(params): Query<OccurrenceParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let Ok(todo) = state.data.todos().get_by_id(id.clone()).await else {
+            return Err(todo_not_found(&id));
+        };
+        let (Some(rule), Some(due_at)) = (todo.recurrence.as_deref(), todo.due_at) else {
+            return Err(not_recurring(&id));
+        };
+        let Ok(recurrence) = Recurrence::parse(rule) else {
+            return Err(not_recurring(&id));
+        };
+
+        let occurrences = recurrence.occurrences(due_at, params.count.clamp(1, MAX_OCCURRENCES));
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "recurrence": rule,
+            "count": occurrences.len(),
+            "occurrences": occurrences,
+        })))
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/todos/{id}/recurrence",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Series stopped; the todo stays as a one-off", body = TodoResponse),
+            (status = 404, description = "Todo not found, or it does not repeat", body = ErrorResponse),
+            (status = 500, description = "Failed to stop the series", body = ErrorResponse),
+        )
+    )]
+    pub async fn stop_recurrence(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        let Ok(mut todo) = repository.get_by_id(id.clone()).await else {
+            return Err(todo_not_found(&id));
+        };
+        if todo.recurrence.is_none() {
+            return Err(not_recurring(&id));
+        }
+
+        todo.recurrence = None;
+        todo.updated_at = Some(Local::now());
+        match repository.update(id, todo).await {
+            Ok(todo) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "todo": todo
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to stop the series"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos/{id}/subtasks",
+        tag = "todos",
+        params(
+            ("id" = String, Path, description = "Record id"),
+            TodoFilter,
+            ("tag" = Option<Vec<String>>, Query, description = "Only subtasks with this tag; repeat for several"),
+        ),
+        responses(
+            (status = 200, description = "Direct subtasks of the todo", body = TodoListResponse),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_subtasks(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        TodoQuery(mut filter): TodoQuery,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        if repository.get_by_id(id.clone()).await.is_err() {
+            return Err(todo_not_found(&id));
+        }
+
+        filter.parent_id = Some(id);
+        let todos = repository.list(filter).await.unwrap_or_default();
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "count": todos.len(),
+            "todos": todos,
+        })))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos/{id}/blockers",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Todos this one waits on, open or not", body = TodoListResponse),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_blockers(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        if repository.get_by_id(id.clone()).await.is_err() {
+            return Err(todo_not_found(&id));
+        }
+
+        let todos = repository.blockers(id).await.unwrap_or_default();
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "count": todos.len(),
+            "todos": todos,
+        })))
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos/{id}/blockers",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id of the blocked todo")),
+        request_body = AddBlocker,
+        responses(
+            (status = 201, description = "Dependency added", body = TodoWithBlockers),
+            (status = 200, description = "Dependency already existed", body = TodoWithBlockers),
+            (status = 400, description = "Blocker not found, or a todo blocking itself", body = ErrorResponse),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+            (status = 409, description = "The dependency would close a cycle", body = ErrorResponse),
+            (status = 500, description = "Failed to add dependency", body = ErrorResponse),
+        )
+    )]
+    pub async fn add_blocker(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Json(body): Json<AddBlocker>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        let Ok(todo) = repository.get_by_id(id.clone()).await else {
+            return Err(todo_not_found(&id));
+        };
+        if body.blocker_id == id {
+            return Err(bad_request("A todo cannot block itself".to_string()));
+        }
+        if repository.get_by_id(body.blocker_id.clone()).await.is_err() {
+            return Err(bad_request(format!(
+                "Blocker todo with ID: {} not found",
+                body.blocker_id
+            )));
+        }
+
+        let failed = || {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to add dependency"
+                })),
+            )
+        };
+        let edges = repository.dependencies().await.map_err(|_| failed())?;
+        let status = if edges
+            .iter()
+            .any(|edge| edge.todo == id && edge.blocker == body.blocker_id)
+        {
+            StatusCode::OK
+        } else if let Some(path) = dependency_path(&edges, &body.blocker_id, &id) {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!(
+                        "Todo with ID: {} already depends on {}; the dependency would close a cycle",
+                        body.blocker_id, id
+                    ),
+                    "cycle": path
+                })),
+            ));
+        } else {
+            repository
+                .add_blocker(id, body.blocker_id)
+                .await
+                .map_err(|_| failed())?;
+            StatusCode::CREATED
+        };
+
+        Ok((
+            status,
+            Json(with_open_blockers(repository.as_ref(), todo).await),
+        ))
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/todos/{id}/blockers/{blocker_id}",
+        tag = "todos",
+        params(
+            ("id" = String, Path, description = "Record id of the blocked todo"),
+            ("blocker_id" = String, Path, description = "Record id of the blocker"),
+        ),
+        responses(
+            (status = 204, description = "Dependency removed"),
+            (status = 404, description = "No such dependency", body = ErrorResponse),
+        )
+    )]
+    pub async fn remove_blocker(
+        State(state): State<AppState>,
+        Path((id, blocker_id)): Path<(String, String)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        match repository
+            .remove_blocker(id.clone(), blocker_id.clone())
+            .await
+        {
+            Ok(()) => Ok((
+                StatusCode::NO_CONTENT,
+                Json(serde_json::json!({
+                    "status": "success",
+                    "message": "Dependency removed successfully"
+                })),
+            )),
+            Err(_) => Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Todo with ID: {} is not blocked by {}", id, blocker_id)
                 })),
             )),
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/todos/{id}/history",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Every version of the todo, newest first, also once it is deleted", body = TodoHistoryResponse),
+            (status = 404, description = "Todo never existed", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_todo_history(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        match state.data.todos().history(id.clone()).await {
+            Ok(versions) if !versions.is_empty() => {
+                Ok((StatusCode::OK, history::respond("todo", versions)))
+            }
+            Ok(_) => Err(todo_not_found(&id)),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to fetch todo history"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos/{id}/revert/{version}",
+        tag = "todos",
+        params(
+            ("id" = String, Path, description = "Record id"),
+            ("version" = u64, Path, description = "Version to restore, from the todo's history"),
+        ),
+        responses(
+            (status = 200, description = "Todo restored to the version; the revert is itself a new version", body = TodoResponse),
+            (status = 404, description = "Todo or version not found", body = ErrorResponse),
+            (status = 409, description = "The version is a deletion, or its list or parent no longer fits", body = ErrorResponse),
+        )
+    )]
+    pub async fn revert_todo(
+        State(state): State<AppState>,
+        Path((id, version)): Path<(String, u64)>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        let current = repository
+            .get_by_id(id.clone())
+            .await
+            .map_err(|_| todo_not_found(&id))?;
+        let versions = repository.history(id.clone()).await.map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to fetch todo history"
+                })),
+            )
+        })?;
+        let Some(found) = versions.into_iter().find(|v| v.version == version) else {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Todo with ID: {} has no version {}", id, version)
+                })),
+            ));
+        };
+        let Some(mut todo) = found.record else {
+            return Err(conflict(format!(
+                "Version {} of todo with ID: {} is its deletion",
+                version, id
+            )));
+        };
+
+        // The list and parent may have changed since; the version must still fit
+        let list_id = todo.list.as_ref().map(|list| list.id.to_raw());
+        check_list(state.data.lists().as_ref(), list_id.as_ref())
+            .await
+            .map_err(conflict)?;
+        let parent_id = todo.parent.as_ref().map(|parent| parent.id.to_raw());
+        check_parent(repository.as_ref(), parent_id.as_ref())
+            .await
+            .map_err(conflict)?;
+        if let Some(parent_id) = parent_id.as_deref() {
+            if creates_parent_cycle(repository.as_ref(), &id, parent_id).await {
+                return Err(conflict(format!(
+                    "Todo with ID: {} cannot be a subtask of its own subtask {}",
+                    id, parent_id
+                )));
+            }
+        }
+
+        todo.created_at = current.created_at;
+        todo.updated_at = Some(Local::now());
+        match repository.update(id.clone(), todo).await {
+            Ok(todo) => Ok((
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "status": "success",
+                    "todo": todo
+                })),
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to revert todo"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/todos/{id}",
+        tag = "todos",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 204, description = "Todo deleted"),
+            (status = 404, description = "Todo not found", body = ErrorResponse),
+        )
+    )]
     pub async fn delete_todo(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = TodosRepository::new(db);
+        let repository = state.data.todos();
 
         if repository.get_by_id(id.clone()).await.is_ok() {
             repository.delete(id.clone()).await.unwrap();
@@ -165,4 +1082,554 @@ pub mod todos_router {
             Err((StatusCode::NOT_FOUND, Json(error_response)))
         }
     }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos/bulk",
+        tag = "todos",
+        params(BulkParams),
+        request_body = Vec<CreateTodo>,
+        responses(
+            (status = 201, description = "Every todo created", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch", body = ErrorResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+            (status = 422, description = "An item failed validation, nothing was created", body = BulkResponse),
+        )
+    )]
+    pub async fn bulk_create_todos(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<CreateTodo>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.todos();
+
+        let lists = state.data.lists();
+        let datetime = Local::now();
+        let mut checked = Vec::with_capacity(body.len());
+        for item in body {
+            checked.push(prepare_create(repository.as_ref(), lists.as_ref(), item, datetime).await);
+        }
+
+        match bulk::execute(params.mode, checked, |todos, atomic| {
+            repository.create_many(todos, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_CREATE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_CREATE)),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos/export",
+        tag = "todos",
+        params(ExportParams),
+        responses(
+            (status = 200, description = "Every todo, one row each", content(
+                (String = "text/csv"),
+                (String = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn export_todos(
+        State(state): State<AppState>,
+        Query(params): Query<ExportParams>,
+    ) -> Response {
+        let repository = state.data.todos();
+        transfer::export(params.format, "todos", move |after, limit| {
+            let repository = repository.clone();
+            async move { repository.page(TodoFilter::default(), after, limit).await }
+        })
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/todos/live",
+        tag = "todos",
+        params(LiveParams),
+        responses(
+            (status = 200, description = "Server-Sent Events named create, update, delete or reset; each change carries its id for resuming", content(
+                (Change = "text/event-stream"),
+            )),
+            (status = 400, description = "Invalid Last-Event-ID", body = ErrorResponse),
+        )
+    )]
+    pub async fn live_todos(
+        State(state): State<AppState>,
+        Query(params): Query<LiveParams>,
+        headers: HeaderMap,
+    ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+        let after = live::resume_from(&headers, params.last_event_id)?;
+        Ok(live::sse(&state, Resource::Todo, after))
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/todos/import",
+        tag = "todos",
+        params(BulkParams, ImportParams),
+        request_body(
+            description = "An iCalendar file, where each VTODO becomes a todo, or CSV or NDJSON rows as exported. \
+                A row whose `id` names a stored todo updates it; other rows create todos.",
+            content(
+                (String = "text/calendar"),
+                (String = "text/csv"),
+                (String = "application/x-ndjson"),
+            )
+        ),
+        responses(
+            (status = 200, description = "Every row imported", body = ImportResponse),
+            (status = 201, description = "Every todo of the calendar imported", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial import", body = BulkResponse),
+            (status = 400, description = "Not a calendar, or the file is unreadable or empty", body = ErrorResponse),
+            (status = 413, description = "Too many todos", body = ErrorResponse),
+            (status = 422, description = "A todo was invalid and nothing was imported, or no row was valid", body = BulkResponse),
+        )
+    )]
+    pub async fn import_todos(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Query(import): Query<ImportParams>,
+        headers: HeaderMap,
+        body: String,
+    ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+        match import.format.or_else(|| transfer::body_format(&headers)) {
+            Some(format) => {
+                let rows = transfer::decode::<Todo>(format, &body)?;
+                import_rows(&state, import.dry_run, rows).await
+            }
+            None => import_calendar(&state, params, &body).await,
+        }
+        .map(IntoResponse::into_response)
+    }
+
+    /// Creates a todo from each VTODO of an iCalendar file, as a bulk create.
+    async fn import_calendar(
+        state: &AppState,
+        params: BulkParams,
+        body: &str,
+    ) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<serde_json::Value>)> {
+        let items = ical::parse(body).map_err(bad_request)?;
+        bulk::check_size(&items)?;
+        let repository = state.data.todos();
+
+        let lists = state.data.lists();
+        let datetime = Local::now();
+        let mut checked = Vec::with_capacity(items.len());
+        for item in items {
+            checked.push(match item {
+                Ok(item) => {
+                    prepare_create(repository.as_ref(), lists.as_ref(), item, datetime).await
+                }
+                Err(message) => Err(ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, message)),
+            });
+        }
+
+        match bulk::execute(params.mode, checked, |todos, atomic| {
+            repository.create_many(todos, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&IMPORT, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&IMPORT)),
+        }
+    }
+
+    /// Upserts CSV or NDJSON rows by id, reporting on each one.
+    async fn import_rows(
+        state: &AppState,
+        dry_run: bool,
+        rows: transfer::Rows<TodoRow>,
+    ) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<serde_json::Value>)> {
+        let repository = state.data.todos();
+        let lists = state.data.lists();
+        let datetime = Local::now();
+        let mut seen = HashSet::new();
+        let mut planned = Vec::with_capacity(rows.len());
+        for row in rows {
+            planned.push(match row {
+                Ok(row) => {
+                    plan_row(
+                        repository.as_ref(),
+                        lists.as_ref(),
+                        &mut seen,
+                        row,
+                        datetime,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            });
+        }
+
+        Ok(transfer::import(dry_run, planned, |plan| async {
+            match plan {
+                Planned::Create(todo) => repository.create(todo).await,
+                Planned::Update(id, todo) => repository.update(id, todo).await,
+            }
+        })
+        .await)
+    }
+
+    /// Decides what an import row does: update the todo its `id` names, with
+    /// the same checks as `PUT /api/todos/{id}`, or create a new one.
+    async fn plan_row(
+        todos: &dyn TodoStore,
+        lists: &dyn ListStore,
+        seen: &mut HashSet<String>,
+        row: TodoRow,
+        now: DateTime<Local>,
+    ) -> Result<Planned<Todo>, ItemError> {
+        let existing = match row.id.clone() {
+            Some(id) if !seen.insert(id.clone()) => {
+                return Err(ItemError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Duplicate ID: {} in import", id),
+                ))
+            }
+            Some(id) => todos
+                .get_by_id(id.clone())
+                .await
+                .ok()
+                .map(|todo| (id, todo)),
+            None => None,
+        };
+        let Some((id, mut todo)) = existing else {
+            return prepare_create(todos, lists, row.into_create(), now)
+                .await
+                .map(Planned::Create);
+        };
+
+        bulk::validate(&row)?;
+        let mut body = row.into_update();
+        let normalized = body
+            .tags
+            .as_deref()
+            .map(normalize_tags)
+            .transpose()
+            .and_then(|tags| {
+                check_recurrence(body.recurrence.as_deref(), body.due_at.or(todo.due_at))
+                    .map(|recurrence| (tags, recurrence))
+            });
+        let (tags, recurrence) = normalized
+            .map_err(|message| ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, message))?;
+        body.tags = tags;
+        body.recurrence = recurrence;
+
+        let links = match check_list(lists, body.list_id.as_ref()).await {
+            Ok(()) => check_parent(todos, body.parent_id.as_ref()).await,
+            Err(message) => Err(message),
+        };
+        links.map_err(|message| ItemError::new(StatusCode::BAD_REQUEST, message))?;
+        if let Some(parent_id) = body.parent_id.as_deref() {
+            if creates_parent_cycle(todos, &id, parent_id).await {
+                return Err(ItemError::new(
+                    StatusCode::CONFLICT,
+                    format!("Todo cannot be a subtask of its own subtask {}", parent_id),
+                ));
+            }
+        }
+
+        todo.apply_update(body, now);
+        Ok(Planned::Update(id, todo))
+    }
+
+    #[utoipa::path(
+        patch,
+        path = "/api/todos/bulk",
+        tag = "todos",
+        params(BulkParams, CompletionParams),
+        request_body = Vec<BulkTodoPatch>,
+        responses(
+            (status = 200, description = "Every todo updated", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch or repeated id", body = ErrorResponse),
+            (status = 404, description = "A todo was missing, nothing was updated", body = BulkResponse),
+            (status = 409, description = "A parent still had open subtasks, nothing was updated", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+            (status = 422, description = "An item failed validation, nothing was updated", body = BulkResponse),
+        )
+    )]
+    pub async fn bulk_update_todos(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Query(completion): Query<CompletionParams>,
+        Json(body): Json<Vec<BulkTodoPatch>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.todos();
+
+        // Subtasks completed by the same request do not hold their parent back
+        let completing = body
+            .iter()
+            .filter(|item| item.completed == Some(true))
+            .map(|item| item.id.clone())
+            .collect::<HashSet<_>>();
+
+        let datetime = Local::now();
+        let unique = bulk::reject_duplicate_ids(body.iter().map(|item| item.id.as_str()));
+        let mut checked = Vec::with_capacity(body.len());
+        for (item, unique) in body.into_iter().zip(unique) {
+            let mut check = unique.and_then(|_| bulk::validate(&item));
+            if check.is_ok() && item.completed == Some(true) && !completion.force {
+                let open = open_subtasks(repository.as_ref(), &item.id)
+                    .await
+                    .into_iter()
+                    .filter(|subtask| {
+                        let id = subtask.id.as_ref().map(|id| id.id.to_raw());
+                        !id.is_some_and(|id| completing.contains(&id))
+                    })
+                    .count();
+                if open > 0 {
+                    check = Err(ItemError::new(
+                        StatusCode::CONFLICT,
+                        format!("Todo has {} open subtasks", open),
+                    ));
+                }
+            }
+            checked.push(match check {
+                Ok(()) => Ok(todo_patch(repository.as_ref(), item, datetime).await),
+                Err(e) => Err(e),
+            });
+        }
+
+        match bulk::execute(params.mode, checked, |patches, atomic| {
+            repository.merge_many(patches, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_UPDATE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_UPDATE)),
+        }
+    }
+
+    /// Builds the patch for one bulk update item. Flipping `completed` or moving
+    /// `remind_at` depends on the stored todo, so those read it first, and
+    /// completing a recurring todo carries its next occurrence along.
+    async fn todo_patch(
+        repository: &dyn TodoStore,
+        item: BulkTodoPatch,
+        datetime: DateTime<Local>,
+    ) -> (String, TodoPatch) {
+        let mut patch = TodoPatch {
+            title: item.title.clone(),
+            content: item.content.clone(),
+            completed: item.completed,
+            priority: item.priority,
+            due_at: item.due_at,
+            remind_at: item.remind_at,
+            updated_at: Some(datetime),
+            ..TodoPatch::default()
+        };
+        if item.completed.is_some() || item.remind_at.is_some() {
+            if let Ok(mut current) = repository.get_by_id(item.id.clone()).await {
+                patch.completed_at =
+                    completed_at_change(current.completed, item.completed, datetime);
+                if item.remind_at.is_some() && item.remind_at != current.remind_at {
+                    patch.reminder_fired_at = Some(None);
+                }
+
+                let was_completed = current.completed == Some(true);
+                current.apply_update(
+                    UpdateTodo {
+                        title: item.title,
+                        content: item.content,
+                        completed: item.completed,
+                        priority: item.priority,
+                        due_at: item.due_at,
+                        remind_at: item.remind_at,
+                        ..UpdateTodo::default()
+                    },
+                    datetime,
+                );
+                if let Some(next) = current.link_next_occurrence(was_completed, datetime) {
+                    patch.next_occurrence = next.id.clone();
+                    patch.next = Some(next);
+                }
+            }
+        }
+        (item.id, patch)
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/todos/bulk",
+        tag = "todos",
+        params(BulkParams),
+        request_body(content = Vec<String>, description = "Record ids to delete"),
+        responses(
+            (status = 200, description = "Every todo deleted", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch or repeated id", body = ErrorResponse),
+            (status = 404, description = "A todo was missing, nothing was deleted", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+        )
+    )]
+    pub async fn bulk_delete_todos(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<String>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.todos();
+
+        let unique = bulk::reject_duplicate_ids(body.iter().map(String::as_str));
+        let checked = body
+            .into_iter()
+            .zip(unique)
+            .map(|(id, unique)| unique.map(|_| id))
+            .collect();
+
+        match bulk::execute(params.mode, checked, |ids, atomic| {
+            repository.delete_many(ids, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_DELETE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_DELETE)),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::config::Config;
+        use crate::data::data_context::DataContext;
+        use axum::body::to_bytes;
+        use axum::response::Response;
+
+        fn state() -> AppState {
+            AppState::new(DataContext::in_memory(), Config::from_env())
+        }
+
+        async fn body_json(response: Response) -> serde_json::Value {
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        async fn seed_todo(state: &AppState, title: &str) -> String {
+            let body = CreateTodo {
+                title: title.to_string(),
+                content: Some("First draft".to_string()),
+                ..Default::default()
+            };
+            let response = create_todo(State(state.clone()), Json(body))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::CREATED);
+
+            let todos = state.data.todos().get_all().await.unwrap();
+            let todo = todos.iter().find(|todo| todo.title == title).unwrap();
+            todo.id.as_ref().unwrap().id.to_raw()
+        }
+
+        #[tokio::test]
+        async fn update_todo_merges_partial_fields() {
+            let state = state();
+            let id = seed_todo(&state, "Write report").await;
+
+            let body = UpdateTodo {
+                completed: Some(true),
+                ..Default::default()
+            };
+            let response = update_todo(
+                State(state.clone()),
+                Path(id),
+                Query(CompletionParams::default()),
+                Json(body),
+            )
+            .await
+            .into_response();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = body_json(response).await;
+            assert_eq!(json["todo"]["title"], "Write report");
+            assert_eq!(json["todo"]["content"], "First draft");
+            assert_eq!(json["todo"]["completed"], true);
+            assert!(!json["todo"]["completed_at"].is_null());
+            assert!(!json["todo"]["updated_at"].is_null());
+        }
+
+        #[tokio::test]
+        async fn update_todo_returns_404_for_missing_todo() {
+            let body = UpdateTodo {
+                title: Some("Renamed".to_string()),
+                ..Default::default()
+            };
+            let response = update_todo(
+                State(state()),
+                Path("missing".to_string()),
+                Query(CompletionParams::default()),
+                Json(body),
+            )
+            .await
+            .into_response();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            assert_eq!(body_json(response).await["status"], "error");
+        }
+
+        #[tokio::test]
+        async fn search_todos_matches_whole_words() {
+            let state = state();
+            seed_todo(&state, "Write report").await;
+            seed_todo(&state, "Reporter interview").await;
+
+            let params = SearchParams {
+                q: "REPORT".to_string(),
+                page: 1,
+                per_page: 20,
+            };
+            let response = search_todos(State(state), Query(params))
+                .await
+                .into_response();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            let json = body_json(response).await;
+            assert_eq!(json["total"], 1);
+            assert_eq!(
+                json["results"][0]["highlights"]["title"],
+                "Write <mark>report</mark>"
+            );
+        }
+
+        #[tokio::test]
+        async fn atomic_bulk_delete_leaves_todos_when_one_is_missing() {
+            let state = state();
+            let id = seed_todo(&state, "Write report").await;
+
+            let params = BulkParams::default();
+            let body = vec![id.clone(), "missing".to_string()];
+            let response = bulk_delete_todos(State(state.clone()), Query(params), Json(body))
+                .await
+                .into_response();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let json = body_json(response).await;
+            assert_eq!(json["results"][0]["status"], 424);
+            assert!(state.data.todos().get_by_id(id).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn get_and_delete_return_404_for_missing_todo() {
+            let state = state();
+
+            let response = get_todo_by_id(
+                State(state.clone()),
+                Path("missing".to_string()),
+                Query(AsOfParams { as_of: None }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+            let response = delete_todo(State(state), Path("missing".to_string()))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
 }
@@ -0,0 +1,225 @@
+use crate::data::models::transfer::TransferFormat;
+use crate::data::transfer::{self, Transfer};
+use crate::routers::bulk::ItemError;
+use crate::routers::streaming;
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{BoxError, Json};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::future::Future;
+
+/// Largest number of rows accepted by one import.
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+
+/// Import rows as read, each either parsed or rejected.
+pub type Rows<R> = Vec<Result<R, ItemError>>;
+
+/// What an import row does to the store.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+}
+
+/// A valid import row, ready to write.
+pub enum Planned<T> {
+    Create(T),
+    /// Replace the record with the given id
+    Update(String, T),
+}
+
+impl<T> Planned<T> {
+    fn action(&self) -> ImportAction {
+        match self {
+            Planned::Create(_) => ImportAction::Create,
+            Planned::Update(..) => ImportAction::Update,
+        }
+    }
+}
+
+/// Streams every record as CSV or NDJSON, a page at a time, so memory stays
+/// flat however large the table is. A store error cuts the download short.
+pub fn export<T, F, Fut>(format: TransferFormat, filename: &str, fetch: F) -> Response
+where
+    T: Transfer + Send + 'static,
+    F: Fn(Option<String>, usize) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<T>, surrealdb::Error>> + Send + 'static,
+{
+    let header = stream::iter(transfer::header(format, T::COLUMNS))
+        .map(|line| Ok::<_, BoxError>(Bytes::from(line)));
+    let rows = streaming::pages(fetch, T::key).map_ok(move |records| {
+        let rows = records
+            .iter()
+            .map(|record| transfer::encode(format, T::COLUMNS, &record.to_row()))
+            .collect::<String>();
+        Bytes::from(rows)
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}.{}\"",
+                    filename,
+                    format.extension()
+                ),
+            ),
+        ],
+        Body::from_stream(header.chain(rows)),
+    )
+        .into_response()
+}
+
+/// The format of an import body: `?format=` when given, else the
+/// `Content-Type` header.
+pub fn import_format(
+    format: Option<TransferFormat>,
+    headers: &HeaderMap,
+) -> Result<TransferFormat, (StatusCode, Json<serde_json::Value>)> {
+    format.or_else(|| body_format(headers)).ok_or_else(|| {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Send text/csv or application/x-ndjson, or pass format=csv|ndjson"
+            })),
+        )
+    })
+}
+
+/// The transfer format named by the `Content-Type` header, if any.
+pub fn body_format(headers: &HeaderMap) -> Option<TransferFormat> {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(TransferFormat::from_content_type)
+}
+
+/// Reads the rows of an import, rejecting unreadable and oversized files.
+pub fn decode<T: Transfer>(
+    format: TransferFormat,
+    body: &str,
+) -> Result<Rows<T::Row>, (StatusCode, Json<serde_json::Value>)> {
+    let rows = transfer::decode::<T::Row>(format, T::COLUMNS, body).map_err(|message| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "status": "error", "message": message })),
+        )
+    })?;
+    if rows.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Import contains no rows"
+            })),
+        ));
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Import exceeds {} rows", MAX_IMPORT_ROWS)
+            })),
+        ));
+    }
+    Ok(rows
+        .into_iter()
+        .map(|row| row.map_err(|message| ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, message)))
+        .collect())
+}
+
+/// Writes each planned row in turn, unless `dry_run`, and reports every row:
+/// `200 OK` when all of them succeed, `422` when none does, and
+/// `207 Multi-Status` otherwise. Rows are not applied all-or-nothing.
+pub async fn import<T, F, Fut>(
+    dry_run: bool,
+    planned: Vec<Result<Planned<T>, ItemError>>,
+    write: F,
+) -> (StatusCode, Json<serde_json::Value>)
+where
+    T: Transfer,
+    F: Fn(Planned<T>) -> Fut,
+    Fut: Future<Output = Result<T, surrealdb::Error>>,
+{
+    let (mut created, mut updated) = (0, 0);
+    let mut rows = Vec::with_capacity(planned.len());
+    for (index, plan) in planned.into_iter().enumerate() {
+        let mut row = serde_json::json!({ "row": index + 1 });
+        let result = match plan {
+            Ok(plan) => {
+                let action = plan.action();
+                let id = match &plan {
+                    Planned::Update(id, _) => Some(id.clone()),
+                    Planned::Create(_) => None,
+                };
+                if dry_run {
+                    Ok((action, id))
+                } else {
+                    match write(plan).await {
+                        Ok(record) => Ok((action, record.key())),
+                        Err(e) => {
+                            tracing::error!(error = %e, row = index + 1, "Failed to import row");
+                            Err(ItemError::new(
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                "Failed to import row",
+                            ))
+                        }
+                    }
+                }
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((action, id)) => {
+                let status = match action {
+                    ImportAction::Create => {
+                        created += 1;
+                        StatusCode::CREATED
+                    }
+                    ImportAction::Update => {
+                        updated += 1;
+                        StatusCode::OK
+                    }
+                };
+                row["action"] = serde_json::json!(action);
+                row["status"] = serde_json::json!(status.as_u16());
+                if let Some(id) = id {
+                    row["id"] = serde_json::json!(id);
+                }
+            }
+            Err(e) => {
+                row["status"] = serde_json::json!(e.status.as_u16());
+                row["message"] = serde_json::json!(e.message);
+                if let Some(errors) = e.errors {
+                    row["errors"] = serde_json::json!(errors);
+                }
+            }
+        }
+        rows.push(row);
+    }
+
+    let failed = rows.len() - created - updated;
+    let (status, label) = match failed {
+        0 => (StatusCode::OK, "success"),
+        _ if failed == rows.len() => (StatusCode::UNPROCESSABLE_ENTITY, "error"),
+        _ => (StatusCode::MULTI_STATUS, "partial"),
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "status": label,
+            "dry_run": dry_run,
+            "created": created,
+            "updated": updated,
+            "failed": failed,
+            "rows": rows,
+        })),
+    )
+}
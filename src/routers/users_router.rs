@@ -1,45 +1,156 @@
 pub mod users_router {
-    use crate::data::models::user::{CreateUser, UpdateUser, User};
-    use crate::data::repositories::users_repository::UsersRepository;
-    use crate::db::Database;
-    use axum::extract::Path;
-    use axum::http::StatusCode;
+    use crate::data::contact::{ContactError, ContactNormalizer};
+    use crate::data::models::bulk::BulkParams;
+    use crate::data::models::history::AsOfParams;
+    use crate::data::models::live::LiveParams;
+    use crate::data::models::search::SearchParams;
+    use crate::data::models::stream::StreamParams;
+    use crate::data::models::transfer::{ExportParams, ImportParams, UserRow};
+    use crate::data::models::user::{BulkUserPatch, CreateUser, UpdateUser, User, UserPatch};
+    use crate::data::stores::{RoleStore, UserStore};
+    use crate::data::transfer::Transfer;
+    use crate::routers::bulk::{self, BulkResource, ItemError};
+    use crate::routers::gate;
+    use crate::routers::history;
+    use crate::routers::openapi::{
+        BulkResponse, ErrorResponse, ImportResponse, UserHistoryResponse, UserListResponse,
+        UserResponse, UserSearchResponse,
+    };
+    use crate::live::{Change, Resource};
+    use crate::routers::live;
+    use crate::routers::search;
+    use crate::routers::streaming;
+    use crate::routers::transfer::{self, Planned};
+    use crate::state::AppState;
+    use axum::extract::{Path, Query, State};
+    use axum::http::{HeaderMap, StatusCode};
     use axum::{
-        response::IntoResponse,
+        response::{IntoResponse, Response},
         routing::{get, post},
-        Extension, Json, Router,
+        Json, Router,
+    };
+    use chrono::{DateTime, Local};
+    use std::collections::HashSet;
+    use surrealdb::sql::Thing;
+
+    const BULK_CREATE: BulkResource = BulkResource {
+        key: "user",
+        name: "User",
+        action: "create",
+        success: StatusCode::CREATED,
+    };
+    const BULK_UPDATE: BulkResource = BulkResource {
+        key: "user",
+        name: "User",
+        action: "update",
+        success: StatusCode::OK,
+    };
+    const BULK_DELETE: BulkResource = BulkResource {
+        key: "user",
+        name: "User",
+        action: "delete",
+        success: StatusCode::OK,
     };
-    use chrono::Local;
-    use std::sync::Arc;
 
-    pub fn router() -> Router {
+    pub fn router() -> Router<AppState> {
         Router::new()
             .route("/", post(create_user).get(get_all_users))
+            .route("/export", get(export_users))
+            .route("/live", get(live_users))
+            .route("/import", post(import_users))
+            .route(
+                "/bulk",
+                post(bulk_create_users)
+                    .patch(bulk_update_users)
+                    .delete(bulk_delete_users),
+            )
             .route(
                 "/:id",
                 get(get_user_by_id).put(update_user).delete(delete_user),
             )
+            .route("/:id/history", get(get_user_history))
             .route("/email/:email", get(get_user_by_email))
             .route("/phone/:phone", get(get_user_by_phone))
+            .route("/search", get(search_users))
     }
 
-    pub async fn get_all_users(Extension(db): Extension<Arc<Database>>) -> impl IntoResponse {
-        let repository = UsersRepository::new(db);
+    #[utoipa::path(
+        get,
+        path = "/api/users",
+        tag = "users",
+        params(StreamParams),
+        responses(
+            (status = 200, description = "List all users, or stream them", content(
+                (UserListResponse = "application/json"),
+                (User = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn get_all_users(
+        State(state): State<AppState>,
+        Query(params): Query<StreamParams>,
+    ) -> Response {
+        let repository = state.data.users();
+        if let Some(format) = params.stream {
+            return streaming::respond(
+                format,
+                move |after, limit| {
+                    let repository = repository.clone();
+                    async move { repository.page(after, limit).await }
+                },
+                User::key,
+            );
+        }
+
         let users = repository.get_all().await.unwrap_or_default();
         Json(serde_json::json!({
             "status": "success",
             "count": users.len(),
             "users": users
         }))
+        .into_response()
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/users/{id}",
+        tag = "users",
+        params(("id" = String, Path, description = "Record id"), AsOfParams),
+        responses(
+            (status = 200, description = "User found; with `as_of`, the UserVersion current at that time instead", body = User),
+            (status = 404, description = "User not found, did not exist at `as_of`, or `as_of` given while USER_HISTORY_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
     pub async fn get_user_by_id(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
+        Query(params): Query<AsOfParams>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = UsersRepository::new(db);
+        let repository = state.data.users();
+        if let Some(at) = params.as_of {
+            gate::require(state.config.user_history_api)?;
+            return match repository.as_of(id.clone(), at).await {
+                Ok(Some(version)) if version.record.is_some() => {
+                    Ok((StatusCode::OK, Json(history::entry("user", version))))
+                }
+                Ok(_) => Err((
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": format!("User with ID: {} did not exist at {}", id, at.to_rfc3339())
+                    })),
+                )),
+                Err(_) => Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": "Failed to fetch user history"
+                    })),
+                )),
+            };
+        }
         match repository.get_by_id(id.clone()).await {
-            Ok(user) => Ok((StatusCode::OK, Json(user))),
+            Ok(user) => Ok((StatusCode::OK, Json(serde_json::json!(user)))),
             Err(_) => Err((
                 StatusCode::NOT_FOUND,
                 Json(serde_json::json!({
@@ -50,11 +161,21 @@ pub mod users_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/users/email/{email}",
+        tag = "users",
+        params(("email" = String, Path, description = "Exact email address")),
+        responses(
+            (status = 200, description = "User found", body = User),
+            (status = 404, description = "User not found", body = ErrorResponse),
+        )
+    )]
     pub async fn get_user_by_email(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(email): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = UsersRepository::new(db);
+        let repository = state.data.users();
         match repository.get_by_email(email.clone()).await {
             Ok(user) => Ok((StatusCode::OK, Json(user))),
             Err(_) => Err((
@@ -67,11 +188,21 @@ pub mod users_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/users/phone/{phone}",
+        tag = "users",
+        params(("phone" = String, Path, description = "Exact phone number")),
+        responses(
+            (status = 200, description = "User found", body = User),
+            (status = 404, description = "User not found", body = ErrorResponse),
+        )
+    )]
     pub async fn get_user_by_phone(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(phone): Path<String>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = UsersRepository::new(db);
+        let repository = state.data.users();
         match repository.get_by_phone(phone.clone()).await {
             Ok(user) => Ok((StatusCode::OK, Json(user))),
             Err(_) => Err((
@@ -84,12 +215,63 @@ pub mod users_router {
         }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/users/search",
+        tag = "users",
+        params(SearchParams),
+        responses(
+            (status = 200, description = "Matching users, best match first", body = UserSearchResponse),
+            (status = 400, description = "Missing query or invalid page", body = ErrorResponse),
+            (status = 500, description = "Search failed", body = ErrorResponse),
+        )
+    )]
+    pub async fn search_users(
+        State(state): State<AppState>,
+        Query(params): Query<SearchParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let request = search::parse(params)?;
+        let repository = state.data.users();
+
+        match repository
+            .search(request.query.clone(), request.start(), request.per_page)
+            .await
+        {
+            Ok(page) => Ok(search::respond("user", &request, page)),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to search users"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/users",
+        tag = "users",
+        request_body = CreateUser,
+        responses(
+            (status = 201, description = "User created", body = UserResponse),
+            (status = 400, description = "User already exists", body = ErrorResponse),
+            (status = 422, description = "Empty name, invalid email, or phone number that cannot be converted to E.164", body = ErrorResponse),
+            (status = 500, description = "Failed to create user", body = ErrorResponse),
+        )
+    )]
     pub async fn create_user(
-        Extension(db): Extension<Arc<Database>>,
-        Json(body): Json<CreateUser>,
+        State(state): State<AppState>,
+        Json(mut body): Json<CreateUser>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = UsersRepository::new(db);
-        
+        // Validate the email as it is stored, so surrounding spaces are fine
+        body.email = state.config.contacts.email(&body.email);
+        bulk::validate(&body)?;
+        let repository = state.data.users();
+        if let Err(e) = state.config.contacts.optional_phone(body.phone.as_deref()) {
+            return Err(invalid_contact(e));
+        }
+
         match repository.get_by_email(body.email.clone()).await {
             Ok(user) => {
                 let json_response = serde_json::json!({
@@ -97,7 +279,7 @@ pub mod users_router {
                     "message": "User already exists",
                     "user": user,
                 });
-                return Err((StatusCode::BAD_REQUEST, Json(json_response)));
+                Err((StatusCode::BAD_REQUEST, Json(json_response)))
             }
             Err(_) => {
                 let datetime = Local::now();
@@ -148,12 +330,40 @@ pub mod users_router {
     }
 
 
+    #[utoipa::path(
+        put,
+        path = "/api/users/{id}",
+        tag = "users",
+        params(("id" = String, Path, description = "Record id")),
+        request_body = UpdateUser,
+        responses(
+            (status = 200, description = "User updated", body = UserResponse),
+            (status = 400, description = "Email belongs to another user", body = ErrorResponse),
+            (status = 404, description = "User not found", body = ErrorResponse),
+            (status = 422, description = "Phone number cannot be converted to E.164", body = ErrorResponse),
+            (status = 500, description = "Failed to update user", body = ErrorResponse),
+        )
+    )]
     pub async fn update_user(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
         Json(body): Json<UpdateUser>,
     ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-        let repository = UsersRepository::new(db);
+        let repository = state.data.users();
+        if let Err(e) = state.config.contacts.optional_phone(body.phone.as_deref()) {
+            return Err(invalid_contact(e));
+        }
+        if let Ok(existing) = repository.get_by_email(body.email.clone()).await {
+            if existing.id.map(|thing| thing.id.to_raw()) != Some(id.clone()) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "status": "error",
+                        "message": "Email already in use"
+                    })),
+                ));
+            }
+        }
 
         match repository.get_by_id(id.clone()).await {
             Ok(mut user) => {
@@ -176,7 +386,7 @@ pub mod users_router {
                         StatusCode::INTERNAL_SERVER_ERROR,
                         Json(serde_json::json!({
                             "status": "error",
-                            "message": "Failed to update todo"
+                            "message": "Failed to update user"
                         })),
                     )),
                 }
@@ -185,7 +395,7 @@ pub mod users_router {
                 StatusCode::NOT_FOUND,
                 Json(serde_json::json!({
                     "status": "error",
-                    "message": format!("Todo with ID: {} not found", id)
+                    "message": format!("User with ID: {} not found", id)
                 })),
             )),
         }
@@ -209,11 +419,57 @@ pub mod users_router {
         // }
     }
 
+    #[utoipa::path(
+        get,
+        path = "/api/users/{id}/history",
+        tag = "users",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Every version of the user, newest first, also once it is deleted", body = UserHistoryResponse),
+            (status = 404, description = "User never existed, or USER_HISTORY_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_user_history(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.user_history_api)?;
+        match state.data.users().history(id.clone()).await {
+            Ok(versions) if !versions.is_empty() => {
+                Ok((StatusCode::OK, history::respond("user", versions)))
+            }
+            Ok(_) => Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("User with ID: {} not found", id)
+                })),
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to fetch user history"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/users/{id}",
+        tag = "users",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 204, description = "User deleted"),
+            (status = 404, description = "User not found", body = ErrorResponse),
+        )
+    )]
     pub async fn delete_user(
-        Extension(db): Extension<Arc<Database>>,
+        State(state): State<AppState>,
         Path(id): Path<String>,
     ) -> impl IntoResponse {
-        let repository = UsersRepository::new(db);
+        let repository = state.data.users();
         match repository.delete(id.clone()).await {
             Ok(_) => (
                 StatusCode::NO_CONTENT,
@@ -231,4 +487,458 @@ pub mod users_router {
             ),
         }
     }
+
+    #[utoipa::path(
+        post,
+        path = "/api/users/bulk",
+        tag = "users",
+        params(BulkParams),
+        request_body = Vec<CreateUser>,
+        responses(
+            (status = 201, description = "Every user created", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch, or an email already in use", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+            (status = 422, description = "An item failed validation, nothing was created", body = BulkResponse),
+        )
+    )]
+    pub async fn bulk_create_users(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<CreateUser>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.users();
+
+        let datetime = Local::now();
+        let mut emails = HashSet::new();
+        let mut checked = Vec::with_capacity(body.len());
+        for item in body {
+            checked.push(
+                check_new_user(repository.as_ref(), &state.config.contacts, &mut emails, item, datetime)
+                    .await,
+            );
+        }
+
+        match bulk::execute(params.mode, checked, |users, atomic| {
+            repository.create_many(users, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_CREATE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_CREATE)),
+        }
+    }
+
+    /// Applies the same checks as `create_user`, plus rejecting an email that
+    /// appears earlier in the same batch.
+    async fn check_new_user(
+        repository: &dyn UserStore,
+        contacts: &ContactNormalizer,
+        emails: &mut HashSet<String>,
+        mut item: CreateUser,
+        datetime: DateTime<Local>,
+    ) -> Result<User, ItemError> {
+        item.email = contacts.email(&item.email);
+        bulk::validate(&item)?;
+        contacts
+            .optional_phone(item.phone.as_deref())
+            .map_err(|e| ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+        if !emails.insert(item.email.clone()) {
+            return Err(ItemError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate email: {} in request", item.email),
+            ));
+        }
+        if repository.get_by_email(item.email.clone()).await.is_ok() {
+            return Err(ItemError::new(StatusCode::BAD_REQUEST, "User already exists"));
+        }
+
+        Ok(User {
+            id: None,
+            name: item.name,
+            email: item.email,
+            phone: item.phone,
+            role: item.role,
+            created_at: Some(datetime),
+            updated_at: Some(datetime),
+        })
+    }
+
+    /// Applies the same check as `update_user` to a changed email, plus
+    /// rejecting one that appears earlier in the same batch.
+    async fn check_patched_email(
+        repository: &dyn UserStore,
+        emails: &mut HashSet<String>,
+        id: &str,
+        email: &str,
+    ) -> Result<(), ItemError> {
+        if !emails.insert(email.to_string()) {
+            return Err(ItemError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate email: {} in request", email),
+            ));
+        }
+        let owner = repository.get_by_email(email.to_string()).await.ok();
+        if owner.is_some_and(|user| user.id.map(|thing| thing.id.to_raw()).as_deref() != Some(id)) {
+            return Err(ItemError::new(StatusCode::BAD_REQUEST, "Email already in use"));
+        }
+        Ok(())
+    }
+
+    #[utoipa::path(
+        patch,
+        path = "/api/users/bulk",
+        tag = "users",
+        params(BulkParams),
+        request_body = Vec<BulkUserPatch>,
+        responses(
+            (status = 200, description = "Every user updated", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch or repeated id, or an email in use or repeated, nothing was updated", body = BulkResponse),
+            (status = 404, description = "A user was missing, nothing was updated", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+            (status = 422, description = "An item failed validation, nothing was updated", body = BulkResponse),
+        )
+    )]
+    pub async fn bulk_update_users(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<BulkUserPatch>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.users();
+
+        let datetime = Local::now();
+        let contacts = &state.config.contacts;
+        let unique = bulk::reject_duplicate_ids(body.iter().map(|item| item.id.as_str()));
+        let mut checked: Vec<Result<(String, UserPatch), ItemError>> = body
+            .into_iter()
+            .zip(unique)
+            .map(|(mut item, unique)| {
+                unique?;
+                item.email = item.email.map(|email| contacts.email(&email));
+                bulk::validate(&item)?;
+                if let Some(phone) = &item.phone {
+                    contacts.optional_phone(Some(phone)).map_err(|e| {
+                        ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+                    })?;
+                }
+                let patch = UserPatch {
+                    name: item.name,
+                    email: item.email,
+                    phone: item.phone,
+                    role: item.role,
+                    updated_at: Some(datetime),
+                };
+                Ok((item.id, patch))
+            })
+            .collect();
+        let mut emails = HashSet::new();
+        for item in checked.iter_mut() {
+            if let Ok((id, UserPatch { email: Some(email), .. })) = item {
+                let checked_email =
+                    check_patched_email(repository.as_ref(), &mut emails, id, email).await;
+                if let Err(e) = checked_email {
+                    *item = Err(e);
+                }
+            }
+        }
+
+        match bulk::execute(params.mode, checked, |patches, atomic| {
+            repository.merge_many(patches, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_UPDATE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_UPDATE)),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/users/bulk",
+        tag = "users",
+        params(BulkParams),
+        request_body(content = Vec<String>, description = "Record ids to delete"),
+        responses(
+            (status = 200, description = "Every user deleted", body = BulkResponse),
+            (status = 207, description = "Per-item results of a partial batch", body = BulkResponse),
+            (status = 400, description = "Empty batch or repeated id", body = ErrorResponse),
+            (status = 404, description = "A user was missing, nothing was deleted", body = BulkResponse),
+            (status = 413, description = "Too many items", body = ErrorResponse),
+        )
+    )]
+    pub async fn bulk_delete_users(
+        State(state): State<AppState>,
+        Query(params): Query<BulkParams>,
+        Json(body): Json<Vec<String>>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        bulk::check_size(&body)?;
+        let repository = state.data.users();
+
+        let unique = bulk::reject_duplicate_ids(body.iter().map(String::as_str));
+        let checked = body
+            .into_iter()
+            .zip(unique)
+            .map(|(id, unique)| unique.map(|_| id))
+            .collect();
+
+        match bulk::execute(params.mode, checked, |ids, atomic| {
+            repository.delete_many(ids, atomic)
+        })
+        .await
+        {
+            Ok(results) => Ok(bulk::respond(&BULK_DELETE, params.mode, results)),
+            Err(_) => Err(bulk::store_error(&BULK_DELETE)),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/users/export",
+        tag = "users",
+        params(ExportParams),
+        responses(
+            (status = 200, description = "Every user, one row each", content(
+                (String = "text/csv"),
+                (String = "application/x-ndjson"),
+            )),
+        )
+    )]
+    pub async fn export_users(
+        State(state): State<AppState>,
+        Query(params): Query<ExportParams>,
+    ) -> Response {
+        let repository = state.data.users();
+        transfer::export(params.format, "users", move |after, limit| {
+            let repository = repository.clone();
+            async move { repository.page(after, limit).await }
+        })
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/users/live",
+        tag = "users",
+        params(LiveParams),
+        responses(
+            (status = 200, description = "Server-Sent Events named create, update, delete or reset; each change carries its id for resuming", content(
+                (Change = "text/event-stream"),
+            )),
+            (status = 400, description = "Invalid Last-Event-ID", body = ErrorResponse),
+            (status = 404, description = "USER_LIVE_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn live_users(
+        State(state): State<AppState>,
+        Query(params): Query<LiveParams>,
+        headers: HeaderMap,
+    ) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.user_live_api)?;
+        let after = live::resume_from(&headers, params.last_event_id)?;
+        Ok(live::sse(&state, Resource::User, after))
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/users/import",
+        tag = "users",
+        params(ImportParams),
+        request_body(
+            description = "CSV or NDJSON rows as exported. A row updates the user with the same email, or creates one.",
+            content((String = "text/csv"), (String = "application/x-ndjson"))
+        ),
+        responses(
+            (status = 200, description = "Every row imported", body = ImportResponse),
+            (status = 207, description = "Some rows failed", body = ImportResponse),
+            (status = 400, description = "The file is unreadable or empty", body = ErrorResponse),
+            (status = 413, description = "Too many rows", body = ErrorResponse),
+            (status = 415, description = "Unknown format", body = ErrorResponse),
+            (status = 422, description = "No row was valid", body = ImportResponse),
+        )
+    )]
+    pub async fn import_users(
+        State(state): State<AppState>,
+        Query(params): Query<ImportParams>,
+        headers: HeaderMap,
+        body: String,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        let format = transfer::import_format(params.format, &headers)?;
+        let rows = transfer::decode::<User>(format, &body)?;
+        let repository = state.data.users();
+        let roles = state.data.roles();
+
+        let mut seen = HashSet::new();
+        let mut planned = Vec::with_capacity(rows.len());
+        for row in rows {
+            planned.push(match row {
+                Ok(row) => {
+                    plan_row(
+                        repository.as_ref(),
+                        roles.as_ref(),
+                        &state.config.contacts,
+                        &mut seen,
+                        row,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            });
+        }
+
+        Ok(transfer::import(params.dry_run, planned, |plan| async {
+            match plan {
+                Planned::Create(user) => repository.create(user).await,
+                Planned::Update(id, user) => repository.update(id, user).await,
+            }
+        })
+        .await)
+    }
+
+    /// Matches an import row to the stored user with the same email, if any.
+    /// Empty `phone` and `role_id` cells keep a matched user's values.
+    async fn plan_row(
+        users: &dyn UserStore,
+        roles: &dyn RoleStore,
+        contacts: &ContactNormalizer,
+        seen: &mut HashSet<String>,
+        row: UserRow,
+    ) -> Result<Planned<User>, ItemError> {
+        bulk::validate(&row)?;
+        contacts
+            .optional_phone(row.phone.as_deref())
+            .map_err(|e| ItemError::new(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+        let email = contacts.email(&row.email);
+        if !seen.insert(email.clone()) {
+            return Err(ItemError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Duplicate email: {} in import", email),
+            ));
+        }
+        if let Some(role_id) = &row.role_id {
+            if roles.get_by_id(role_id.clone()).await.is_err() {
+                return Err(ItemError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Role with ID: {} not found", role_id),
+                ));
+            }
+        }
+
+        let role = row
+            .role_id
+            .as_deref()
+            .map(|role_id| Thing::from(("role", role_id)));
+        Ok(match users.get_by_email(email).await {
+            Ok(user) => Planned::Update(
+                user.key().unwrap_or_default(),
+                User {
+                    name: row.name,
+                    email: row.email,
+                    phone: row.phone.or(user.phone),
+                    role: role.or(user.role),
+                    ..user
+                },
+            ),
+            Err(_) => Planned::Create(User {
+                id: None,
+                name: row.name,
+                email: row.email,
+                phone: row.phone,
+                role,
+                created_at: None,
+                updated_at: None,
+            }),
+        })
+    }
+
+    fn invalid_contact(error: ContactError) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": error.to_string()
+            })),
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::config::Config;
+        use crate::data::data_context::DataContext;
+        use axum::body::to_bytes;
+        use axum::response::Response;
+
+        fn state() -> AppState {
+            AppState::new(DataContext::in_memory(), Config::from_env())
+        }
+
+        async fn body_json(response: Response) -> serde_json::Value {
+            let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice(&bytes).unwrap()
+        }
+
+        fn create_body(email: &str) -> CreateUser {
+            CreateUser {
+                name: "Alice".to_string(),
+                email: email.to_string(),
+                phone: Some("+15555550100".to_string()),
+                role: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn create_user_rejects_duplicate_email() {
+            let state = state();
+
+            let body = create_body("alice@example.com");
+            let response = create_user(State(state.clone()), Json(body))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::CREATED);
+
+            let body = create_body("alice@example.com");
+            let response = create_user(State(state.clone()), Json(body))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(body_json(response).await["message"], "User already exists");
+            assert_eq!(state.data.users().get_all().await.unwrap().len(), 1);
+        }
+
+        #[tokio::test]
+        async fn lookups_return_404_for_missing_user() {
+            let state = state();
+
+            let response = get_user_by_id(
+                State(state.clone()),
+                Path("missing".to_string()),
+                Query(AsOfParams { as_of: None }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+            let response = get_user_by_email(State(state), Path("nobody@example.com".to_string()))
+                .await
+                .into_response();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        #[tokio::test]
+        async fn update_user_returns_404_for_missing_user() {
+            let body = UpdateUser {
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string(),
+                phone: None,
+                role: None,
+            };
+            let response = update_user(State(state()), Path("missing".to_string()), Json(body))
+                .await
+                .into_response();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
 }
@@ -0,0 +1,377 @@
+pub mod webhooks_router {
+    use crate::data::models::webhook::{
+        CreateWebhook, DeliveryFilter, DeliveryParams, UpdateWebhook, Webhook, MAX_DELIVERY_PAGE,
+    };
+    use crate::routers::gate;
+    use crate::routers::openapi::{
+        DeliveryListResponse, DeliveryResponse, ErrorResponse, WebhookListResponse, WebhookResponse,
+    };
+    use crate::routers::search::page_start;
+    use crate::state::AppState;
+    use crate::webhooks;
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use axum::{
+        response::IntoResponse,
+        routing::{get, post},
+        Json, Router,
+    };
+    use chrono::Local;
+    use uuid::Uuid;
+    use validator::{Validate, ValidationError, ValidationErrors};
+
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", post(create_webhook).get(get_all_webhooks))
+            .route("/deliveries", get(get_all_deliveries))
+            .route("/deliveries/:delivery_id/retry", post(retry_delivery))
+            .route(
+                "/:id",
+                get(get_webhook_by_id)
+                    .put(update_webhook)
+                    .delete(delete_webhook),
+            )
+            .route("/:id/deliveries", get(get_webhook_deliveries))
+    }
+
+    fn webhook_not_found(id: &str) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Webhook with ID: {} not found", id)
+            })),
+        )
+    }
+
+    fn invalid(errors: ValidationErrors) -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Validation failed",
+                "errors": errors
+            })),
+        )
+    }
+
+    /// Rejects URLs the dispatcher would refuse to send to, as a `url` error.
+    async fn check_url(
+        state: &AppState,
+        url: &str,
+    ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        let allow_private = state.config.webhook_allow_private_targets;
+        webhooks::check_url(url, allow_private)
+            .await
+            .map_err(|message| {
+                let mut errors = ValidationErrors::new();
+                errors.add(
+                    "url",
+                    ValidationError::new("target").with_message(message.into()),
+                );
+                invalid(errors)
+            })
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/webhooks",
+        tag = "webhooks",
+        responses(
+            (status = 200, description = "List all webhooks, without their secrets", body = WebhookListResponse),
+            (status = 404, description = "WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_all_webhooks(
+        State(state): State<AppState>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        let webhooks = state
+            .data
+            .webhooks()
+            .get_all()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(Webhook::redacted)
+            .collect::<Vec<_>>();
+        Ok(Json(serde_json::json!({
+            "status": "success",
+            "count": webhooks.len(),
+            "webhooks": webhooks
+        })))
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/webhooks/{id}",
+        tag = "webhooks",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 200, description = "Webhook found, without its secret", body = Webhook),
+            (status = 404, description = "Webhook not found, or WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_webhook_by_id(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        match state.data.webhooks().get_by_id(id.clone()).await {
+            Ok(webhook) => Ok(Json(webhook.redacted())),
+            Err(_) => Err(webhook_not_found(&id)),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/webhooks",
+        tag = "webhooks",
+        request_body = CreateWebhook,
+        responses(
+            (status = 201, description = "Webhook created; the only response that includes its secret", body = WebhookResponse),
+            (status = 404, description = "WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+            (status = 422, description = "Invalid webhook, or its URL is not http(s) or points at an internal address", body = ErrorResponse),
+            (status = 500, description = "Failed to create webhook", body = ErrorResponse),
+        )
+    )]
+    pub async fn create_webhook(
+        State(state): State<AppState>,
+        Json(body): Json<CreateWebhook>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        body.validate().map_err(invalid)?;
+        check_url(&state, &body.url).await?;
+        let repository = state.data.webhooks();
+
+        let datetime = Local::now();
+        let webhook = Webhook {
+            id: None,
+            url: body.url,
+            secret: Some(
+                body.secret
+                    .unwrap_or_else(|| format!("whsec_{}", Uuid::new_v4().simple())),
+            ),
+            events: body.events,
+            active: body.active.unwrap_or(true),
+            created_at: Some(datetime),
+            updated_at: Some(datetime),
+        };
+        match repository.create(webhook).await {
+            Ok(webhook) => Ok((
+                StatusCode::CREATED,
+                Json(serde_json::json!({
+                    "status": "success",
+                    "webhook": webhook
+                })),
+            )),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to create webhook"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        put,
+        path = "/api/webhooks/{id}",
+        tag = "webhooks",
+        params(("id" = String, Path, description = "Record id")),
+        request_body = UpdateWebhook,
+        responses(
+            (status = 200, description = "Webhook updated", body = WebhookResponse),
+            (status = 404, description = "Webhook not found, or WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+            (status = 422, description = "Invalid webhook, or its URL is not http(s) or points at an internal address", body = ErrorResponse),
+            (status = 500, description = "Failed to update webhook", body = ErrorResponse),
+        )
+    )]
+    pub async fn update_webhook(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Json(body): Json<UpdateWebhook>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        body.validate().map_err(invalid)?;
+        if let Some(url) = &body.url {
+            check_url(&state, url).await?;
+        }
+        let repository = state.data.webhooks();
+
+        let Ok(mut webhook) = repository.get_by_id(id.clone()).await else {
+            return Err(webhook_not_found(&id));
+        };
+        webhook.url = body.url.unwrap_or(webhook.url);
+        webhook.secret = body.secret.or(webhook.secret);
+        webhook.events = body.events.unwrap_or(webhook.events);
+        webhook.active = body.active.unwrap_or(webhook.active);
+        webhook.updated_at = Some(Local::now());
+
+        match repository.update(id, webhook).await {
+            Ok(webhook) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "webhook": webhook.redacted()
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to update webhook"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        delete,
+        path = "/api/webhooks/{id}",
+        tag = "webhooks",
+        params(("id" = String, Path, description = "Record id")),
+        responses(
+            (status = 204, description = "Webhook deleted; its queued deliveries are dead-lettered"),
+            (status = 404, description = "Webhook not found, or WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn delete_webhook(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        match state.data.webhooks().delete(id.clone()).await {
+            Ok(_) => Ok(StatusCode::NO_CONTENT),
+            Err(_) => Err(webhook_not_found(&id)),
+        }
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/webhooks/{id}/deliveries",
+        tag = "webhooks",
+        params(("id" = String, Path, description = "Record id"), DeliveryParams),
+        responses(
+            (status = 200, description = "The webhook's delivery log, newest first, with every attempt", body = DeliveryListResponse),
+            (status = 400, description = "Invalid page", body = ErrorResponse),
+            (status = 404, description = "Webhook not found, or WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_webhook_deliveries(
+        State(state): State<AppState>,
+        Path(id): Path<String>,
+        Query(params): Query<DeliveryParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        if state.data.webhooks().get_by_id(id.clone()).await.is_err() {
+            return Err(webhook_not_found(&id));
+        }
+        list_deliveries(&state, Some(id), params).await
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/webhooks/deliveries",
+        tag = "webhooks",
+        params(DeliveryParams),
+        responses(
+            (status = 200, description = "Deliveries to every webhook, newest first; filter on status=dead for the dead letters", body = DeliveryListResponse),
+            (status = 400, description = "Invalid page", body = ErrorResponse),
+            (status = 404, description = "WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn get_all_deliveries(
+        State(state): State<AppState>,
+        Query(params): Query<DeliveryParams>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        list_deliveries(&state, None, params).await
+    }
+
+    async fn list_deliveries(
+        state: &AppState,
+        webhook: Option<String>,
+        params: DeliveryParams,
+    ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+        if params.page == 0 {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Query parameter page starts at 1"
+                })),
+            ));
+        }
+        let per_page = params.per_page.clamp(1, MAX_DELIVERY_PAGE);
+        let Some(start) = page_start(params.page, per_page) else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Query parameter page is too large"
+                })),
+            ));
+        };
+        let filter = DeliveryFilter {
+            webhook,
+            status: params.status,
+        };
+
+        match state
+            .data
+            .webhooks()
+            .deliveries(filter, start, per_page)
+            .await
+        {
+            Ok(page) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "page": params.page,
+                "per_page": per_page,
+                "total": page.total,
+                "count": page.deliveries.len(),
+                "deliveries": page.deliveries,
+            }))),
+            Err(_) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": "Failed to read the delivery log"
+                })),
+            )),
+        }
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/webhooks/deliveries/{delivery_id}/retry",
+        tag = "webhooks",
+        params(("delivery_id" = String, Path, description = "Delivery record id")),
+        responses(
+            (status = 200, description = "Delivery queued again, due now; its attempts so far are kept", body = DeliveryResponse),
+            (status = 404, description = "Delivery not found, or WEBHOOKS_API_ENABLED is off", body = ErrorResponse),
+        )
+    )]
+    pub async fn retry_delivery(
+        State(state): State<AppState>,
+        Path(delivery_id): Path<String>,
+    ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+        gate::require(state.config.webhooks_api)?;
+        match state
+            .data
+            .webhooks()
+            .redeliver(delivery_id.clone(), Local::now())
+            .await
+        {
+            Ok(delivery) => Ok(Json(serde_json::json!({
+                "status": "success",
+                "delivery": delivery
+            }))),
+            Err(_) => Err((
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Delivery with ID: {} not found", delivery_id)
+                })),
+            )),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::config::Config;
+use crate::data::data_context::DataContext;
+use crate::events::EventBus;
+use crate::live::ChangeFeed;
+use std::sync::Arc;
+
+/// Shared state handed to every router via `Router::with_state`.
+#[derive(Clone, Debug)]
+pub struct AppState {
+    pub data: DataContext,
+    pub config: Arc<Config>,
+    pub events: EventBus,
+    pub changes: ChangeFeed,
+}
+
+impl AppState {
+    pub fn new(data: DataContext, config: Config) -> Self {
+        AppState {
+            data,
+            config: Arc::new(config),
+            events: EventBus::default(),
+            changes: ChangeFeed::default(),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::middleware::request_id::RequestId;
+#[cfg(feature = "otel")]
+use crate::otel;
+use axum::extract::MatchedPath;
+use axum::http::{Request, Response};
+use std::env;
+use std::time::Duration;
+use tower_http::classify::{ServerErrorsAsFailures, SharedClassifier};
+use tower_http::trace::{DefaultOnRequest, MakeSpan, OnResponse, TraceLayer};
+use tracing::{field, Span};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Installs the global tracing subscriber.
+///
+/// The filter is read from `RUST_LOG` and the output format from `LOG_FORMAT`
+/// (`json` or `pretty`, defaulting to `pretty`). With the `otel` feature, spans
+/// are also exported over OTLP; keep the returned guard alive until shutdown so
+/// buffered spans get flushed.
+pub fn init() -> TelemetryGuard {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("info,tower_http=info"));
+    let format = env::var("LOG_FORMAT").unwrap_or("pretty".to_string());
+
+    #[cfg(feature = "otel")]
+    let provider = otel::tracer_provider(&otel::OtelConfig::from_env())
+        .map_err(|e| eprintln!("Failed to build OTLP exporter, traces will not be exported: {e}"))
+        .ok();
+
+    let registry = tracing_subscriber::registry().with(filter);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(provider.as_ref().map(otel::layer));
+
+    match format.as_str() {
+        "json" => registry
+            .with(tracing_subscriber::fmt::layer().json().flatten_event(true))
+            .init(),
+        _ => registry
+            .with(tracing_subscriber::fmt::layer().pretty())
+            .init(),
+    }
+
+    TelemetryGuard {
+        #[cfg(feature = "otel")]
+        provider,
+    }
+}
+
+/// Flushes exported spans when dropped.
+#[derive(Debug)]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to flush OTLP spans: {e}");
+            }
+        }
+    }
+}
+
+pub type HttpTraceLayer = TraceLayer<
+    SharedClassifier<ServerErrorsAsFailures>,
+    RequestSpan,
+    DefaultOnRequest,
+    RequestSpan,
+>;
+
+/// Builds the `TraceLayer` that opens one span per request and records the
+/// response status and latency on it.
+pub fn trace_layer() -> HttpTraceLayer {
+    TraceLayer::new_for_http()
+        .make_span_with(RequestSpan)
+        .on_response(RequestSpan)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        // Prefer the route template so `/todos/:id` is one series, not one per id
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_else(|| request.uri().path().to_owned());
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+            .unwrap_or_default();
+
+        let span = tracing::info_span!(
+            "request",
+            method = %request.method(),
+            route = %route,
+            request_id = %request_id,
+            status = field::Empty,
+            latency_ms = field::Empty,
+        );
+        #[cfg(feature = "otel")]
+        otel::set_parent_from_headers(&span, request.headers());
+        span
+    }
+}
+
+impl<B> OnResponse<B> for RequestSpan {
+    fn on_response(self, response: &Response<B>, latency: Duration, span: &Span) {
+        span.record("status", response.status().as_u16());
+        span.record("latency_ms", latency.as_millis() as u64);
+        tracing::info!("finished processing request");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::data::models::webhook::{
+    AttemptOutcome, DeliveryAttempt, DeliveryStatus, Webhook, WebhookDelivery,
+};
+use crate::data::stores::WebhookStore;
+use crate::retry::RetryPolicy;
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use surrealdb::Error;
+use tokio::task::JoinHandle;
+
+/// Most deliveries sent per poll.
+const BATCH: usize = 20;
+
+pub const EVENT_HEADER: &str = "x-webhook-event";
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+/// Retries for deliveries, overridden by the `WEBHOOK_*` variables. The
+/// timeout is how long an endpoint has to respond.
+pub const DEFAULT_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: 8,
+    backoff: Duration::from_secs(30),
+    max_backoff: Duration::from_secs(60 * 60),
+    timeout: Duration::from_secs(10),
+};
+
+/// `sha256=` and the hex HMAC-SHA256 of `{timestamp}.{body}` under `secret`.
+/// Receivers recompute it to check the payload came from us, unchanged, and
+/// can reject old timestamps to stop replays.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether `ip` is an address webhooks must not reach: loopback, private,
+/// shared (carrier-grade NAT), link-local, unspecified, broadcast or
+/// multicast, including IPv4 addresses embedded by NAT64.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                // 100.64.0.0/10
+                || (a == 100 && b & 0xc0 == 64)
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_internal(IpAddr::V4(ip)),
+            // 64:ff9b::/96 goes through a NAT64 gateway on the local network
+            None if ip.segments()[..6] == [0x64, 0xff9b, 0, 0, 0, 0] => true,
+            None => {
+                ip.is_loopback()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+            }
+        },
+    }
+}
+
+/// The first internal address among `addrs`, as an error.
+fn reject_internal(host: &str, addrs: &[SocketAddr]) -> Result<(), String> {
+    match addrs.iter().find(|addr| is_internal(addr.ip())) {
+        Some(addr) => Err(format!("{} points at internal address {}", host, addr.ip())),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `url` is an http or https URL whose host resolves only to
+/// addresses webhooks may reach, unless private targets are allowed.
+pub async fn check_url(url: &str, allow_private: bool) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Only http and https URLs are allowed".to_string());
+    }
+    let host = url.host_str().ok_or("URL has no host")?;
+    if allow_private {
+        return Ok(());
+    }
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs = match literal.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("{} could not be resolved: {}", host, e))?
+            .collect(),
+    };
+    reject_internal(host, &addrs)
+}
+
+/// Resolves names for the webhook client, failing for any that point at an
+/// internal address, so a name cannot be re-pointed after `check_url`.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .collect::<Vec<_>>();
+            reject_internal(name.as_str(), &addrs)?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The key of a `table:key` record id.
+fn key(record: &str) -> &str {
+    record.split_once(':').map_or(record, |(_, key)| key)
+}
+
+/// Sends queued deliveries, retrying failures with exponential backoff and
+/// dead-lettering those that run out of attempts. Redirects are not followed
+/// and, unless `allow_private` is set, internal addresses are refused.
+#[derive(Clone)]
+pub struct Dispatcher {
+    store: Arc<dyn WebhookStore>,
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    allow_private: bool,
+}
+
+impl Dispatcher {
+    pub fn new(store: Arc<dyn WebhookStore>, policy: RetryPolicy, allow_private: bool) -> Self {
+        let mut builder = reqwest::Client::builder()
+            .timeout(policy.timeout)
+            .redirect(Policy::none())
+            .user_agent(concat!(
+                "rss-boilerplate-webhooks/",
+                env!("CARGO_PKG_VERSION")
+            ));
+        if !allow_private {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = builder
+            .build()
+            .expect("Failed to build the webhook HTTP client");
+        Dispatcher {
+            store,
+            client,
+            policy,
+            allow_private,
+        }
+    }
+
+    /// Attempts every delivery that is due, returning how many were attempted.
+    pub async fn deliver_due(&self) -> Result<usize, Error> {
+        let now = Local::now();
+        // Long enough for a whole batch to time out before anyone retakes it
+        let Some(lease) = self.policy.lease(now, BATCH + 1) else {
+            tracing::error!(timeout = ?self.policy.timeout, "Webhook timeout is too long to lease a batch");
+            return Ok(0);
+        };
+        let due = self.store.claim_due(now, lease, BATCH).await?;
+        let count = due.len();
+        futures::future::join_all(due.into_iter().map(|delivery| self.attempt(delivery))).await;
+        Ok(count)
+    }
+
+    async fn attempt(&self, delivery: WebhookDelivery) {
+        let webhook = self
+            .store
+            .get_by_id(key(&delivery.webhook).to_string())
+            .await
+            .ok();
+        let started = Instant::now();
+        let result = match &webhook {
+            Some(webhook) if webhook.active => self.send(webhook, &delivery).await,
+            Some(_) => Err("Webhook is inactive".to_string()),
+            None => Err("Webhook was deleted".to_string()),
+        };
+        let (status, error) = match result {
+            Ok(status) if (200..300).contains(&status) => (Some(status), None),
+            Ok(status) => (
+                Some(status),
+                Some(format!("Endpoint responded with status {}", status)),
+            ),
+            Err(error) => (None, Some(error)),
+        };
+        let delivered = error.is_none();
+        let attempt = DeliveryAttempt {
+            at: Local::now(),
+            status,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        let failures = delivery.attempts.len() + 1;
+        let retry_at = webhook
+            .as_ref()
+            .is_some_and(|webhook| webhook.active)
+            .then(|| self.policy.retry_at(Local::now(), failures))
+            .flatten();
+        let outcome = if delivered {
+            AttemptOutcome {
+                attempt,
+                status: DeliveryStatus::Delivered,
+                next_attempt_at: None,
+            }
+        } else if let Some(next_attempt_at) = retry_at {
+            AttemptOutcome {
+                attempt,
+                status: DeliveryStatus::Pending,
+                next_attempt_at: Some(next_attempt_at),
+            }
+        } else {
+            tracing::warn!(
+                delivery = %delivery.id,
+                event = %delivery.event,
+                attempts = failures,
+                error = ?attempt.error,
+                "Webhook delivery dead-lettered"
+            );
+            AttemptOutcome {
+                attempt,
+                status: DeliveryStatus::Dead,
+                next_attempt_at: None,
+            }
+        };
+        if let Err(e) = self
+            .store
+            .record_attempt(key(&delivery.id).to_string(), outcome)
+            .await
+        {
+            tracing::error!(error = %e, delivery = %delivery.id, "Failed to record webhook attempt");
+        }
+    }
+
+    /// POSTs the signed payload, returning the response status.
+    async fn send(&self, webhook: &Webhook, delivery: &WebhookDelivery) -> Result<u16, String> {
+        check_url(&webhook.url, self.allow_private).await?;
+        let body = serde_json::to_vec(&delivery.payload).map_err(|e| e.to_string())?;
+        let timestamp = Local::now().timestamp();
+        let signature = sign(
+            webhook.secret.as_deref().unwrap_or_default(),
+            timestamp,
+            &body,
+        );
+        let response = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event)
+            .header(DELIVERY_HEADER, key(&delivery.id))
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(response.status().as_u16())
+    }
+}
+
+/// Sends due deliveries every `interval` until the runtime shuts down.
+pub fn spawn(dispatcher: Dispatcher, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = dispatcher.deliver_due().await {
+                tracing::error!(error = %e, "Failed to deliver webhooks");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_addresses_are_recognised() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "100.127.255.254",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+            "64:ff9b::5db8:d70e",
+        ] {
+            assert!(is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.215.14", "100.128.0.1", "2606:4700::1111"] {
+            assert!(!is_internal(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        let signature = sign("secret", 1_700_000_000, b"{}");
+        assert_eq!(
+            signature,
+            "sha256=b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163"
+        );
+        assert_ne!(signature, sign("secret", 1_700_000_001, b"{}"));
+        assert_ne!(signature, sign("other", 1_700_000_000, b"{}"));
+    }
+}
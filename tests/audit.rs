@@ -0,0 +1,138 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Local};
+use common::{record_id, TestApp};
+use rss_boilerplate::config::Config;
+use rss_boilerplate::{audit, reminders};
+use serde_json::json;
+
+#[tokio::test]
+async fn every_write_is_audited_with_its_diff() {
+    let app = TestApp::new().await;
+    let id = app.seed_role("admin").await;
+    let resource = format!("role:{}", id);
+    let response = app
+        .put(&format!("/api/roles/{}", id), json!({ "name": "owner" }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    app.delete(&format!("/api/roles/{}", id)).await;
+    app.seed_role("ops").await;
+
+    let response = app.get(&format!("/api/audit?resource={}", resource)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["total"], 3);
+    let entries = response.body["entries"].as_array().unwrap();
+    let actions = entries
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(actions, ["delete", "update", "create"]);
+    assert!(entries
+        .iter()
+        .all(|entry| entry["resource"] == resource.as_str()));
+
+    let update = &entries[1];
+    assert_eq!(update["before"]["name"], "admin");
+    assert_eq!(update["after"]["name"], "owner");
+    assert_eq!(
+        update["changes"]["name"],
+        json!({ "before": "admin", "after": "owner" })
+    );
+    assert!(update["changes"].get("id").is_none());
+    assert!(update["actor"].is_null());
+    let request_id = update["request_id"].as_str().unwrap();
+    assert_ne!(request_id, entries[0]["request_id"].as_str().unwrap());
+    assert!(entries[0]["after"].is_null());
+
+    let response = app
+        .get(&format!("/api/audit?request_id={}", request_id))
+        .await;
+    assert_eq!(response.body["count"], 1);
+    let response = app.get("/api/audit?resource=role&action=create").await;
+    assert_eq!(response.body["total"], 2);
+    let response = app.get("/api/audit?resource=role&per_page=1&page=3").await;
+    assert_eq!(response.body["count"], 1);
+    assert_eq!(response.body["entries"][0]["action"], "update");
+    let response = app.get("/api/audit?page=0").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn bulk_and_background_writes_are_audited() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos/bulk",
+            json!([{ "title": "One" }, { "title": "Two" }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Call mum", "remind_at": Local::now() - Duration::minutes(1) }),
+        )
+        .await;
+    let id = record_id(&response.body["todo"]);
+
+    let todos = app.state.data.todos();
+    reminders::fire_due(todos.as_ref(), &app.state.events)
+        .await
+        .unwrap();
+
+    let response = app.get("/api/audit?resource=todo").await;
+    assert_eq!(response.body["total"], 4);
+    let fired = &response.body["entries"][0];
+    assert_eq!(fired["resource"], format!("todo:{}", id));
+    assert_eq!(fired["action"], "update");
+    assert_eq!(fired["actor"], "system");
+    assert!(fired["request_id"].is_null());
+}
+
+#[tokio::test]
+async fn retention_prunes_old_entries() {
+    let app = TestApp::new().await;
+    app.seed_user("Alice", "alice@example.com").await;
+    let audit_log = app.state.data.audit();
+
+    let pruned = audit::prune(audit_log.as_ref(), std::time::Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(pruned, 0);
+    let pruned = audit::prune(audit_log.as_ref(), std::time::Duration::MAX)
+        .await
+        .unwrap();
+    assert_eq!(pruned, 0);
+    assert_eq!(app.get("/api/audit").await.body["total"], 1);
+
+    let pruned = audit_log
+        .prune(Local::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+    assert_eq!(app.get("/api/audit").await.body["total"], 0);
+}
+
+#[tokio::test]
+async fn audit_log_is_off_by_default() {
+    let mut config = Config::from_env();
+    config.audit_api = false;
+    let app = TestApp::with_config(config).await;
+    app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app.get("/api/audit").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert!(response.body.get("entries").is_none());
+}
+
+#[tokio::test]
+async fn pages_too_far_out_are_rejected() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .get(&format!("/api/audit?page={}&per_page=100", usize::MAX))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["message"], "Query parameter page is too large");
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{record_id, TestApp};
+use serde_json::json;
+
+#[tokio::test]
+async fn bulk_create_todos_in_one_batch() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/todos/bulk",
+            json!([{ "title": "Buy milk" }, { "title": "Walk dog", "completed": true }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(response.body["status"], "success");
+    assert_eq!(response.body["mode"], "atomic");
+    assert_eq!(response.body["succeeded"], 2);
+    assert_eq!(response.body["results"][1]["status"], 201);
+    assert_eq!(response.body["results"][1]["todo"]["completed"], true);
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn atomic_bulk_create_rejects_whole_batch_on_invalid_item() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/todos/bulk",
+            json!([{ "title": "Buy milk" }, { "title": "" }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["status"], "error");
+    assert_eq!(response.body["results"][0]["status"], 424);
+    assert_eq!(response.body["results"][1]["status"], 422);
+    assert!(response.body["results"][1]["errors"]["title"].is_array());
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 0);
+}
+
+#[tokio::test]
+async fn partial_bulk_create_reports_each_item() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/todos/bulk?mode=partial",
+            json!([{ "title": "Buy milk" }, { "title": "" }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    assert_eq!(response.body["status"], "partial");
+    assert_eq!(response.body["succeeded"], 1);
+    assert_eq!(response.body["failed"], 1);
+    assert_eq!(response.body["results"][0]["status"], 201);
+    assert_eq!(response.body["results"][1]["status"], 422);
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 1);
+}
+
+#[tokio::test]
+async fn atomic_bulk_update_rolls_back_when_a_todo_is_missing() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app
+        .patch(
+            "/api/todos/bulk",
+            json!([{ "id": id, "completed": true }, { "id": "missing", "completed": true }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.body["results"][0]["status"], 424);
+    assert_eq!(response.body["results"][1]["status"], 404);
+
+    let response = app.get(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.body["completed"], false);
+}
+
+#[tokio::test]
+async fn partial_bulk_update_applies_existing_todos() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app
+        .patch(
+            "/api/todos/bulk?mode=partial",
+            json!([{ "id": id, "completed": true }, { "id": "missing", "completed": true }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    assert_eq!(response.body["results"][0]["todo"]["title"], "Write report");
+    assert_eq!(response.body["results"][0]["todo"]["completed"], true);
+    assert_eq!(response.body["results"][1]["status"], 404);
+}
+
+#[tokio::test]
+async fn bulk_delete_todos() {
+    let app = TestApp::new().await;
+    let first = app.seed_todo("Write report").await;
+    let second = app.seed_todo("Walk dog").await;
+
+    let response = app
+        .request(
+            Method::DELETE,
+            "/api/todos/bulk",
+            Some(json!([first, second])),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["succeeded"], 2);
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 0);
+}
+
+#[tokio::test]
+async fn bulk_delete_rejects_repeated_ids() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app
+        .request(Method::DELETE, "/api/todos/bulk", Some(json!([id, id])))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["results"][1]["status"], 400);
+
+    let response = app.get(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn bulk_requests_must_not_be_empty() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/api/todos/bulk", json!([])).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["status"], "error");
+}
+
+#[tokio::test]
+async fn bulk_create_users_rejects_taken_and_repeated_emails() {
+    let app = TestApp::new().await;
+    app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app
+        .post(
+            "/api/users/bulk?mode=partial",
+            json!([
+                { "name": "Bob", "email": "bob@example.com" },
+                { "name": "Alice", "email": "alice@example.com" },
+                { "name": "Bobby", "email": "bob@example.com" },
+                { "name": "Carol", "email": "not-an-email" },
+            ]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    let statuses = response.body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["status"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(statuses, vec![201, 400, 400, 422]);
+
+    let response = app.get("/api/users").await;
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn bulk_update_users_rejects_taken_and_repeated_emails() {
+    let app = TestApp::new().await;
+    let alice = app.seed_user("Alice", "alice@example.com").await;
+    let bob = app.seed_user("Bob", "bob@example.com").await;
+    let carol = app.seed_user("Carol", "carol@example.com").await;
+
+    let response = app
+        .patch(
+            "/api/users/bulk?mode=partial",
+            json!([
+                { "id": bob, "email": "alice@EXAMPLE.com" },
+                { "id": carol, "email": "new@example.com" },
+                { "id": alice, "email": "new@example.com" },
+                { "id": alice, "name": "Repeated" },
+            ]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    let results = response.body["results"].as_array().unwrap();
+    let statuses = results
+        .iter()
+        .map(|result| result["status"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(statuses, vec![400, 200, 400, 400]);
+    assert_eq!(results[0]["message"], "Email already in use");
+    assert_eq!(
+        results[2]["message"],
+        "Duplicate email: new@example.com in request"
+    );
+
+    let response = app
+        .patch(
+            "/api/users/bulk",
+            json!([{ "id": alice, "email": " Alice@Example.com " }, { "id": bob, "email": "new@example.com" }]),
+        )
+        .await;
+    assert_eq!(
+        response.status,
+        StatusCode::BAD_REQUEST,
+        "{:?}",
+        response.body
+    );
+    let response = app.get(&format!("/api/users/{}", bob)).await;
+    assert_eq!(response.body["email"], "bob@example.com");
+}
+
+#[tokio::test]
+async fn bulk_update_users_keeps_role_links() {
+    let app = TestApp::new().await;
+    let user = app.seed_user("Alice", "alice@example.com").await;
+    let role = app.seed_role("admin").await;
+
+    let response = app
+        .patch(
+            "/api/users/bulk",
+            json!([{ "id": user, "role": { "tb": "role", "id": { "String": role } } }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(
+        record_id(&json!({ "id": response.body["results"][0]["user"]["role"] })),
+        role
+    );
+
+    let response = app.get(&format!("/api/users/{}", user)).await;
+    assert_eq!(response.body["name"], "Alice");
+
+    // Stored as a record link rather than a plain object
+    let mut response = app
+        .db
+        .client
+        .query("SELECT VALUE type::is::record(role) FROM user")
+        .await
+        .unwrap();
+    let links: Vec<bool> = response.take(0).unwrap();
+    assert_eq!(links, vec![true]);
+}
@@ -0,0 +1,201 @@
+#![allow(dead_code)]
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::CONTENT_TYPE, HeaderMap, Method, Request, StatusCode};
+use axum::{middleware, Router};
+use rss_boilerplate::config::Config;
+use rss_boilerplate::data::data_context::DataContext;
+use rss_boilerplate::data::migrations;
+use rss_boilerplate::db::Database;
+use rss_boilerplate::live;
+use rss_boilerplate::middleware::rate_limit::rate_limit;
+use rss_boilerplate::middleware::request_id::request_id;
+use rss_boilerplate::routers::api_router::api_router;
+use rss_boilerplate::state::AppState;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// The full application wired to a fresh in-memory SurrealDB database.
+pub struct TestApp {
+    pub router: Router,
+    pub db: Arc<Database>,
+    pub state: AppState,
+}
+
+/// A decoded response from `TestApp::request`.
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub body: Value,
+    /// The raw body, for responses that are not JSON
+    pub text: String,
+    pub content_type: Option<String>,
+    pub headers: HeaderMap,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        let mut config = Config::from_env();
+        // Only the rate limiting tests want requests throttled
+        config.rate_limit.enabled = false;
+        // Off by default as they expose personal data, but under test here
+        config.audit_api = true;
+        config.user_history_api = true;
+        config.user_live_api = true;
+        config.webhooks_api = true;
+        // Webhook stand-ins listen on 127.0.0.1
+        config.webhook_allow_private_targets = true;
+        Self::with_config(config).await
+    }
+
+    pub async fn with_config(config: Config) -> Self {
+        // A unique database per test keeps tests independent while sharing one engine
+        let db = Database::connect("mem://", None, "test", &Uuid::new_v4().simple().to_string())
+            .await
+            .expect("Failed to start in-memory SurrealDB");
+        migrations::run(&db, &config.contacts)
+            .await
+            .expect("Failed to run migrations");
+        let state = AppState::new(DataContext::new(db.clone(), &config), config);
+        let router = Router::new()
+            .nest("/api", api_router())
+            .layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+            .layer(middleware::from_fn(request_id))
+            .with_state(state.clone());
+
+        TestApp { router, db, state }
+    }
+
+    pub async fn request(&self, method: Method, uri: &str, body: Option<Value>) -> TestResponse {
+        let builder = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => builder
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .unwrap();
+        self.send(request).await
+    }
+
+    /// Posts a non-JSON body such as an uploaded file.
+    pub async fn post_text(&self, uri: &str, content_type: &str, body: &str) -> TestResponse {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        self.send(request).await
+    }
+
+    /// Sends a request built by hand, e.g. with extra headers.
+    pub async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        TestResponse {
+            status,
+            body,
+            text,
+            content_type,
+            headers,
+        }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.request(Method::GET, uri, None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::PUT, uri, Some(body)).await
+    }
+
+    pub async fn patch(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::PATCH, uri, Some(body)).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.request(Method::DELETE, uri, None).await
+    }
+
+    /// Starts the live queries behind the change feeds, returning once every
+    /// watched table has one registered.
+    pub async fn watch_changes(&self) {
+        live::spawn(self.db.clone(), self.state.changes.clone());
+        for _ in 0..100 {
+            let mut ready = true;
+            for table in ["todo", "user", "role"] {
+                let info: Option<Value> = self
+                    .db
+                    .client
+                    .query(format!("INFO FOR TABLE {}", table))
+                    .await
+                    .unwrap()
+                    .take(0)
+                    .unwrap();
+                let lives = info.as_ref().and_then(|info| info["lives"].as_object());
+                ready &= lives.is_some_and(|lives| !lives.is_empty());
+            }
+            if ready {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("live queries did not start");
+    }
+
+    /// Creates a todo through the API and returns its record id.
+    pub async fn seed_todo(&self, title: &str) -> String {
+        let response = self
+            .post("/api/todos", json!({ "title": title, "content": "Seeded" }))
+            .await;
+        assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+        record_id(&response.body["todo"])
+    }
+
+    /// Creates a user through the API and returns its record id.
+    pub async fn seed_user(&self, name: &str, email: &str) -> String {
+        let response = self
+            .post(
+                "/api/users",
+                json!({ "name": name, "email": email, "phone": "+15555550100" }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+        record_id(&response.body["user"])
+    }
+
+    /// Creates a role through the API and returns its record id.
+    pub async fn seed_role(&self, name: &str) -> String {
+        let response = self.post("/api/roles", json!({ "name": name })).await;
+        assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+        record_id(&response.body["role"])
+    }
+}
+
+/// Extracts the raw key from a serialized record's `id` field.
+pub fn record_id(record: &Value) -> String {
+    match &record["id"]["id"] {
+        Value::Object(id) => id
+            .values()
+            .next()
+            .map(|key| key.as_str().map(str::to_owned).unwrap_or(key.to_string()))
+            .expect("record id has no key"),
+        other => panic!("unexpected record id: {}", other),
+    }
+}
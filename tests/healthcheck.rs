@@ -0,0 +1,44 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+
+#[tokio::test]
+async fn healthcheck_reports_success() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/healthcheck").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "success");
+}
+
+#[tokio::test]
+async fn openapi_document_is_served() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/openapi.json").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["openapi"], "3.1.0");
+    assert!(response.body["paths"]["/api/todos/{id}"].is_object());
+}
+
+#[tokio::test]
+async fn docs_page_is_served_without_third_party_assets() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/docs").await;
+    assert_eq!(response.status, StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers["location"], "/api/docs/");
+
+    let response = app.get("/api/docs/").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.content_type.unwrap().starts_with("text/html"));
+    assert!(!response.text.contains("://"), "{}", response.text);
+
+    let response = app.get("/api/docs/swagger-initializer.js").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.text.contains("/api/openapi.json"));
+
+    let response = app.get("/api/docs/missing.js").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use common::TestApp;
+use rss_boilerplate::config::Config;
+use serde_json::{json, Value};
+
+/// The `at` of a history entry as a query-string safe timestamp.
+fn timestamp(version: &Value) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(version["at"].as_str().unwrap())
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn as_of(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+#[tokio::test]
+async fn todo_history_reads_and_reverts_versions() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Draft").await;
+    let uri = format!("/api/todos/{}", id);
+    app.put(&uri, json!({ "title": "Second draft" })).await;
+    app.put(&uri, json!({ "title": "Final", "completed": true }))
+        .await;
+
+    let response = app.get(&format!("{}/history", uri)).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["count"], 3);
+    let versions = response.body["versions"].as_array().unwrap().clone();
+    let numbers = versions
+        .iter()
+        .map(|version| version["version"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, [3, 2, 1]);
+    assert_eq!(versions[2]["action"], "create");
+    assert_eq!(versions[0]["action"], "update");
+    assert_eq!(versions[1]["todo"]["title"], "Second draft");
+
+    let second = timestamp(&versions[1]);
+    let response = app.get(&format!("{}?as_of={}", uri, as_of(second))).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["version"], 2);
+    assert_eq!(response.body["todo"]["title"], "Second draft");
+    let before = timestamp(&versions[2]) - Duration::seconds(1);
+    let response = app.get(&format!("{}?as_of={}", uri, as_of(before))).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post(&format!("{}/revert/1", uri), json!({})).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["todo"]["title"], "Draft");
+    assert_eq!(response.body["todo"]["completed"], false);
+    let response = app.get(&format!("{}/history", uri)).await;
+    assert_eq!(response.body["count"], 4);
+    assert_eq!(response.body["versions"][0]["action"], "update");
+    let response = app.post(&format!("{}/revert/9", uri), json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    app.delete(&uri).await;
+    let response = app.get(&format!("{}/history", uri)).await;
+    assert_eq!(response.body["count"], 5);
+    assert_eq!(response.body["versions"][0]["action"], "delete");
+    assert!(response.body["versions"][0]["todo"].is_null());
+    let response = app
+        .get(&format!("{}?as_of={}", uri, as_of(Utc::now())))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.post(&format!("{}/revert/1", uri), json!({})).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get("/api/todos/missing/history").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn revert_rejects_a_version_whose_list_is_gone() {
+    let app = TestApp::new().await;
+    let errands = app.post("/api/lists", json!({ "name": "Errands" })).await;
+    let errands = common::record_id(&errands.body["list"]);
+    let chores = app.post("/api/lists", json!({ "name": "Chores" })).await;
+    let chores = common::record_id(&chores.body["list"]);
+    let response = app
+        .post("/api/todos", json!({ "title": "Milk", "list_id": errands }))
+        .await;
+    let id = common::record_id(&response.body["todo"]);
+    app.put(&format!("/api/todos/{}", id), json!({ "list_id": chores }))
+        .await;
+    let response = app.delete(&format!("/api/lists/{}", errands)).await;
+    assert!(response.status.is_success(), "{:?}", response.body);
+
+    let response = app
+        .post(&format!("/api/todos/{}/revert/1", id), json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT, "{:?}", response.body);
+}
+
+#[tokio::test]
+async fn user_history_and_point_in_time_reads() {
+    let app = TestApp::new().await;
+    let id = app.seed_user("Ada", "ada@example.com").await;
+    let uri = format!("/api/users/{}", id);
+    let response = app
+        .put(
+            &uri,
+            json!({ "name": "Ada Lovelace", "email": "ada@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+
+    let response = app.get(&format!("{}/history", uri)).await;
+    assert_eq!(response.body["count"], 2);
+    assert_eq!(response.body["versions"][0]["user"]["name"], "Ada Lovelace");
+    let first = timestamp(&response.body["versions"][1]);
+    let response = app.get(&format!("{}?as_of={}", uri, as_of(first))).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["user"]["name"], "Ada");
+    let response = app.get(&uri).await;
+    assert_eq!(response.body["name"], "Ada Lovelace");
+}
+
+#[tokio::test]
+async fn user_history_is_off_by_default() {
+    let mut config = Config::from_env();
+    config.user_history_api = false;
+    let app = TestApp::with_config(config).await;
+    let id = app.seed_user("Ada", "ada@example.com").await;
+    app.delete(&format!("/api/users/{}", id)).await;
+
+    let response = app.get(&format!("/api/users/{}/history", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert!(response.body.get("versions").is_none());
+    let response = app
+        .get(&format!("/api/users/{}?as_of={}", id, as_of(Utc::now())))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert!(response.body.get("user").is_none());
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Local};
+use common::{record_id, TestApp};
+use serde_json::json;
+
+async fn seed_list(app: &TestApp, name: &str) -> String {
+    let response = app.post("/api/lists", json!({ "name": name })).await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    record_id(&response.body["list"])
+}
+
+async fn seed_todo_in(app: &TestApp, list_id: &str, title: &str) -> String {
+    let response = app
+        .post("/api/todos", json!({ "title": title, "list_id": list_id }))
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    record_id(&response.body["todo"])
+}
+
+#[tokio::test]
+async fn create_update_and_list_lists() {
+    let app = TestApp::new().await;
+    let id = seed_list(&app, "Home").await;
+    seed_list(&app, "Work").await;
+
+    let response = app.get("/api/lists").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+
+    let response = app
+        .put(
+            &format!("/api/lists/{}", id),
+            json!({ "description": "Chores" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["list"]["name"], "Home");
+    assert_eq!(response.body["list"]["description"], "Chores");
+
+    let response = app.post("/api/lists", json!({ "name": "" })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.get("/api/lists/missing").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_todos_only_returns_members() {
+    let app = TestApp::new().await;
+    let home = seed_list(&app, "Home").await;
+    let work = seed_list(&app, "Work").await;
+    seed_todo_in(&app, &home, "Water plants").await;
+    seed_todo_in(&app, &home, "Fix sink").await;
+    seed_todo_in(&app, &work, "Write report").await;
+    app.seed_todo("Unfiled").await;
+
+    let response = app.get(&format!("/api/lists/{}/todos", home)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+
+    let response = app.get(&format!("/api/todos?list_id={}", work)).await;
+    assert_eq!(response.body["count"], 1);
+    assert_eq!(response.body["todos"][0]["title"], "Write report");
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Lost", "list_id": "missing" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/lists/missing/todos").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn move_todos_between_lists() {
+    let app = TestApp::new().await;
+    let home = seed_list(&app, "Home").await;
+    let work = seed_list(&app, "Work").await;
+    let first = seed_todo_in(&app, &home, "Call plumber").await;
+    let second = app.seed_todo("Book flights").await;
+
+    let response = app
+        .post(
+            &format!("/api/lists/{}/todos", work),
+            json!([first, second]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["succeeded"], 2);
+
+    let response = app.get(&format!("/api/lists/{}/todos", work)).await;
+    assert_eq!(response.body["count"], 2);
+    let response = app.get(&format!("/api/lists/{}/todos", home)).await;
+    assert_eq!(response.body["count"], 0);
+
+    // A single todo can be moved back with a regular update
+    let response = app
+        .put(&format!("/api/todos/{}", first), json!({ "list_id": home }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.get(&format!("/api/lists/{}/todos", home)).await;
+    assert_eq!(response.body["count"], 1);
+
+    let response = app
+        .post(
+            &format!("/api/lists/{}/todos", work),
+            json!([first, "missing"]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get(&format!("/api/lists/{}/todos", home)).await;
+    assert_eq!(response.body["count"], 1);
+
+    let response = app.post("/api/lists/missing/todos", json!([first])).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_stats_count_completion() {
+    let app = TestApp::new().await;
+    let id = seed_list(&app, "Home").await;
+
+    let response = app.get(&format!("/api/lists/{}/stats", id)).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["stats"]["total"], 0);
+    assert_eq!(response.body["stats"]["completion_rate"], 0.0);
+
+    let now = Local::now();
+    for (title, completed, due_at) in [
+        ("Done", true, None),
+        ("Late", false, Some(now - Duration::days(1))),
+        ("Upcoming", false, Some(now + Duration::days(1))),
+        ("Also done", true, Some(now - Duration::days(1))),
+    ] {
+        app.post(
+            "/api/todos",
+            json!({ "title": title, "list_id": id, "completed": completed, "due_at": due_at }),
+        )
+        .await;
+    }
+    app.seed_todo("Elsewhere").await;
+
+    let response = app.get(&format!("/api/lists/{}/stats", id)).await;
+    let stats = &response.body["stats"];
+    assert_eq!(stats["total"], 4);
+    assert_eq!(stats["completed"], 2);
+    assert_eq!(stats["open"], 2);
+    assert_eq!(stats["overdue"], 1);
+    assert_eq!(stats["completion_rate"], 0.5);
+}
+
+#[tokio::test]
+async fn delete_list_restricts_unless_cascading() {
+    let app = TestApp::new().await;
+    let id = seed_list(&app, "Home").await;
+    let todo = seed_todo_in(&app, &id, "Water plants").await;
+    let other = app.seed_todo("Unfiled").await;
+
+    let response = app.delete(&format!("/api/lists/{}", id)).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.body["status"], "error");
+    assert_eq!(
+        app.get(&format!("/api/lists/{}", id)).await.status,
+        StatusCode::OK
+    );
+
+    let response = app
+        .delete(&format!("/api/lists/{}?on_delete=cascade", id))
+        .await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app.get(&format!("/api/lists/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get(&format!("/api/todos/{}", todo)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get(&format!("/api/todos/{}", other)).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let empty = seed_list(&app, "Empty").await;
+    let response = app.delete(&format!("/api/lists/{}", empty)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = app.delete("/api/lists/missing").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
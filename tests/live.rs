@@ -0,0 +1,219 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use common::{record_id, TestApp};
+use futures::{SinkExt, StreamExt};
+use rss_boilerplate::config::Config;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+use tower::ServiceExt;
+
+/// An open Server-Sent Events response, read one event at a time.
+struct EventStream {
+    body: axum::body::BodyDataStream,
+    buffer: String,
+}
+
+#[derive(Debug)]
+struct SseEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: Value,
+}
+
+impl EventStream {
+    async fn open(app: &TestApp, uri: &str, last_event_id: Option<&str>) -> Self {
+        let mut request = Request::builder().uri(uri);
+        if let Some(id) = last_event_id {
+            request = request.header("last-event-id", id);
+        }
+        let response = app
+            .router
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        EventStream {
+            body: response.into_body().into_data_stream(),
+            buffer: String::new(),
+        }
+    }
+
+    /// The next event with data, skipping heartbeat comments.
+    async fn next(&mut self) -> SseEvent {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block = self.buffer[..end].to_string();
+                self.buffer.drain(..end + 2);
+                let mut event = SseEvent {
+                    id: None,
+                    event: None,
+                    data: Value::Null,
+                };
+                for line in block.lines() {
+                    match line.split_once(':') {
+                        Some(("id", value)) => event.id = Some(value.trim().to_string()),
+                        Some(("event", value)) => event.event = Some(value.trim().to_string()),
+                        Some(("data", value)) => event.data = serde_json::from_str(value).unwrap(),
+                        _ => {}
+                    }
+                }
+                if event.data != Value::Null {
+                    return event;
+                }
+                continue;
+            }
+            let chunk = tokio::time::timeout(Duration::from_secs(5), self.body.next())
+                .await
+                .expect("no event within 5s")
+                .expect("stream ended")
+                .unwrap();
+            self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+}
+
+#[tokio::test]
+async fn sse_pushes_changes_to_one_resource() {
+    let app = TestApp::new().await;
+    app.watch_changes().await;
+    let mut todos = EventStream::open(&app, "/api/todos/live", None).await;
+
+    app.seed_role("admin").await;
+    let id = app.seed_todo("Water plants").await;
+    app.put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    app.delete(&format!("/api/todos/{}", id)).await;
+
+    let created = todos.next().await;
+    assert_eq!(created.event.as_deref(), Some("create"));
+    assert_eq!(created.data["resource"], "todo");
+    assert_eq!(record_id(&created.data["record"]), id);
+    assert_eq!(created.id, Some(created.data["id"].to_string()));
+
+    let updated = todos.next().await;
+    assert_eq!(updated.event.as_deref(), Some("update"));
+    assert_eq!(updated.data["record"]["completed"], true);
+    assert_eq!(todos.next().await.event.as_deref(), Some("delete"));
+}
+
+#[tokio::test]
+async fn sse_resumes_from_last_event_id() {
+    let app = TestApp::new().await;
+    app.watch_changes().await;
+    let mut roles = EventStream::open(&app, "/api/roles/live", None).await;
+    app.seed_role("admin").await;
+    let first = roles.next().await.data["id"].as_u64().unwrap();
+    app.seed_role("ops").await;
+    roles.next().await;
+
+    // A reconnecting client gets what it missed, then carries on
+    let mut resumed = EventStream::open(&app, "/api/roles/live", Some(&first.to_string())).await;
+    let replayed = resumed.next().await;
+    assert_eq!(replayed.data["id"], first + 1);
+    assert_eq!(replayed.data["record"]["name"], "ops");
+
+    // An id the feed no longer covers asks the client to refetch
+    let mut stale = EventStream::open(&app, "/api/roles/live?last_event_id=1", None).await;
+    let reset = stale.next().await;
+    assert_eq!(reset.event.as_deref(), Some("reset"));
+    assert_eq!(reset.data["latest"], first + 1);
+    assert_eq!(reset.id, Some((first + 1).to_string()));
+
+    let response = app
+        .router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/roles/live")
+                .header("last-event-id", "soon")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn websocket_streams_selected_resources() {
+    let app = TestApp::new().await;
+    app.watch_changes().await;
+
+    let response = app.get("/api/live").await;
+    assert_eq!(response.status, StatusCode::UPGRADE_REQUIRED);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = app.router.clone();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let error =
+        tokio_tungstenite::connect_async(format!("ws://{}/api/live?resources=todo,tag", addr))
+            .await
+            .unwrap_err();
+    assert!(error.to_string().contains("400"), "{}", error);
+
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/api/live?resources=user,role", addr))
+            .await
+            .unwrap();
+    app.seed_todo("Ignored").await;
+    app.seed_role("admin").await;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("no message within 5s")
+        .unwrap()
+        .unwrap();
+    let item: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(item["type"], "change");
+    assert_eq!(item["resource"], "role");
+    assert_eq!(item["action"], "create");
+    assert_eq!(item["record"]["name"], "admin");
+    socket.send(Message::Close(None)).await.unwrap();
+}
+
+#[tokio::test]
+async fn user_changes_are_not_streamed_by_default() {
+    let mut config = Config::from_env();
+    config.rate_limit.enabled = false;
+    config.user_live_api = false;
+    let app = TestApp::with_config(config).await;
+    app.watch_changes().await;
+
+    let response = app.get("/api/users/live").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = app.router.clone();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let error = tokio_tungstenite::connect_async(format!("ws://{}/api/live?resources=user", addr))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("400"), "{}", error);
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/api/live", addr))
+        .await
+        .unwrap();
+    app.seed_user("Ada", "ada@example.com").await;
+    app.seed_role("admin").await;
+
+    let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("no message within 5s")
+        .unwrap()
+        .unwrap();
+    let item: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(item["resource"], "role");
+    socket.send(Message::Close(None)).await.unwrap();
+}
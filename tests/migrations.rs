@@ -0,0 +1,73 @@
+mod common;
+
+use common::TestApp;
+use rss_boilerplate::data::contact::ContactNormalizer;
+use rss_boilerplate::data::migrations::{self, normalize_contacts, EmailCollision};
+
+#[tokio::test]
+async fn normalize_contacts_rewrites_stored_values_and_reports_collisions() {
+    let app = TestApp::new().await;
+    // Written directly, as rows stored before normalisation existed would be
+    app.db
+        .client
+        .query("REMOVE INDEX user_email_unique ON user")
+        .query("CREATE user:a CONTENT { name: 'Alice', email: 'Alice@Example.com' }")
+        .query("CREATE user:b CONTENT { name: 'Al', email: 'alice@example.com' }")
+        .query("CREATE user:c CONTENT { name: 'Carol', email: 'Carol@Example.com', phone: '(555) 555-0199' }")
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+    let contacts = ContactNormalizer {
+        lowercase_email_local_part: true,
+        default_country_code: Some("1".to_string()),
+    };
+
+    let report = normalize_contacts(&app.db, &contacts).await.unwrap();
+    assert_eq!(report.updated, 1);
+    assert_eq!(
+        report.collisions,
+        vec![EmailCollision {
+            email: "alice@example.com".to_string(),
+            user_ids: vec!["a".to_string(), "b".to_string()],
+        }]
+    );
+    assert!(!report.unique_email_index);
+
+    let response = app.get("/api/users/c").await;
+    assert_eq!(response.body["email"], "carol@example.com");
+    assert_eq!(response.body["phone"], "+15555550199");
+
+    app.delete("/api/users/b").await;
+    let report = normalize_contacts(&app.db, &contacts).await.unwrap();
+    assert!(report.collisions.is_empty());
+    assert!(report.unique_email_index);
+
+    let result = app
+        .db
+        .client
+        .query("CREATE user CONTENT { name: 'Dup', email: 'carol@example.com' }")
+        .await
+        .unwrap()
+        .check();
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn migrations_run_once() {
+    let app = TestApp::new().await;
+    app.db
+        .client
+        .query("CREATE user:d CONTENT { name: 'Dave', email: 'Dave@Example.com' }")
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+    // TestApp already ran and recorded them on the empty database
+    migrations::run(&app.db, &ContactNormalizer::default())
+        .await
+        .unwrap();
+    let response = app.get("/api/users/d").await;
+    assert_eq!(response.body["email"], "Dave@Example.com");
+}
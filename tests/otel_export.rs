@@ -0,0 +1,82 @@
+#![cfg(feature = "otel")]
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry_otlp::Protocol;
+use rss_boilerplate::otel::{self, OtelConfig};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Starts a stand-in OTLP/HTTP collector that forwards every export payload to a channel.
+async fn spawn_collector() -> (String, mpsc::UnboundedReceiver<Bytes>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/v1/traces",
+        post(move |body: Bytes| {
+            let tx = tx.clone();
+            async move {
+                tx.send(body).ok();
+                StatusCode::OK
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/v1/traces", addr), rx)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exports_spans_to_collector() {
+    let (endpoint, mut exports) = spawn_collector().await;
+    let provider = otel::tracer_provider(&OtelConfig {
+        service_name: "otel-test".to_string(),
+        protocol: Protocol::HttpBinary,
+        endpoint: Some(endpoint),
+    })
+    .unwrap();
+
+    let subscriber = tracing_subscriber::registry().with(otel::layer(&provider));
+    tracing::subscriber::with_default(subscriber, || {
+        let _span = tracing::info_span!("repository_call").entered();
+    });
+    tokio::task::spawn_blocking(move || provider.force_flush())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let payload = tokio::time::timeout(Duration::from_secs(10), exports.recv())
+        .await
+        .expect("collector received no export")
+        .unwrap();
+    assert!(!payload.is_empty());
+}
+
+#[tokio::test]
+async fn continues_incoming_traceparent() {
+    let provider = otel::tracer_provider(&OtelConfig {
+        service_name: "otel-test".to_string(),
+        protocol: Protocol::HttpBinary,
+        endpoint: Some("http://127.0.0.1:9/v1/traces".to_string()),
+    })
+    .unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "traceparent",
+        HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+    );
+
+    let subscriber = tracing_subscriber::registry().with(otel::layer(&provider));
+    let trace_id = tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("request");
+        otel::set_parent_from_headers(&span, &headers);
+        span.context().span().span_context().trace_id()
+    });
+
+    assert_eq!(trace_id.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+}
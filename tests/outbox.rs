@@ -0,0 +1,234 @@
+mod common;
+
+use async_trait::async_trait;
+use common::TestApp;
+use rss_boilerplate::data::models::outbox::DomainEvent;
+use rss_boilerplate::events::Event;
+use rss_boilerplate::outbox::{self, ChannelSink, Dispatcher, Sink};
+use rss_boilerplate::retry::RetryPolicy;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A sink that records the events it takes, and fails while told to.
+struct Recorder {
+    name: &'static str,
+    failing: AtomicBool,
+    taken: Mutex<Vec<String>>,
+    offered: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn new(name: &'static str, failing: bool) -> Arc<Self> {
+        Arc::new(Recorder {
+            name,
+            failing: AtomicBool::new(failing),
+            taken: Mutex::default(),
+            offered: Mutex::default(),
+        })
+    }
+
+    fn taken(&self) -> Vec<String> {
+        self.taken.lock().unwrap().clone()
+    }
+
+    fn offered(&self) -> Vec<String> {
+        self.offered.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Sink for Recorder {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn publish(&self, event: &DomainEvent) -> Result<(), String> {
+        let name = format!(
+            "{} {}",
+            event.event,
+            event.data["title"].as_str().unwrap_or("")
+        );
+        self.offered.lock().unwrap().push(name.clone());
+        if self.failing.load(Ordering::SeqCst) {
+            return Err("unavailable".to_string());
+        }
+        self.taken.lock().unwrap().push(name);
+        Ok(())
+    }
+}
+
+fn policy(max_attempts: usize, backoff: Duration) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        backoff,
+        max_backoff: backoff,
+        timeout: Duration::from_secs(5),
+    }
+}
+
+fn dispatcher(app: &TestApp, sinks: Vec<Arc<dyn Sink>>, policy: RetryPolicy) -> Dispatcher {
+    Dispatcher::new(app.state.data.outbox(), sinks, policy)
+}
+
+async fn statuses(app: &TestApp) -> Vec<String> {
+    let mut response = app
+        .db
+        .client
+        .query("SELECT sequence, status FROM outbox ORDER BY sequence")
+        .await
+        .unwrap();
+    response.take((0, "status")).unwrap()
+}
+
+#[tokio::test]
+async fn changes_are_published_in_order_to_every_sink() {
+    let app = TestApp::new().await;
+    let mut events = app.state.events.subscribe();
+    let recorder = Recorder::new("recorder", false);
+    let dispatcher = dispatcher(
+        &app,
+        vec![
+            Arc::new(ChannelSink(app.state.events.clone())),
+            recorder.clone(),
+        ],
+        policy(3, Duration::ZERO),
+    );
+
+    let first = app.seed_todo("First").await;
+    app.put(
+        &format!("/api/todos/{}", first),
+        json!({ "title": "Renamed" }),
+    )
+    .await;
+    app.seed_todo("Second").await;
+    app.delete(&format!("/api/todos/{}", first)).await;
+
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 4);
+    let expected = [
+        "todo.created First",
+        "todo.updated Renamed",
+        "todo.created Second",
+        "todo.deleted Renamed",
+    ];
+    assert_eq!(recorder.taken(), expected);
+    for name in expected {
+        let Ok(Event::Domain { event }) = events.try_recv() else {
+            panic!("expected a domain event for {}", name);
+        };
+        assert_eq!(event.event, name.split(' ').next().unwrap());
+        assert!(event.resource.starts_with("todo:"));
+        assert!(event.request_id.is_some());
+    }
+    assert_eq!(statuses(&app).await, ["published"; 4]);
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn events_written_together_keep_their_order() {
+    let app = TestApp::new().await;
+    let recorder = Recorder::new("recorder", false);
+    let dispatcher = dispatcher(&app, vec![recorder.clone()], policy(3, Duration::ZERO));
+
+    let mut ids = Vec::new();
+    for n in 1..=20 {
+        ids.push(app.seed_todo(&format!("Todo {}", n)).await);
+    }
+    assert_eq!(dispatcher.publish_due().await.unwrap(), ids.len());
+    // Each completion writes its update and completion events together
+    let completions: Vec<_> = ids
+        .iter()
+        .map(|id| json!({ "id": id, "completed": true }))
+        .collect();
+    app.patch("/api/todos/bulk", json!(completions)).await;
+
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 2 * ids.len());
+    let expected: Vec<String> = (1..=20)
+        .flat_map(|n| {
+            [
+                format!("todo.updated Todo {}", n),
+                format!("todo.completed Todo {}", n),
+            ]
+        })
+        .collect();
+    assert_eq!(recorder.taken()[ids.len()..], expected);
+}
+
+#[tokio::test]
+async fn a_failed_event_holds_back_later_ones_until_it_is_retried() {
+    let app = TestApp::new().await;
+    let steady = Recorder::new("steady", false);
+    let flaky = Recorder::new("flaky", true);
+    let dispatcher = dispatcher(
+        &app,
+        vec![steady.clone(), flaky.clone()],
+        policy(5, Duration::from_secs(60 * 60)),
+    );
+
+    app.seed_todo("First").await;
+    app.seed_todo("Second").await;
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 1);
+    assert_eq!(
+        dispatcher.publish_due().await.unwrap(),
+        0,
+        "should be backing off"
+    );
+    assert_eq!(statuses(&app).await, ["pending", "pending"]);
+
+    // Skip the backoff rather than wait it out
+    app.db
+        .client
+        .query("UPDATE outbox SET next_attempt_at = time::now()")
+        .await
+        .unwrap();
+    flaky.failing.store(false, Ordering::SeqCst);
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 2);
+    // The steady sink took the first event already, so isn't offered it again
+    let expected = ["todo.created First", "todo.created Second"];
+    assert_eq!(steady.offered(), expected);
+    assert_eq!(flaky.taken(), expected);
+    assert_eq!(flaky.offered()[..2], ["todo.created First"; 2]);
+    assert_eq!(statuses(&app).await, ["published", "published"]);
+}
+
+#[tokio::test]
+async fn events_that_run_out_of_attempts_are_dead_lettered() {
+    let app = TestApp::new().await;
+    let flaky = Recorder::new("flaky", true);
+    let dispatcher = dispatcher(&app, vec![flaky], policy(1, Duration::ZERO));
+
+    app.seed_todo("First").await;
+    app.seed_user("Ada", "ada@example.com").await;
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 2);
+    assert_eq!(statuses(&app).await, ["dead", "dead"]);
+    let mut response = app
+        .db
+        .client
+        .query("SELECT VALUE last_error FROM outbox")
+        .await
+        .unwrap();
+    let errors: Vec<String> = response.take(0).unwrap();
+    assert!(errors.iter().all(|error| error == "flaky: unavailable"));
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn retention_beyond_the_calendar_keeps_everything() {
+    let app = TestApp::new().await;
+    let dispatcher = dispatcher(
+        &app,
+        vec![Recorder::new("ok", false)],
+        policy(1, Duration::ZERO),
+    );
+    app.seed_todo("First").await;
+    assert_eq!(dispatcher.publish_due().await.unwrap(), 1);
+
+    let outbox = app.state.data.outbox();
+    let pruned = outbox::prune(outbox.as_ref(), Duration::MAX).await.unwrap();
+    assert_eq!(pruned, 0);
+    let pruned = outbox::prune(outbox.as_ref(), Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::header::RETRY_AFTER;
+use axum::http::{Method, Request, StatusCode};
+use chrono::{Duration as ChronoDuration, Local};
+use common::{TestApp, TestResponse};
+use rss_boilerplate::config::Config;
+use rss_boilerplate::data::data_context::DataContext;
+use rss_boilerplate::data::models::rate_limit::Quota;
+use rss_boilerplate::middleware::rate_limit::{
+    RATELIMIT_LIMIT, RATELIMIT_POLICY, RATELIMIT_REMAINING, RATELIMIT_RESET,
+};
+use rss_boilerplate::rate_limit::{Backend, RateLimitConfig, Rule};
+use serde_json::json;
+use std::net::SocketAddr;
+
+fn config(limits: RateLimitConfig) -> Config {
+    Config {
+        rate_limit: limits,
+        ..Config::from_env()
+    }
+}
+
+fn limits(default: &str, rules: &[&str]) -> RateLimitConfig {
+    RateLimitConfig {
+        default: Quota::parse(default).unwrap(),
+        rules: rules
+            .iter()
+            .map(|rule| Rule::parse(rule).unwrap())
+            .collect(),
+        ..RateLimitConfig::default()
+    }
+}
+
+/// Sends a request from `address` with the extra `headers`.
+async fn send(
+    app: &TestApp,
+    method: Method,
+    uri: &str,
+    address: &str,
+    headers: &[(&str, &str)],
+    body: Option<serde_json::Value>,
+) -> TestResponse {
+    let mut builder = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let mut request = match body {
+        Some(body) => builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .unwrap();
+    let address: SocketAddr = format!("{}:4000", address).parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(address));
+    app.send(request).await
+}
+
+fn header(response: &TestResponse, name: impl axum::http::header::AsHeaderName) -> &str {
+    response.headers.get(name).unwrap().to_str().unwrap()
+}
+
+#[tokio::test]
+async fn requests_past_the_quota_are_rejected_with_retry_after() {
+    let app = TestApp::with_config(config(limits(
+        "3/min",
+        &["POST /api/users=2/min", "/api/healthcheck=off"],
+    )))
+    .await;
+
+    for remaining in ["2", "1", "0"] {
+        let response = send(&app, Method::GET, "/api/todos", "192.0.2.1", &[], None).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(header(&response, RATELIMIT_LIMIT), "3");
+        assert_eq!(header(&response, RATELIMIT_REMAINING), remaining);
+        assert_eq!(header(&response, RATELIMIT_POLICY), "3;w=60");
+    }
+    let response = send(&app, Method::GET, "/api/todos/abc", "192.0.2.1", &[], None).await;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.body["status"], "error");
+    assert!(response.body["request_id"].is_string());
+    let retry_after: u64 = header(&response, RETRY_AFTER).parse().unwrap();
+    assert!((1..=20).contains(&retry_after), "{}", retry_after);
+    let reset: u64 = header(&response, RATELIMIT_RESET).parse().unwrap();
+    assert!((21..=60).contains(&reset), "{}", reset);
+
+    // Other clients have buckets of their own
+    let response = send(&app, Method::GET, "/api/todos", "192.0.2.2", &[], None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    // Creating users has a stricter quota, counted apart from the default
+    for (i, expected) in [
+        StatusCode::CREATED,
+        StatusCode::CREATED,
+        StatusCode::TOO_MANY_REQUESTS,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let user = json!({
+            "name": "Ada",
+            "email": format!("ada{}@example.com", i),
+            "phone": "+15555550100"
+        });
+        let response = send(
+            &app,
+            Method::POST,
+            "/api/users",
+            "192.0.2.1",
+            &[],
+            Some(user),
+        )
+        .await;
+        assert_eq!(response.status, expected, "{:?}", response.body);
+        assert_eq!(header(&response, RATELIMIT_LIMIT), "2");
+    }
+
+    let response = send(
+        &app,
+        Method::GET,
+        "/api/healthcheck",
+        "192.0.2.1",
+        &[],
+        None,
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.headers.get(RATELIMIT_LIMIT).is_none());
+}
+
+#[tokio::test]
+async fn clients_are_told_apart_by_api_key_or_forwarded_address_when_trusted() {
+    let app = TestApp::with_config(config(RateLimitConfig {
+        by_api_key: true,
+        trust_forwarded: true,
+        ..limits("1/min", &[])
+    }))
+    .await;
+    let get = |headers: &'static [(&'static str, &'static str)]| {
+        send(&app, Method::GET, "/api/roles", "192.0.2.1", headers, None)
+    };
+
+    assert_eq!(get(&[("x-api-key", "one")]).await.status, StatusCode::OK);
+    assert_eq!(
+        get(&[("x-api-key", "one")]).await.status,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(get(&[("x-api-key", "two")]).await.status, StatusCode::OK);
+
+    const FORWARDED: &[(&str, &str)] = &[("x-forwarded-for", "203.0.113.7")];
+    assert_eq!(get(FORWARDED).await.status, StatusCode::OK);
+    assert_eq!(get(FORWARDED).await.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        get(&[("x-forwarded-for", "203.0.113.8")]).await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn forged_leading_forwarded_entries_are_ignored() {
+    let app = TestApp::with_config(config(RateLimitConfig {
+        trust_forwarded: true,
+        ..limits("1/min", &[])
+    }))
+    .await;
+    let app = &app;
+    let get = |forwarded: &'static str| async move {
+        let headers = [("x-forwarded-for", forwarded)];
+        send(app, Method::GET, "/api/roles", "192.0.2.1", &headers, None).await
+    };
+
+    assert_eq!(
+        get("198.51.100.1, 203.0.113.7").await.status,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get("198.51.100.2, 203.0.113.7").await.status,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        get("203.0.113.7, 198.51.100.3").await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn instances_share_buckets_kept_in_surrealdb() {
+    let limits = RateLimitConfig {
+        backend: Backend::Surreal,
+        ..limits("2/min", &[])
+    };
+    let app = TestApp::with_config(config(limits.clone())).await;
+    // A second instance on the same database
+    let other = DataContext::new(app.db.clone(), &config(limits)).rate_limits();
+
+    let response = send(&app, Method::GET, "/api/roles", "192.0.2.1", &[], None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let quota = Quota::parse("2/min").unwrap();
+    let take = other
+        .take("default|ip:192.0.2.1".to_string(), quota, Local::now())
+        .await
+        .unwrap();
+    assert!(take.allowed);
+    assert_eq!(take.tokens.floor(), 0.0);
+    let response = send(&app, Method::GET, "/api/roles", "192.0.2.1", &[], None).await;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Buckets go once they have refilled
+    let store = app.state.data.rate_limits();
+    assert_eq!(store.prune(Local::now()).await.unwrap(), 0);
+    let later = Local::now() + ChronoDuration::minutes(2);
+    assert_eq!(store.prune(later).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn requests_are_refused_when_buckets_cannot_be_read() {
+    let limits = RateLimitConfig {
+        backend: Backend::Surreal,
+        ..limits("100/min", &[])
+    };
+    let app = TestApp::with_config(config(limits)).await;
+    // Every take now fails, as a store that keeps conflicting would
+    app.db
+        .client
+        .query("DEFINE FIELD tokens ON rate_limit ASSERT false")
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+    let response = send(&app, Method::GET, "/api/roles", "192.0.2.1", &[], None).await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers[RETRY_AFTER], "1");
+    assert_eq!(response.body["status"], "error");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn create_and_list_roles() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/api/roles", json!({ "name": "admin" })).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["role"]["name"], "admin");
+
+    app.seed_role("editor").await;
+    let response = app.get("/api/roles").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn create_role_rejects_duplicate_name() {
+    let app = TestApp::new().await;
+    app.seed_role("admin").await;
+
+    let response = app.post("/api/roles", json!({ "name": "admin" })).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["message"], "Role already exists");
+}
+
+#[tokio::test]
+async fn get_role_by_id_and_name() {
+    let app = TestApp::new().await;
+    let id = app.seed_role("admin").await;
+
+    let response = app.get(&format!("/api/roles/{}", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "admin");
+
+    let response = app.get("/api/roles/name/admin").await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get("/api/roles/missing").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.get("/api/roles/name/nobody").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_role() {
+    let app = TestApp::new().await;
+    let id = app.seed_role("admin").await;
+
+    let response = app
+        .put(&format!("/api/roles/{}", id), json!({ "name": "owner" }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["role"]["name"], "owner");
+
+    let response = app
+        .put("/api/roles/missing", json!({ "name": "owner" }))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_role() {
+    let app = TestApp::new().await;
+    let id = app.seed_role("admin").await;
+
+    let response = app.delete(&format!("/api/roles/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = app.delete(&format!("/api/roles/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn search_todos_ranks_title_matches_first() {
+    let app = TestApp::new().await;
+    app.post(
+        "/api/todos",
+        json!({ "title": "Walk the dog", "content": "Buy treats on the way" }),
+    )
+    .await;
+    app.post(
+        "/api/todos",
+        json!({ "title": "Buy milk", "content": "Two litres" }),
+    )
+    .await;
+    app.seed_todo("Write report").await;
+
+    let response = app.get("/api/todos/search?q=buying").await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["total"], 2);
+    assert_eq!(response.body["results"][0]["todo"]["title"], "Buy milk");
+    assert_eq!(
+        response.body["results"][0]["highlights"]["title"],
+        "<mark>Buy</mark> milk"
+    );
+    assert!(response.body["results"][0]["highlights"]["content"].is_null());
+    assert_eq!(
+        response.body["results"][1]["highlights"]["content"],
+        "<mark>Buy</mark> treats on the way"
+    );
+}
+
+#[tokio::test]
+async fn search_todos_paginates() {
+    let app = TestApp::new().await;
+    for title in ["Report one", "Report two", "Report three"] {
+        app.seed_todo(title).await;
+    }
+
+    let response = app.get("/api/todos/search?q=report&per_page=2").await;
+    assert_eq!(response.body["total"], 3);
+    assert_eq!(response.body["count"], 2);
+
+    let response = app
+        .get("/api/todos/search?q=report&per_page=2&page=2")
+        .await;
+    assert_eq!(response.body["page"], 2);
+    assert_eq!(response.body["count"], 1);
+}
+
+#[tokio::test]
+async fn search_requires_a_query() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/todos/search?q=%20").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/users/search?q=alice&page=0").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .get(&format!(
+            "/api/todos/search?q=milk&page={}&per_page=100",
+            usize::MAX
+        ))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.body["message"],
+        "Query parameter page is too large"
+    );
+}
+
+#[tokio::test]
+async fn search_users_by_name_and_email() {
+    let app = TestApp::new().await;
+    app.seed_user("Alice Smith", "alice@example.com").await;
+    app.seed_user("Bob Jones", "bob@work.org").await;
+
+    let response = app.get("/api/users/search?q=SMITH").await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["total"], 1);
+    assert_eq!(
+        response.body["results"][0]["user"]["email"],
+        "alice@example.com"
+    );
+    assert_eq!(
+        response.body["results"][0]["highlights"]["name"],
+        "Alice <mark>Smith</mark>"
+    );
+
+    let response = app.get("/api/users/search?q=work").await;
+    assert_eq!(response.body["results"][0]["user"]["name"], "Bob Jones");
+
+    let response = app.get("/api/users/search?q=carol").await;
+    assert_eq!(response.body["total"], 0);
+    assert_eq!(response.body["results"], json!([]));
+}
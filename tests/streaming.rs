@@ -0,0 +1,77 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn streams_users_as_ndjson_and_json() {
+    let app = TestApp::new().await;
+    let response = app.get("/api/users?stream=json").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.text, "[]");
+
+    let users = (0..501)
+        .map(
+            |i| json!({ "name": format!("User {}", i), "email": format!("user{}@example.com", i) }),
+        )
+        .collect::<Vec<_>>();
+    app.post("/api/users/bulk", json!(users)).await;
+
+    let response = app.get("/api/users?stream=ndjson").await;
+    assert_eq!(
+        response.content_type.as_deref(),
+        Some("application/x-ndjson")
+    );
+    let lines = response.text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 501);
+    let first: Value = serde_json::from_str(lines[0]).unwrap();
+    assert!(first["email"].as_str().unwrap().ends_with("@example.com"));
+
+    let response = app.get("/api/users?stream=json").await;
+    assert_eq!(response.content_type.as_deref(), Some("application/json"));
+    let users: Vec<Value> = serde_json::from_str(&response.text).unwrap();
+    assert_eq!(users.len(), 501);
+}
+
+#[tokio::test]
+async fn streamed_todos_keep_filters_across_pages() {
+    let app = TestApp::new().await;
+    let todos = (0..750)
+        .map(|i| json!({ "title": format!("Todo {}", i), "completed": i % 3 == 0 }))
+        .collect::<Vec<_>>();
+    let response = app.post("/api/todos/bulk", json!(todos)).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let response = app.get("/api/todos?stream=json&completed=false").await;
+    assert_eq!(response.status, StatusCode::OK);
+    let todos: Vec<Value> = serde_json::from_str(&response.text).unwrap();
+    assert_eq!(todos.len(), 500);
+    assert!(todos.iter().all(|todo| todo["completed"] == false));
+
+    let response = app.get("/api/todos?stream=ndjson&sort=due_at").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    // Without `stream` the usual document comes back
+    let response = app.get("/api/todos?completed=true").await;
+    assert_eq!(response.body["count"], 250);
+}
+
+#[tokio::test]
+async fn streams_roles_and_lists() {
+    let app = TestApp::new().await;
+    app.seed_role("admin").await;
+    app.seed_role("ops").await;
+    app.post("/api/lists", json!({ "name": "Home" })).await;
+
+    let response = app.get("/api/roles?stream=ndjson").await;
+    assert_eq!(response.text.lines().count(), 2);
+
+    let response = app.get("/api/lists?stream=json").await;
+    let lists: Vec<Value> = serde_json::from_str(&response.text).unwrap();
+    assert_eq!(lists.len(), 1);
+    assert_eq!(lists[0]["name"], "Home");
+
+    let response = app.get("/api/lists?stream=xml").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{record_id, TestApp};
+use serde_json::json;
+
+async fn seed_subtask(app: &TestApp, parent_id: &str, title: &str) -> String {
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": title, "parent_id": parent_id }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    record_id(&response.body["todo"])
+}
+
+#[tokio::test]
+async fn subtasks_are_listed_under_their_parent() {
+    let app = TestApp::new().await;
+    let parent = app.seed_todo("Move house").await;
+    seed_subtask(&app, &parent, "Pack books").await;
+    seed_subtask(&app, &parent, "Hire van").await;
+    app.seed_todo("Unrelated").await;
+
+    let response = app.get(&format!("/api/todos/{}/subtasks", parent)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Orphan", "parent_id": "missing" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/todos/missing/subtasks").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn parent_cannot_be_completed_while_subtasks_are_open() {
+    let app = TestApp::new().await;
+    let parent = app.seed_todo("Move house").await;
+    let subtask = seed_subtask(&app, &parent, "Pack books").await;
+    let uri = format!("/api/todos/{}", parent);
+
+    let response = app.put(&uri, json!({ "completed": true })).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.body["open_subtasks"], json!([subtask]));
+
+    let response = app
+        .put(&format!("{}?force=true", uri), json!({ "completed": true }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["todo"]["completed"], true);
+}
+
+#[tokio::test]
+async fn bulk_completion_counts_subtasks_in_the_same_batch() {
+    let app = TestApp::new().await;
+    let parent = app.seed_todo("Move house").await;
+    let subtask = seed_subtask(&app, &parent, "Pack books").await;
+
+    let response = app
+        .patch(
+            "/api/todos/bulk",
+            json!([{ "id": parent, "completed": true }]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT, "{:?}", response.body);
+
+    let response = app
+        .patch(
+            "/api/todos/bulk",
+            json!([
+                { "id": parent, "completed": true },
+                { "id": subtask, "completed": true },
+            ]),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+}
+
+#[tokio::test]
+async fn parent_links_cannot_loop() {
+    let app = TestApp::new().await;
+    let top = app.seed_todo("Top").await;
+    let middle = seed_subtask(&app, &top, "Middle").await;
+    let bottom = seed_subtask(&app, &middle, "Bottom").await;
+
+    let response = app
+        .put(
+            &format!("/api/todos/{}", top),
+            json!({ "parent_id": bottom }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    let response = app
+        .put(&format!("/api/todos/{}", top), json!({ "parent_id": top }))
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    // Deleting a parent detaches its subtasks
+    app.delete(&format!("/api/todos/{}", middle)).await;
+    let response = app.get(&format!("/api/todos/{}", bottom)).await;
+    assert!(response.body["parent"].is_null(), "{:?}", response.body);
+}
+
+#[tokio::test]
+async fn blockers_are_reported_until_completed() {
+    let app = TestApp::new().await;
+    let todo = app.seed_todo("Paint walls").await;
+    let first = app.seed_todo("Buy paint").await;
+    let second = app.seed_todo("Tape edges").await;
+    let uri = format!("/api/todos/{}/blockers", todo);
+
+    let response = app.post(&uri, json!({ "blocker_id": first })).await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    let response = app.post(&uri, json!({ "blocker_id": second })).await;
+    assert_eq!(response.body["open_blockers"].as_array().unwrap().len(), 2);
+
+    let response = app.post(&uri, json!({ "blocker_id": first })).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    app.put(
+        &format!("/api/todos/{}", first),
+        json!({ "completed": true }),
+    )
+    .await;
+    let response = app.get(&format!("/api/todos/{}", todo)).await;
+    assert_eq!(response.body["title"], "Paint walls");
+    let open = response.body["open_blockers"].as_array().unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0]["title"], "Tape edges");
+
+    let response = app.get(&uri).await;
+    assert_eq!(response.body["count"], 2);
+
+    let response = app.delete(&format!("{}/{}", uri, second)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app.delete(&format!("{}/{}", uri, second)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post(&uri, json!({ "blocker_id": "missing" })).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    let response = app.post(&uri, json!({ "blocker_id": todo })).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn dependency_cycles_are_rejected() {
+    let app = TestApp::new().await;
+    let a = app.seed_todo("A").await;
+    let b = app.seed_todo("B").await;
+    let c = app.seed_todo("C").await;
+
+    for (todo, blocker) in [(&a, &b), (&b, &c)] {
+        let response = app
+            .post(
+                &format!("/api/todos/{}/blockers", todo),
+                json!({ "blocker_id": blocker }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::CREATED);
+    }
+
+    let response = app
+        .post(
+            &format!("/api/todos/{}/blockers", c),
+            json!({ "blocker_id": a }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.body["cycle"], json!([a, b, c]));
+
+    // Deleting a todo drops its edges, so the chain no longer loops
+    app.delete(&format!("/api/todos/{}", b)).await;
+    let response = app
+        .post(
+            &format!("/api/todos/{}/blockers", c),
+            json!({ "blocker_id": a }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+}
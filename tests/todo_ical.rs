@@ -0,0 +1,177 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Local, TimeZone};
+use common::{record_id, TestApp};
+use serde_json::json;
+
+const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Example//Tasks//EN\r
+BEGIN:VTODO\r
+UID:1@example.com\r
+SUMMARY:File taxes\\, finally\r
+DESCRIPTION:Forms are in\\nthe drawer\r
+DUE:20300415T170000Z\r
+PRIORITY:1\r
+STATUS:NEEDS-ACTION\r
+CATEGORIES:Home,Money\r
+END:VTODO\r
+BEGIN:VTODO\r
+UID:2@example.com\r
+SUMMARY:Weekly report\r
+DUE;TZID=Europe/Paris:20300107T090000\r
+RRULE:FREQ=WEEKLY\r
+STATUS:COMPLETED\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+#[tokio::test]
+async fn import_maps_vtodo_properties() {
+    let app = TestApp::new().await;
+    let response = app
+        .post_text("/api/todos/import", "text/calendar", CALENDAR)
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(response.body["succeeded"], 2);
+
+    let response = app.get("/api/todos?sort=due_at").await;
+    let todos = response.body["todos"].as_array().unwrap();
+    assert_eq!(todos.len(), 2);
+
+    let report = &todos[0];
+    assert_eq!(report["title"], "Weekly report");
+    assert_eq!(report["completed"], true);
+    assert_eq!(report["recurrence"], "FREQ=WEEKLY");
+    assert_eq!(report["priority"], "normal");
+
+    let taxes = &todos[1];
+    assert_eq!(taxes["title"], "File taxes, finally");
+    assert_eq!(taxes["content"], "Forms are in\nthe drawer");
+    assert_eq!(taxes["priority"], "urgent");
+    assert_eq!(taxes["completed"], false);
+    assert_eq!(taxes["tags"], json!(["home", "money"]));
+    assert_eq!(
+        taxes["due_at"].as_str().map(|due_at| {
+            chrono::DateTime::parse_from_rfc3339(due_at)
+                .unwrap()
+                .to_utc()
+                .to_rfc3339()
+        }),
+        Some("2030-04-15T17:00:00+00:00".to_string())
+    );
+}
+
+#[tokio::test]
+async fn import_rejects_bad_calendars_and_todos() {
+    let app = TestApp::new().await;
+    let response = app
+        .post_text("/api/todos/import", "text/calendar", "not a calendar")
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_text(
+            "/api/todos/import",
+            "text/calendar",
+            "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n",
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let calendar = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VTODO\r\nSUMMARY:Good\r\nEND:VTODO\r\n\
+        BEGIN:VTODO\r\nSUMMARY:Bad\r\nRRULE:FREQ=SOMETIMES\r\nDUE:20300101T090000Z\r\nEND:VTODO\r\n\
+        END:VCALENDAR\r\n";
+    let response = app
+        .post_text("/api/todos/import", "text/calendar", calendar)
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(app.get("/api/todos").await.body["count"], 0);
+
+    let response = app
+        .post_text("/api/todos/import?mode=partial", "text/calendar", calendar)
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    assert_eq!(app.get("/api/todos").await.body["count"], 1);
+}
+
+#[tokio::test]
+async fn export_renders_vtodos() {
+    let app = TestApp::new().await;
+    let due_at = Local.with_ymd_and_hms(2030, 1, 7, 9, 0, 0).unwrap();
+    let response = app
+        .post(
+            "/api/todos",
+            json!({
+                "title": "Pay rent; utilities",
+                "content": "",
+                "due_at": due_at,
+                "priority": "high",
+                "tags": ["home"],
+                "recurrence": "FREQ=MONTHLY",
+            }),
+        )
+        .await;
+    let id = record_id(&response.body["todo"]);
+    let done = app.seed_todo("Done already").await;
+    app.put(
+        &format!("/api/todos/{}", done),
+        json!({ "completed": true }),
+    )
+    .await;
+
+    let response = app.get("/api/todos.ics").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.content_type.as_deref(),
+        Some("text/calendar; charset=utf-8")
+    );
+    let text = response.text;
+    assert!(text.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert_eq!(text.matches("BEGIN:VTODO").count(), 2);
+    assert!(text.contains(&format!("UID:{}@rss-boilerplate\r\n", id)));
+    assert!(text.contains("SUMMARY:Pay rent\\; utilities\r\n"));
+    assert!(!text.contains("DESCRIPTION:\r\n"));
+    assert!(text.contains(&format!(
+        "DUE:{}\r\n",
+        due_at.to_utc().format("%Y%m%dT%H%M%SZ")
+    )));
+    assert!(text.contains("PRIORITY:3\r\n"));
+    assert!(text.contains("CATEGORIES:home\r\n"));
+    assert!(text.contains("RRULE:FREQ=MONTHLY\r\n"));
+    assert!(text.contains("STATUS:COMPLETED\r\n"));
+    assert!(text.ends_with("END:VCALENDAR\r\n"));
+
+    let response = app.get("/api/todos.ics?completed=false").await;
+    assert_eq!(response.text.matches("BEGIN:VTODO").count(), 1);
+
+    // Exported calendars import back
+    let response = app
+        .post_text("/api/todos/import", "text/calendar", &text)
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(app.get("/api/todos").await.body["count"], 4);
+}
+
+#[tokio::test]
+async fn list_feed_only_has_members() {
+    let app = TestApp::new().await;
+    let response = app.post("/api/lists", json!({ "name": "Home" })).await;
+    let list = record_id(&response.body["list"]);
+    app.post(
+        "/api/todos",
+        json!({ "title": "Water plants", "list_id": list }),
+    )
+    .await;
+    app.seed_todo("Elsewhere").await;
+
+    let response = app.get(&format!("/api/lists/{}/todos.ics", list)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.text.matches("BEGIN:VTODO").count(), 1);
+    assert!(response.text.contains("SUMMARY:Water plants"));
+
+    let response = app.get("/api/lists/missing/todos.ics").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Local, TimeZone};
+use common::{record_id, TestApp};
+use serde_json::{json, Value};
+
+fn at(day: u32, hour: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2030, 1, day, hour, 0, 0).unwrap()
+}
+
+fn time(value: &Value) -> DateTime<Local> {
+    DateTime::parse_from_rfc3339(value.as_str().unwrap())
+        .unwrap()
+        .with_timezone(&Local)
+}
+
+#[tokio::test]
+async fn completing_a_recurring_todo_creates_the_next_occurrence() {
+    let app = TestApp::new().await;
+    // 2030-01-07 is a Monday
+    let response = app
+        .post(
+            "/api/todos",
+            json!({
+                "title": "Weekly report",
+                "tags": ["work"],
+                "due_at": at(7, 17),
+                "remind_at": at(7, 9),
+                "recurrence": "rrule:freq=weekly;byday=mo,th;count=3",
+            }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(
+        response.body["todo"]["recurrence"],
+        "FREQ=WEEKLY;COUNT=3;BYDAY=MO,TH"
+    );
+    let id = record_id(&response.body["todo"]);
+
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    let next = &response.body["next_occurrence"];
+    assert_eq!(next["title"], "Weekly report");
+    assert_eq!(next["completed"], false);
+    assert_eq!(next["tags"], json!(["work"]));
+    assert_eq!(time(&next["due_at"]), at(10, 17));
+    assert_eq!(time(&next["remind_at"]), at(10, 9));
+    assert_eq!(next["recurrence"], "FREQ=WEEKLY;COUNT=2;BYDAY=MO,TH");
+
+    assert_eq!(response.body["todo"]["next_occurrence"], next["id"]);
+
+    // Saving an already completed todo does not spawn another one
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    assert!(response.body.get("next_occurrence").is_none());
+
+    // Nor does reopening and completing it again
+    app.put(&format!("/api/todos/{}", id), json!({ "completed": false }))
+        .await;
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body.get("next_occurrence").is_none());
+
+    // The last occurrence of the series has no successor
+    let next_id = record_id(next);
+    let response = app
+        .put(
+            &format!("/api/todos/{}", next_id),
+            json!({ "completed": true }),
+        )
+        .await;
+    let last = &response.body["next_occurrence"];
+    assert_eq!(time(&last["due_at"]), at(14, 17));
+    let response = app
+        .put(
+            &format!("/api/todos/{}", record_id(last)),
+            json!({ "completed": true }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body.get("next_occurrence").is_none());
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 3);
+}
+
+#[tokio::test]
+async fn completing_recurring_todos_in_bulk_creates_each_next_occurrence_once() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Water plants", "due_at": at(7, 8), "recurrence": "FREQ=DAILY" }),
+        )
+        .await;
+    let recurring = record_id(&response.body["todo"]);
+    let plain = app.seed_todo("Buy milk").await;
+
+    let complete = json!([
+        { "id": recurring, "completed": true },
+        { "id": plain, "completed": true },
+    ]);
+    let response = app.patch("/api/todos/bulk", complete.clone()).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 3);
+    let todos = response.body["todos"].as_array().unwrap();
+    let next = todos
+        .iter()
+        .find(|todo| todo["completed"] == false)
+        .expect("next occurrence");
+    assert_eq!(next["title"], "Water plants");
+    assert_eq!(time(&next["due_at"]), at(8, 8));
+    let completed = todos
+        .iter()
+        .find(|todo| record_id(todo) == recurring)
+        .unwrap();
+    assert_eq!(completed["next_occurrence"], next["id"]);
+
+    let reopen = json!([{ "id": recurring, "completed": false }]);
+    app.patch("/api/todos/bulk", reopen).await;
+    app.patch("/api/todos/bulk", complete).await;
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.body["count"], 3);
+}
+
+#[tokio::test]
+async fn preview_occurrences_and_stop_the_series() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos",
+            json!({
+                "title": "Month-end review",
+                "due_at": at(31, 10),
+                "recurrence": "FREQ=MONTHLY;BYMONTHDAY=-1",
+            }),
+        )
+        .await;
+    let id = record_id(&response.body["todo"]);
+
+    let response = app
+        .get(&format!("/api/todos/{}/occurrences?count=3", id))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["count"], 3);
+    let days: Vec<String> = response.body["occurrences"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| time(value).format("%Y-%m-%d").to_string())
+        .collect();
+    assert_eq!(days, vec!["2030-02-28", "2030-03-31", "2030-04-30"]);
+
+    let response = app.delete(&format!("/api/todos/{}/recurrence", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body["todo"]["recurrence"].is_null());
+
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    assert!(response.body.get("next_occurrence").is_none());
+
+    let response = app.delete(&format!("/api/todos/{}/recurrence", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get(&format!("/api/todos/{}/occurrences", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invalid_recurrence_is_rejected() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Undated", "recurrence": "FREQ=DAILY" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Hourly", "due_at": at(7, 9), "recurrence": "FREQ=HOURLY" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let id = app.seed_todo("Plain").await;
+    let response = app
+        .put(
+            &format!("/api/todos/{}", id),
+            json!({ "recurrence": "FREQ=WEEKLY" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app
+        .put(
+            &format!("/api/todos/{}", id),
+            json!({ "due_at": at(7, 9), "recurrence": "FREQ=WEEKLY" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["todo"]["recurrence"], "FREQ=WEEKLY");
+}
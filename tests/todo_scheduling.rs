@@ -0,0 +1,197 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Local};
+use common::TestApp;
+use rss_boilerplate::events::Event;
+use rss_boilerplate::reminders;
+use serde_json::{json, Value};
+
+fn titles(body: &Value) -> Vec<String> {
+    body["todos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|todo| todo["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn create_todo_with_due_date_and_priority() {
+    let app = TestApp::new().await;
+    let due_at = Local::now() + Duration::days(1);
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "File taxes", "priority": "urgent", "due_at": due_at }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(response.body["todo"]["priority"], "urgent");
+    assert!(response.body["todo"]["due_at"].is_string());
+    assert!(response.body["todo"]["completed_at"].is_null());
+
+    let response = app.post("/api/todos", json!({ "title": "Plain" })).await;
+    assert_eq!(response.body["todo"]["priority"], "normal");
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Bad", "priority": "someday" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn completed_at_follows_completed() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+    let uri = format!("/api/todos/{}", id);
+
+    let response = app.put(&uri, json!({ "completed": true })).await;
+    let completed_at = response.body["todo"]["completed_at"].clone();
+    assert!(completed_at.is_string());
+
+    // Saving again without flipping keeps the original timestamp
+    let response = app.put(&uri, json!({ "completed": true })).await;
+    assert_eq!(response.body["todo"]["completed_at"], completed_at);
+
+    let response = app.put(&uri, json!({ "completed": false })).await;
+    assert!(response.body["todo"]["completed_at"].is_null());
+
+    let response = app
+        .patch("/api/todos/bulk", json!([{ "id": id, "completed": true }]))
+        .await;
+    assert!(response.body["results"][0]["todo"]["completed_at"].is_string());
+}
+
+#[tokio::test]
+async fn list_filters_overdue_and_due_before() {
+    let app = TestApp::new().await;
+    let now = Local::now();
+    for (title, due_at, completed) in [
+        ("Late", now - Duration::days(2), false),
+        ("Late but done", now - Duration::days(1), true),
+        ("Soon", now + Duration::hours(2), false),
+        ("Later", now + Duration::days(7), false),
+    ] {
+        let response = app
+            .post(
+                "/api/todos",
+                json!({ "title": title, "due_at": due_at, "completed": completed }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::CREATED);
+    }
+    app.seed_todo("Undated").await;
+
+    let response = app.get("/api/todos?overdue=true").await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(titles(&response.body), vec!["Late"]);
+
+    let due_before = (now + Duration::days(1)).to_rfc3339();
+    let response = app
+        .get(&format!(
+            "/api/todos?due_before={}&completed=false&sort=due_at",
+            due_before.replace('+', "%2B")
+        ))
+        .await;
+    assert_eq!(titles(&response.body), vec!["Late", "Soon"]);
+
+    let response = app.get("/api/todos?overdue=yes").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn list_sorts_by_priority_and_due_date() {
+    let app = TestApp::new().await;
+    let now = Local::now();
+    for (title, priority, days) in [
+        ("Normal", "normal", 3),
+        ("Urgent", "urgent", 5),
+        ("Low", "low", 1),
+        ("High", "high", 2),
+    ] {
+        app.post(
+            "/api/todos",
+            json!({ "title": title, "priority": priority, "due_at": now + Duration::days(days) }),
+        )
+        .await;
+    }
+
+    let response = app.get("/api/todos?sort=priority").await;
+    assert_eq!(
+        titles(&response.body),
+        vec!["Urgent", "High", "Normal", "Low"]
+    );
+
+    let response = app.get("/api/todos?sort=priority&order=asc").await;
+    assert_eq!(
+        titles(&response.body),
+        vec!["Low", "Normal", "High", "Urgent"]
+    );
+
+    let response = app.get("/api/todos?sort=due_at").await;
+    assert_eq!(
+        titles(&response.body),
+        vec!["Low", "High", "Normal", "Urgent"]
+    );
+
+    let response = app.get("/api/todos?priority=high").await;
+    assert_eq!(titles(&response.body), vec!["High"]);
+}
+
+#[tokio::test]
+async fn due_reminders_fire_once_and_publish_events() {
+    let app = TestApp::new().await;
+    let now = Local::now();
+    app.post(
+        "/api/todos",
+        json!({ "title": "Call mum", "remind_at": now - Duration::minutes(1) }),
+    )
+    .await;
+    app.post(
+        "/api/todos",
+        json!({ "title": "Done already", "remind_at": now - Duration::minutes(1), "completed": true }),
+    )
+    .await;
+    let later = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Next week", "remind_at": now + Duration::days(7) }),
+        )
+        .await;
+
+    let mut events = app.state.events.subscribe();
+    let todos = app.state.data.todos();
+    let fired = reminders::fire_due(todos.as_ref(), &app.state.events)
+        .await
+        .unwrap();
+    assert_eq!(fired, 1);
+    match events.try_recv().unwrap() {
+        Event::ReminderFired { todo } => {
+            assert_eq!(todo.title, "Call mum");
+            assert!(todo.reminder_fired_at.is_some());
+        }
+        other => panic!("Expected a reminder, got {:?}", other),
+    }
+
+    let fired = reminders::fire_due(todos.as_ref(), &app.state.events)
+        .await
+        .unwrap();
+    assert_eq!(fired, 0);
+
+    // Moving a reminder into the past re-arms it
+    let id = common::record_id(&later.body["todo"]);
+    app.put(
+        &format!("/api/todos/{}", id),
+        json!({ "remind_at": now - Duration::seconds(5) }),
+    )
+    .await;
+    let fired = reminders::fire_due(todos.as_ref(), &app.state.events)
+        .await
+        .unwrap();
+    assert_eq!(fired, 1);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{record_id, TestApp};
+use serde_json::{json, Value};
+
+async fn seed_tagged(app: &TestApp, title: &str, tags: &[&str]) -> String {
+    let response = app
+        .post("/api/todos", json!({ "title": title, "tags": tags }))
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    record_id(&response.body["todo"])
+}
+
+fn titles(body: &Value) -> Vec<String> {
+    let mut titles: Vec<String> = body["todos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|todo| todo["title"].as_str().unwrap().to_string())
+        .collect();
+    titles.sort();
+    titles
+}
+
+#[tokio::test]
+async fn tags_are_normalised_on_create_and_update() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Pay rent", "tags": [" Home ", "home", "Bills"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["todo"]["tags"], json!(["home", "bills"]));
+    let id = record_id(&response.body["todo"]);
+
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "tags": ["URGENT"] }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["todo"]["tags"], json!(["urgent"]));
+
+    let response = app
+        .post("/api/todos", json!({ "title": "Blank", "tags": ["  "] }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let long = "x".repeat(51);
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "tags": [long] }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn add_and_remove_single_tags() {
+    let app = TestApp::new().await;
+    let id = seed_tagged(&app, "Pay rent", &["home"]).await;
+
+    let response = app
+        .post(
+            &format!("/api/todos/{}/tags", id),
+            json!({ "tags": ["Bills", "home"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["todo"]["tags"], json!(["home", "bills"]));
+
+    let response = app.delete(&format!("/api/todos/{}/tags/HOME", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["todo"]["tags"], json!(["bills"]));
+
+    let response = app.delete(&format!("/api/todos/{}/tags/home", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post("/api/todos/missing/tags", json!({ "tags": ["home"] }))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post(&format!("/api/todos/{}/tags", id), json!({ "tags": [""] }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn filter_by_all_or_any_tags() {
+    let app = TestApp::new().await;
+    seed_tagged(&app, "Pay rent", &["home", "bills"]).await;
+    seed_tagged(&app, "Water plants", &["home"]).await;
+    seed_tagged(&app, "Expense report", &["work", "bills"]).await;
+    app.seed_todo("Untagged").await;
+
+    let response = app.get("/api/todos?tag=home&tag=Bills").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(titles(&response.body), vec!["Pay rent"]);
+
+    let response = app.get("/api/todos?tag=home&tag=work&tag_match=any").await;
+    assert_eq!(
+        titles(&response.body),
+        vec!["Expense report", "Pay rent", "Water plants"]
+    );
+
+    let response = app.get("/api/todos?tag=bills&sort=created_at").await;
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn tag_counts_are_ordered_by_usage() {
+    let app = TestApp::new().await;
+    seed_tagged(&app, "Pay rent", &["home", "bills"]).await;
+    seed_tagged(&app, "Water plants", &["home"]).await;
+    seed_tagged(&app, "Expense report", &["work", "bills"]).await;
+    seed_tagged(&app, "Call mum", &["home"]).await;
+
+    let response = app.get("/api/tags").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 3);
+    assert_eq!(
+        response.body["tags"],
+        json!([
+            { "tag": "home", "count": 3 },
+            { "tag": "bills", "count": 2 },
+            { "tag": "work", "count": 1 },
+        ])
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn create_and_list_todos() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/todos",
+            json!({ "title": "Buy milk", "content": "2 litres" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["todo"]["title"], "Buy milk");
+    assert_eq!(response.body["todo"]["completed"], false);
+
+    app.seed_todo("Walk dog").await;
+    let response = app.get("/api/todos").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn get_todo_by_id_and_title() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app.get(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["title"], "Write report");
+
+    let response = app.get("/api/todos/title/Write%20report").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["content"], "Seeded");
+}
+
+#[tokio::test]
+async fn missing_todo_lookups_return_404() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/todos/missing").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.body["status"], "error");
+    assert!(response.body["request_id"].is_string());
+
+    let response = app.get("/api/todos/title/nothing").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_todo_merges_fields() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app
+        .put(&format!("/api/todos/{}", id), json!({ "completed": true }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["todo"]["title"], "Write report");
+    assert_eq!(response.body["todo"]["content"], "Seeded");
+    assert_eq!(response.body["todo"]["completed"], true);
+}
+
+#[tokio::test]
+async fn update_missing_todo_returns_404() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .put("/api/todos/missing", json!({ "title": "Renamed" }))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.body["message"], "Todo with ID: missing not found");
+}
+
+#[tokio::test]
+async fn delete_todo() {
+    let app = TestApp::new().await;
+    let id = app.seed_todo("Write report").await;
+
+    let response = app.delete(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = app.get(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.delete(&format!("/api/todos/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn create_todo_rejects_invalid_body() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post("/api/todos", json!({ "content": "no title" }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.post("/api/todos", json!({ "title": "" })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.body["errors"]["title"].is_array());
+}
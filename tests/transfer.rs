@@ -0,0 +1,170 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{record_id, TestApp};
+use serde_json::json;
+
+#[tokio::test]
+async fn export_streams_every_page() {
+    let app = TestApp::new().await;
+    let users = (0..501)
+        .map(
+            |i| json!({ "name": format!("User {}", i), "email": format!("user{}@example.com", i) }),
+        )
+        .collect::<Vec<_>>();
+    let response = app.post("/api/users/bulk", json!(users)).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let response = app.get("/api/users/export").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.content_type.as_deref(),
+        Some("text/csv; charset=utf-8")
+    );
+    let lines = response.text.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "id,name,email,phone,role_id,created_at,updated_at"
+    );
+    assert_eq!(lines.len(), 502);
+
+    let response = app.get("/api/users/export?format=ndjson").await;
+    assert_eq!(
+        response.content_type.as_deref(),
+        Some("application/x-ndjson")
+    );
+    let emails = response
+        .text
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["email"].clone())
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(emails.len(), 501);
+}
+
+#[tokio::test]
+async fn todo_export_imports_back() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/todos",
+            json!({
+                "title": "Pay rent, utilities",
+                "content": "Line one\nLine \"two\"",
+                "tags": ["home", "money"],
+                "priority": "high",
+            }),
+        )
+        .await;
+    let id = record_id(&response.body["todo"]);
+
+    let export = app.get("/api/todos/export").await.text;
+    assert!(export.contains("\"Pay rent, utilities\""));
+    assert!(export.contains("\"home,money\""));
+
+    // Rows with a known id update in place, others create
+    let edited = export.replace("Pay rent, utilities", "Pay rent");
+    let response = app
+        .post_text("/api/todos/import", "text/csv", &edited)
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["updated"], 1);
+    assert_eq!(response.body["rows"][0]["action"], "update");
+    assert_eq!(response.body["rows"][0]["id"], id.as_str());
+
+    let todo = &app.get(&format!("/api/todos/{}", id)).await.body;
+    assert_eq!(todo["title"], "Pay rent");
+    assert_eq!(todo["content"], "Line one\nLine \"two\"");
+    assert_eq!(todo["tags"], json!(["home", "money"]));
+
+    let response = app
+        .post_text(
+            "/api/todos/import?format=ndjson",
+            "text/plain",
+            "{\"title\":\"New one\",\"tags\":[\"Work\"]}\n",
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["created"], 1);
+    assert_eq!(app.get("/api/todos?tag=work").await.body["count"], 1);
+}
+
+#[tokio::test]
+async fn import_upserts_users_by_email() {
+    let app = TestApp::new().await;
+    let admin = app.seed_role("admin").await;
+    let existing = app.seed_user("Alice", "alice@example.com").await;
+
+    let csv = format!(
+        "name,email,role_id\n\
+         Alice Smith,alice@EXAMPLE.com,{}\n\
+         Bob,bob@example.com,\n\
+         ,carol@example.com,\n\
+         Dan,dan@example.com,missing\n\
+         Bobby,bob@example.com,\n",
+        admin
+    );
+    let response = app
+        .post_text("/api/users/import?dry_run=true", "text/csv", &csv)
+        .await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    assert_eq!(response.body["dry_run"], true);
+    assert_eq!(response.body["updated"], 1);
+    assert_eq!(response.body["created"], 1);
+    assert_eq!(response.body["failed"], 3);
+    let rows = response.body["rows"].as_array().unwrap();
+    assert_eq!(rows[0]["id"], existing.as_str());
+    assert_eq!(rows[2]["status"], 422);
+    assert!(rows[2]["message"].as_str().unwrap().contains("`name`"));
+    assert_eq!(rows[3]["message"], "Role with ID: missing not found");
+    assert_eq!(
+        rows[4]["message"],
+        "Duplicate email: bob@example.com in import"
+    );
+    assert_eq!(app.get("/api/users").await.body["count"], 1);
+
+    let response = app.post_text("/api/users/import", "text/csv", &csv).await;
+    assert_eq!(response.status, StatusCode::MULTI_STATUS);
+    assert_eq!(app.get("/api/users").await.body["count"], 2);
+    let alice = &app.get(&format!("/api/users/{}", existing)).await.body;
+    assert_eq!(alice["name"], "Alice Smith");
+    assert_eq!(alice["role"]["id"]["String"], admin.as_str());
+}
+
+#[tokio::test]
+async fn import_rejects_unusable_files() {
+    let app = TestApp::new().await;
+    app.seed_role("admin").await;
+
+    let response = app
+        .post_text("/api/roles/import", "text/plain", "name\nops\n")
+        .await;
+    assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = app
+        .post_text("/api/roles/import", "text/csv", "name,colour\nops,red\n")
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_text("/api/roles/import", "text/csv", "name\n")
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_text(
+            "/api/roles/import",
+            "application/x-ndjson",
+            "{\"name\":\"\"}\n{\"name\":\"ops\",\"level\":3}\n",
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["failed"], 2);
+
+    let response = app
+        .post_text("/api/roles/import", "text/csv", "name\nadmin\nops\n")
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["updated"], 1);
+    assert_eq!(response.body["created"], 1);
+    assert_eq!(app.get("/api/roles").await.body["count"], 2);
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn create_and_list_users() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/users",
+            json!({ "name": "Alice", "email": "alice@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["user"]["email"], "alice@example.com");
+    assert!(response.body["user"]["created_at"].is_string());
+
+    app.seed_user("Bob", "bob@example.com").await;
+    let response = app.get("/api/users").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["count"], 2);
+}
+
+#[tokio::test]
+async fn create_user_rejects_duplicate_email() {
+    let app = TestApp::new().await;
+    app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app
+        .post(
+            "/api/users",
+            json!({ "name": "Impostor", "email": "alice@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["message"], "User already exists");
+}
+
+#[tokio::test]
+async fn get_user_by_id_email_and_phone() {
+    let app = TestApp::new().await;
+    let id = app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app.get(&format!("/api/users/{}", id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+
+    let response = app.get("/api/users/email/alice@example.com").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+
+    let response = app.get("/api/users/phone/+15555550100").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+}
+
+#[tokio::test]
+async fn missing_user_lookups_return_404() {
+    let app = TestApp::new().await;
+
+    for uri in [
+        "/api/users/missing",
+        "/api/users/email/nobody@example.com",
+        "/api/users/phone/+15555550199",
+    ] {
+        let response = app.get(uri).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", uri);
+        assert_eq!(response.body["status"], "error");
+    }
+}
+
+#[tokio::test]
+async fn update_user_replaces_fields() {
+    let app = TestApp::new().await;
+    let id = app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app
+        .put(
+            &format!("/api/users/{}", id),
+            json!({ "name": "Alice Smith", "email": "alice.smith@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["user"]["name"], "Alice Smith");
+    assert_eq!(response.body["user"]["email"], "alice.smith@example.com");
+    assert!(response.body["user"]["phone"].is_null());
+}
+
+#[tokio::test]
+async fn update_missing_user_returns_404() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .put(
+            "/api/users/missing",
+            json!({ "name": "Ghost", "email": "ghost@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.body["message"], "User with ID: missing not found");
+}
+
+#[tokio::test]
+async fn delete_user() {
+    let app = TestApp::new().await;
+    let id = app.seed_user("Alice", "alice@example.com").await;
+
+    let response = app.delete(&format!("/api/users/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = app.delete(&format!("/api/users/{}", id)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn emails_and_phones_are_normalised() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/users",
+            json!({ "name": "Alice", "email": " Alice@Example.COM ", "phone": "+1 (555) 555-0123" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert_eq!(response.body["user"]["email"], "Alice@example.com");
+    assert_eq!(response.body["user"]["phone"], "+15555550123");
+
+    let response = app.get("/api/users/email/Alice@EXAMPLE.com").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+
+    let response = app.get("/api/users/phone/+1%20555-555-0123").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+
+    let response = app
+        .post(
+            "/api/users",
+            json!({ "name": "Impostor", "email": " Alice@example.COM " }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn invalid_phone_is_rejected() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/users",
+            json!({ "name": "Alice", "email": "alice@example.com", "phone": "call me" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["message"], "Invalid phone number: call me");
+}
+
+#[tokio::test]
+async fn create_user_validates_like_bulk_create() {
+    let app = TestApp::new().await;
+
+    let user = json!({ "name": "", "email": "not an email" });
+    let response = app.post("/api/users", user.clone()).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["message"], "Validation failed");
+    assert!(response.body["errors"]["name"].is_array());
+    assert!(response.body["errors"]["email"].is_array());
+
+    let response = app.post("/api/users/bulk", json!([user])).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn update_user_cannot_take_another_users_email() {
+    let app = TestApp::new().await;
+    app.seed_user("Alice", "alice@example.com").await;
+    let bob = app.seed_user("Bob", "bob@example.com").await;
+
+    let response = app
+        .put(
+            &format!("/api/users/{}", bob),
+            json!({ "name": "Bob", "email": "alice@Example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["message"], "Email already in use");
+
+    let response = app
+        .put(
+            &format!("/api/users/{}", bob),
+            json!({ "name": "Robert", "email": "bob@EXAMPLE.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["user"]["email"], "bob@example.com");
+}
@@ -0,0 +1,366 @@
+mod common;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use common::{record_id, TestApp};
+use rss_boilerplate::config::Config;
+use rss_boilerplate::outbox::{self, WebhookSink};
+use rss_boilerplate::retry::RetryPolicy;
+use rss_boilerplate::webhooks::{self, Dispatcher};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A local endpoint standing in for an external system: it records every
+/// request and answers with the status it is set to.
+#[derive(Clone)]
+struct StandIn {
+    received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    status: Arc<AtomicU16>,
+}
+
+impl StandIn {
+    async fn start(status: u16) -> (Self, String) {
+        let stand_in = StandIn {
+            received: Arc::default(),
+            status: Arc::new(AtomicU16::new(status)),
+        };
+        let router = Router::new()
+            .route("/hook", post(receive))
+            .with_state(stand_in.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (stand_in, url)
+    }
+
+    fn received(&self) -> Vec<(HeaderMap, Bytes)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn receive(State(stand_in): State<StandIn>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    stand_in.received.lock().unwrap().push((headers, body));
+    StatusCode::from_u16(stand_in.status.load(Ordering::SeqCst)).unwrap()
+}
+
+fn dispatcher(app: &TestApp, max_attempts: usize, backoff: Duration) -> Dispatcher {
+    Dispatcher::new(
+        app.state.data.webhooks(),
+        RetryPolicy {
+            max_attempts,
+            backoff,
+            max_backoff: backoff,
+            timeout: Duration::from_secs(5),
+        },
+        true,
+    )
+}
+
+/// Queues deliveries for the changes made so far, as the outbox dispatcher
+/// does in the background.
+async fn relay(app: &TestApp) {
+    outbox::Dispatcher::new(
+        app.state.data.outbox(),
+        vec![Arc::new(WebhookSink(app.state.data.webhooks()))],
+        outbox::DEFAULT_RETRY,
+    )
+    .publish_due()
+    .await
+    .unwrap();
+}
+
+async fn create_webhook(app: &TestApp, url: &str, events: Value) -> (String, String) {
+    let response = app
+        .post(
+            "/api/webhooks",
+            json!({ "url": url, "secret": "0123456789abcdef", "events": events }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    let secret = response.body["webhook"]["secret"].as_str().unwrap();
+    (record_id(&response.body["webhook"]), secret.to_string())
+}
+
+#[tokio::test]
+async fn webhooks_are_managed_without_exposing_secrets() {
+    let app = TestApp::new().await;
+    let response = app
+        .post(
+            "/api/webhooks",
+            json!({ "url": "not a url", "events": ["todo.archived"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(response.body["errors"]["url"].is_array());
+    assert!(response.body["errors"]["events"].is_array());
+
+    let response = app
+        .post(
+            "/api/webhooks",
+            json!({ "url": "https://example.com/hook", "events": ["user.created"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    assert!(response.body["webhook"]["secret"]
+        .as_str()
+        .unwrap()
+        .starts_with("whsec_"));
+    assert_eq!(response.body["webhook"]["active"], true);
+    let id = record_id(&response.body["webhook"]);
+    let uri = format!("/api/webhooks/{}", id);
+
+    let response = app.get(&uri).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body.get("secret").is_none());
+    let response = app.get("/api/webhooks").await;
+    assert_eq!(response.body["count"], 1);
+    assert!(response.body["webhooks"][0].get("secret").is_none());
+
+    let response = app
+        .put(
+            &uri,
+            json!({ "events": ["todo.completed"], "active": false }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(
+        response.body["webhook"]["events"],
+        json!(["todo.completed"])
+    );
+    assert_eq!(response.body["webhook"]["active"], false);
+    assert!(response.body["webhook"].get("secret").is_none());
+
+    let response = app.delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app.get(&uri).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn webhooks_are_off_by_default() {
+    let mut config = Config::from_env();
+    config.rate_limit.enabled = false;
+    config.webhooks_api = false;
+    let app = TestApp::with_config(config).await;
+
+    let response = app.get("/api/webhooks").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app
+        .post(
+            "/api/webhooks",
+            json!({ "url": "https://example.com/hook", "events": ["user.created"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get("/api/webhooks/deliveries").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn internal_and_non_http_targets_are_rejected() {
+    let mut config = Config::from_env();
+    config.rate_limit.enabled = false;
+    config.webhooks_api = true;
+    config.webhook_allow_private_targets = false;
+    let app = TestApp::with_config(config).await;
+
+    for url in [
+        "ftp://93.184.215.14/hook",
+        "http://127.0.0.1:8080/hook",
+        "http://localhost/hook",
+        "http://10.0.0.5/hook",
+        "http://192.168.1.1/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://0.0.0.0/hook",
+        "http://[::1]/hook",
+        "http://[::ffff:127.0.0.1]/hook",
+    ] {
+        let response = app
+            .post(
+                "/api/webhooks",
+                json!({ "url": url, "events": ["user.created"] }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", url);
+        assert!(response.body["errors"]["url"].is_array(), "{}", url);
+    }
+
+    let response = app
+        .post(
+            "/api/webhooks",
+            json!({ "url": "https://93.184.215.14/hook", "events": ["user.created"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED, "{:?}", response.body);
+    let uri = format!("/api/webhooks/{}", record_id(&response.body["webhook"]));
+    let response = app
+        .put(&uri, json!({ "url": "http://127.0.0.1/hook" }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn internal_targets_are_refused_at_send_time() {
+    let app = TestApp::new().await;
+    let (stand_in, url) = StandIn::start(204).await;
+    let (id, _) = create_webhook(&app, &url, json!(["role.created"])).await;
+    app.seed_role("admin").await;
+    relay(&app).await;
+    let strict = Dispatcher::new(
+        app.state.data.webhooks(),
+        RetryPolicy {
+            max_attempts: 1,
+            backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(60),
+            timeout: Duration::from_secs(5),
+        },
+        false,
+    );
+
+    assert_eq!(strict.deliver_due().await.unwrap(), 1);
+    assert!(stand_in.received().is_empty());
+    let response = app.get(&format!("/api/webhooks/{}/deliveries", id)).await;
+    let delivery = &response.body["deliveries"][0];
+    assert_eq!(delivery["status"], "dead");
+    assert!(delivery["attempts"][0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("internal address"));
+}
+
+#[tokio::test]
+async fn subscribed_events_are_delivered_signed() {
+    let app = TestApp::new().await;
+    let (stand_in, url) = StandIn::start(200).await;
+    let (id, secret) = create_webhook(&app, &url, json!(["user.created", "todo.completed"])).await;
+
+    app.seed_user("Ada", "ada@example.com").await;
+    let todo = app.seed_todo("Ship it").await;
+    app.put(
+        &format!("/api/todos/{}", todo),
+        json!({ "title": "Ship it" }),
+    )
+    .await;
+    app.put(
+        &format!("/api/todos/{}", todo),
+        json!({ "completed": true }),
+    )
+    .await;
+    relay(&app).await;
+
+    let attempted = dispatcher(&app, 3, Duration::ZERO)
+        .deliver_due()
+        .await
+        .unwrap();
+    assert_eq!(attempted, 2);
+    let mut received = stand_in.received();
+    received.sort_by_key(|(headers, _)| headers[webhooks::EVENT_HEADER].as_bytes().to_vec());
+    assert_eq!(received.len(), 2);
+    for (headers, body) in &received {
+        let timestamp = headers[webhooks::TIMESTAMP_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            headers[webhooks::SIGNATURE_HEADER],
+            webhooks::sign(&secret, timestamp, body).as_str()
+        );
+        assert_eq!(headers["content-type"], "application/json");
+    }
+    let (headers, body) = &received[0];
+    assert_eq!(headers[webhooks::EVENT_HEADER], "todo.completed");
+    let payload: Value = serde_json::from_slice(body).unwrap();
+    assert_eq!(payload["event"], "todo.completed");
+    assert_eq!(payload["data"]["title"], "Ship it");
+    assert_eq!(payload["data"]["completed"], true);
+    let payload: Value = serde_json::from_slice(&received[1].1).unwrap();
+    assert_eq!(payload["event"], "user.created");
+    assert_eq!(payload["data"]["email"], "ada@example.com");
+
+    let response = app.get(&format!("/api/webhooks/{}/deliveries", id)).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(response.body["total"], 2);
+    for delivery in response.body["deliveries"].as_array().unwrap() {
+        assert_eq!(delivery["status"], "delivered");
+        assert_eq!(delivery["attempts"][0]["status"], 200);
+        assert!(delivery["delivered_at"].is_string());
+    }
+    let attempted = dispatcher(&app, 3, Duration::ZERO)
+        .deliver_due()
+        .await
+        .unwrap();
+    assert_eq!(attempted, 0);
+}
+
+#[tokio::test]
+async fn failures_back_off_then_dead_letter_and_can_be_retried() {
+    let app = TestApp::new().await;
+    let (stand_in, url) = StandIn::start(500).await;
+    let (id, _) = create_webhook(&app, &url, json!(["role.created"])).await;
+    app.seed_role("admin").await;
+    relay(&app).await;
+    let slow = dispatcher(&app, 2, Duration::from_secs(60 * 60));
+
+    assert_eq!(slow.deliver_due().await.unwrap(), 1);
+    assert_eq!(
+        slow.deliver_due().await.unwrap(),
+        0,
+        "should be backing off"
+    );
+    let response = app.get(&format!("/api/webhooks/{}/deliveries", id)).await;
+    let delivery = response.body["deliveries"][0].clone();
+    assert_eq!(delivery["status"], "pending");
+    assert_eq!(delivery["attempts"][0]["status"], 500);
+    assert!(delivery["attempts"][0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("500"));
+    let next: DateTime<Local> = delivery["next_attempt_at"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(next > Local::now() + ChronoDuration::minutes(59));
+
+    let (_, delivery_id) = delivery["id"].as_str().unwrap().split_once(':').unwrap();
+    let retry = format!("/api/webhooks/deliveries/{}/retry", delivery_id);
+    let response = app.post(&retry, json!({})).await;
+    assert_eq!(response.status, StatusCode::OK, "{:?}", response.body);
+    assert_eq!(slow.deliver_due().await.unwrap(), 1);
+    let response = app.get("/api/webhooks/deliveries?status=dead").await;
+    assert_eq!(response.body["total"], 1);
+    assert_eq!(
+        response.body["deliveries"][0]["attempts"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(response.body["deliveries"][0]["next_attempt_at"].is_null());
+    assert_eq!(slow.deliver_due().await.unwrap(), 0);
+
+    stand_in.status.store(204, Ordering::SeqCst);
+    app.post(&retry, json!({})).await;
+    assert_eq!(slow.deliver_due().await.unwrap(), 1);
+    let response = app.get("/api/webhooks/deliveries?status=delivered").await;
+    assert_eq!(response.body["total"], 1);
+    assert_eq!(stand_in.received().len(), 3);
+
+    let response = app
+        .post("/api/webhooks/deliveries/missing/retry", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app.get("/api/webhooks/missing/deliveries").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = app
+        .get(&format!("/api/webhooks/deliveries?page={}", usize::MAX))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}